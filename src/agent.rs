@@ -1,25 +1,32 @@
-use crate::types::B;
-use burn::tensor::{Tensor, backend::Backend};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 pub struct Agent {
     pub epsilon: f32,
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
 }
 
 impl Agent {
     pub fn new(epsilon: f32) -> Self {
         Self {
             epsilon,
-            rng: rand::thread_rng(),
+            rng: StdRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    //시드를 고정해서 같은 입력이면 항상 같은 행동을 고르도록 함 (백테스트용)
+    pub fn with_seed(epsilon: f32, seed: u64) -> Self {
+        Self {
+            epsilon,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
     //현재 Q값을 바탕으로 행동을 선택(e-greedy)
     pub fn select_action(&mut self, q_array: &[f32]) -> usize {
         //확률적으로 무작위 행동 선택(탐험)
-        if self.rng.r#gen::<f32>() < self.epsilon {
-            return self.rng.gen_range(0..3);
+        if self.rng.random::<f32>() < self.epsilon {
+            return self.rng.random_range(0..3);
         }
 
         q_array
@@ -1,29 +1,68 @@
-use crate::websocket::{OrderBookData, OrderBookUnit, TickData};
-use std::collections::VecDeque;
+use crate::candles::{BarSpec, CandleEvent, CandleSet};
+use crate::indicators::IndicatorSet;
+use crate::schema::{DEPTH_FIELDS, parse_depth_name};
+use crate::websocket::{OrderBookData, TickData};
+use std::collections::{HashMap, VecDeque, vec_deque};
 
 /// 📦 실시간 Tick / OrderBook 데이터를 저장하는 순환 버퍼 구조
+/// 기본 피처는 최근 capacity개 틱, 시간 구간 피처는 retention 구간 안의 틱까지 사용
 pub struct MarketStorage {
     pub ticks: VecDeque<TickData>,
     pub orderbooks: VecDeque<OrderBookData>,
     pub capacity: usize,
+    pub retention: Option<TimeWindow>, // 이 구간 안의 틱은 capacity를 넘어도 유지 (가장 긴 시간 구간)
+    pub candles: CandleSet,            // 틱으로 만드는 봉과 최근 봉 기록 (기본은 비어 있음)
+    pub indicators: IndicatorSet,      // 틱마다 갱신하는 기술적 지표 (기본은 비어 있음)
+    evicted_at: Option<u64>,           // 마지막으로 버린 틱의 시각 (구간이 다 덮이는지 판단)
 }
 
 impl MarketStorage {
     /// 🔧 새로운 저장소 생성
     pub fn new(capacity: usize) -> Self {
+        Self::with_candles(capacity, CandleSet::default())
+    }
+
+    /// 🔧 봉 집계까지 하는 저장소 생성
+    pub fn with_candles(capacity: usize, candles: CandleSet) -> Self {
         Self {
             ticks: VecDeque::with_capacity(capacity),
             orderbooks: VecDeque::with_capacity(capacity),
             capacity,
+            retention: None,
+            candles,
+            indicators: IndicatorSet::default(),
+            evicted_at: None,
         }
     }
 
-    /// ✅ Tick 데이터 추가
-    pub fn push_tick(&mut self, tick: TickData) {
-        if self.ticks.len() == self.capacity {
-            self.ticks.pop_front(); // 오래된 것 제거
-        }
+    /// ⏳ window 구간 안의 틱은 capacity를 넘어도 버리지 않음 (스키마의 가장 긴 구간을 넣음)
+    pub fn with_retention(mut self, window: TimeWindow) -> Self {
+        self.retention = Some(window);
+        self
+    }
+
+    /// 📐 기술적 지표도 함께 갱신
+    pub fn with_indicators(mut self, indicators: IndicatorSet) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /// ✅ Tick 데이터 추가, 이번 틱으로 완성된 봉들을 반환
+    pub fn push_tick(&mut self, tick: TickData) -> Vec<CandleEvent> {
+        let completed = self.candles.push(&tick);
+        self.indicators.push_tick(&tick);
+        let latest = tick.timestamp;
         self.ticks.push_back(tick);
+        // capacity를 넘은 오래된 것 제거 (retention 구간 안이면 유지)
+        while self.ticks.len() > self.capacity {
+            let front = self.ticks.front().unwrap().timestamp;
+            if self.retention.is_some_and(|r| front + r.millis >= latest) {
+                break;
+            }
+            self.ticks.pop_front();
+            self.evicted_at = Some(front);
+        }
+        completed
     }
 
     /// ✅ OrderBook 데이터 추가
@@ -35,8 +74,87 @@ impl MarketStorage {
     }
 }
 
-/// 📈 분석된 피처들을 담는 구조체 (총 12개)
-#[derive(Debug, Clone)]
+/// 🗂️ 마켓 코드별로 독립된 MarketStorage 모음
+pub struct MultiMarketStorage {
+    pub markets: HashMap<String, MarketStorage>,
+    pub capacity: usize,
+    pub candle_specs: Vec<BarSpec>,    // 새 마켓 저장소에 붙일 봉 종류
+    pub candle_history: usize,         // 봉 종류별로 보관하는 최근 봉 수
+    pub indicators: IndicatorSet,      // 새 마켓 저장소에 복사해서 붙일 지표 묶음
+    pub retention: Option<TimeWindow>, // 새 마켓 저장소에 붙일 시간 기준 보관 구간
+}
+
+impl MultiMarketStorage {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_candles(capacity, &[], 0)
+    }
+
+    /// 🕯️ 마켓마다 봉도 함께 집계
+    pub fn with_candles(capacity: usize, specs: &[BarSpec], history_len: usize) -> Self {
+        Self {
+            markets: HashMap::new(),
+            capacity,
+            candle_specs: specs.to_vec(),
+            candle_history: history_len,
+            indicators: IndicatorSet::default(),
+            retention: None,
+        }
+    }
+
+    /// 📐 마켓마다 기술적 지표도 함께 갱신
+    pub fn with_indicators(mut self, indicators: IndicatorSet) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /// ⏳ 마켓마다 window 구간의 틱을 capacity와 무관하게 보관 (MarketStorage::with_retention)
+    pub fn with_retention(mut self, window: TimeWindow) -> Self {
+        self.retention = Some(window);
+        self
+    }
+
+    /// ✅ Tick을 해당 마켓 저장소에 추가하고 이 틱으로 완성된 봉들을 반환 (저장소는 get으로)
+    pub fn push_tick(&mut self, tick: TickData) -> Vec<CandleEvent> {
+        self.storage_mut(&tick.code).push_tick(tick)
+    }
+
+    /// ✅ OrderBook을 해당 마켓 저장소에 추가하고 그 저장소를 반환
+    pub fn push_orderbook(&mut self, ob: OrderBookData) -> &MarketStorage {
+        let storage = self.storage_mut(&ob.code);
+        storage.push_orderbook(ob);
+        storage
+    }
+
+    pub fn get(&self, code: &str) -> Option<&MarketStorage> {
+        self.markets.get(code)
+    }
+
+    /// 🔍 마켓별로 analyze 실행 (분석 가능한 마켓만)
+    pub fn analyze_all(&self) -> HashMap<String, MarketFeatures> {
+        self.markets
+            .iter()
+            .filter_map(|(code, storage)| analyze(storage).map(|f| (code.clone(), f)))
+            .collect()
+    }
+
+    fn storage_mut(&mut self, code: &str) -> &mut MarketStorage {
+        let (capacity, specs, history) = (self.capacity, &self.candle_specs, self.candle_history);
+        let (indicators, retention) = (&self.indicators, self.retention);
+        self.markets.entry(code.to_string()).or_insert_with(|| {
+            let storage = MarketStorage::with_candles(capacity, CandleSet::new(specs, history))
+                .with_indicators(indicators.clone());
+            match retention {
+                Some(window) => storage.with_retention(window),
+                None => storage,
+            }
+        })
+    }
+}
+
+/// 📈 분석된 피처들을 담는 구조체
+/// 앞쪽은 기본 피처(schema::MARKET_FEATURES), 뒤쪽은 마이크로스트럭처 피처(schema::MICROSTRUCTURE_FEATURES)
+/// FeatureSchema로 골라서 사용
+#[derive(Debug, Clone, Default)]
 pub struct MarketFeatures {
     pub avg_price: f32,
     pub price_delta: f32,
@@ -50,6 +168,236 @@ pub struct MarketFeatures {
     pub bid_depth_ratio: f32,
     pub tick_speed: f32,
     pub last_tick_size: f32,
+    pub ofi: f32,             // 직전 오더북 대비 1호가 주문 흐름 불균형
+    pub microprice: f32,      // 1호가 잔량 가중 중간가
+    pub vwap: f32,            // 거래량 가중 평균 체결가
+    pub trade_imbalance: f32, // (매수 체결량 - 매도 체결량) / 전체 체결량
+    pub trade_intensity: f32, // 초당 체결 횟수
+    pub tick_gap: f32,        // 직전 체결과의 시간 간격 (ms)
+}
+
+impl MarketFeatures {
+    /// 이름으로 피처 값 조회 (FeatureSchema가 관측 벡터를 만들 때 사용)
+    pub fn value(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "avg_price" => self.avg_price,
+            "price_delta" => self.price_delta,
+            "volume_sum" => self.volume_sum,
+            "volatility" => self.volatility,
+            "imbalance" => self.imbalance,
+            "spread" => self.spread,
+            "ask1_price" => self.ask1_price,
+            "bid1_price" => self.bid1_price,
+            "ask_depth_ratio" => self.ask_depth_ratio,
+            "bid_depth_ratio" => self.bid_depth_ratio,
+            "tick_speed" => self.tick_speed,
+            "last_tick_size" => self.last_tick_size,
+            "ofi" => self.ofi,
+            "microprice" => self.microprice,
+            "vwap" => self.vwap,
+            "trade_imbalance" => self.trade_imbalance,
+            "trade_intensity" => self.trade_intensity,
+            "tick_gap" => self.tick_gap,
+            _ => return None,
+        })
+    }
+}
+
+/// 🔎 이름으로 피처 값을 꺼낼 수 있는 분석 결과 (FeatureSchema::extract에서 사용)
+pub trait FeatureSource {
+    fn value(&self, name: &str) -> Option<f32>;
+}
+
+impl<T: FeatureSource + ?Sized> FeatureSource for &T {
+    fn value(&self, name: &str) -> Option<f32> {
+        (**self).value(name)
+    }
+}
+
+/// 없는 결과 (분석 전 오더북 등)
+impl<T: FeatureSource> FeatureSource for Option<T> {
+    fn value(&self, name: &str) -> Option<f32> {
+        self.as_ref()?.value(name)
+    }
+}
+
+/// 두 결과를 이어 붙임 (앞쪽에 없는 이름은 뒤쪽에서 찾음), 예: (분석 피처, 지표)
+impl<A: FeatureSource, B: FeatureSource> FeatureSource for (A, B) {
+    fn value(&self, name: &str) -> Option<f32> {
+        self.0.value(name).or_else(|| self.1.value(name))
+    }
+}
+
+/// 세 결과를 이어 붙임, 예: (분석 피처, 지표, 오더북 블록)
+impl<A: FeatureSource, B: FeatureSource, C: FeatureSource> FeatureSource for (A, B, C) {
+    fn value(&self, name: &str) -> Option<f32> {
+        (&self.0, (&self.1, &self.2)).value(name)
+    }
+}
+
+/// 📚 오더북 하나로 미리 계산한 [단계, 4] 블록 (오더북이 바뀔 때 한 번만 만들고 이름으로 조회)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBookDepth {
+    pub rows: Vec<[f32; 4]>, // orderbook_depth와 같은 값, 오더북에 있는 단계 수만큼
+}
+
+impl OrderBookDepth {
+    pub fn new(orderbook: &OrderBookData) -> Self {
+        Self {
+            rows: orderbook_depth(orderbook, orderbook.order_units.len()),
+        }
+    }
+}
+
+/// 📚 오더북 블록 ("depth3_bid_size" 등, schema::DEPTH_FIELDS), 없는 단계는 0
+impl FeatureSource for OrderBookDepth {
+    fn value(&self, name: &str) -> Option<f32> {
+        let (level, field) = parse_depth_name(name)?;
+        let column = DEPTH_FIELDS.iter().position(|f| *f == field)?;
+        Some(self.rows.get(level).map_or(0.0, |row| row[column]))
+    }
+}
+
+/// 📚 호가 단계별 [매도 오프셋, 매도 잔량, 매수 오프셋, 매수 잔량] (levels개, 없는 단계는 0)
+/// 오프셋은 중간가 대비 bp ((호가 - 중간가) / 중간가 x 10000, 매도는 +, 매수는 -)
+/// 잔량은 오더북에 보이는 전체 잔량(매도 + 매수) 대비 비율이라 합이 1 이하
+pub fn orderbook_depth(orderbook: &OrderBookData, levels: usize) -> Vec<[f32; 4]> {
+    let units = &orderbook.order_units;
+    let mid = units
+        .first()
+        .map_or(0.0, |u| (u.ask_price + u.bid_price) / 2.0);
+    let total: f32 = units.iter().map(|u| u.ask_size + u.bid_size).sum();
+    let offset = |price: f32| {
+        if mid > 0.0 {
+            (price - mid) / mid * 10_000.0
+        } else {
+            0.0
+        }
+    };
+    let share = |size: f32| if total > 0.0 { size / total } else { 0.0 };
+
+    (0..levels)
+        .map(|level| match units.get(level) {
+            Some(u) => [
+                offset(u.ask_price),
+                share(u.ask_size),
+                offset(u.bid_price),
+                share(u.bid_size),
+            ],
+            None => [0.0; 4],
+        })
+        .collect()
+}
+
+impl FeatureSource for MarketFeatures {
+    fn value(&self, name: &str) -> Option<f32> {
+        MarketFeatures::value(self, name)
+    }
+}
+
+/// ⏱️ 틱 타임스탬프 기준 시간 구간 (가장 최근 틱부터 거슬러 올라감)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimeWindow {
+    pub millis: u64,
+}
+
+/// 기본 분석 구간: 1초, 10초, 1분, 5분
+pub const DEFAULT_WINDOWS: [TimeWindow; 4] = [
+    TimeWindow::seconds(1),
+    TimeWindow::seconds(10),
+    TimeWindow::minutes(1),
+    TimeWindow::minutes(5),
+];
+
+impl TimeWindow {
+    pub const fn millis(millis: u64) -> Self {
+        Self { millis }
+    }
+
+    pub const fn seconds(seconds: u64) -> Self {
+        Self::millis(seconds * 1000)
+    }
+
+    pub const fn minutes(minutes: u64) -> Self {
+        Self::millis(minutes * 60_000)
+    }
+
+    /// "500ms", "10s", "5m" 형식 (피처 이름의 @ 뒤에 붙는 부분)
+    pub fn parse(label: &str) -> Option<Self> {
+        let (number, unit) = label.split_at(label.find(|c: char| !c.is_ascii_digit())?);
+        let number: u64 = number.parse().ok()?;
+        match unit {
+            "ms" => Some(Self::millis(number)),
+            "s" => Some(Self::seconds(number)),
+            "m" => Some(Self::minutes(number)),
+            _ => None,
+        }
+    }
+
+    /// parse의 반대, 나누어떨어지는 가장 큰 단위로 표시 (60s → 1m)
+    pub fn label(&self) -> String {
+        match self.millis {
+            0 => "0ms".to_string(),
+            ms if ms.is_multiple_of(60_000) => format!("{}m", ms / 60_000),
+            ms if ms.is_multiple_of(1000) => format!("{}s", ms / 1000),
+            ms => format!("{}ms", ms),
+        }
+    }
+}
+
+impl MarketStorage {
+    /// 기본 피처에 쓰는 최근 capacity개 틱
+    pub fn recent(&self) -> vec_deque::Iter<'_, TickData> {
+        self.ticks
+            .range(self.ticks.len().saturating_sub(self.capacity)..)
+    }
+
+    /// window 구간의 틱이 하나도 버려지지 않았는지 (false면 window()의 앞부분이 빠져 있음)
+    pub fn covers(&self, window: TimeWindow) -> bool {
+        let Some(latest) = self.ticks.back().map(|t| t.timestamp) else {
+            return true;
+        };
+        self.evicted_at
+            .is_none_or(|evicted| evicted + window.millis < latest)
+    }
+
+    /// 🕒 가장 최근 틱 기준으로 [최근 - window, 최근] 안에 들어오는 틱들 (오래된 것부터)
+    /// 틱이 있으면 최근 틱은 항상 포함, 저장된 틱보다 긴 구간은 저장된 만큼만 돌려줌
+    pub fn window(&self, window: TimeWindow) -> vec_deque::Iter<'_, TickData> {
+        let Some(latest) = self.ticks.back().map(|t| t.timestamp) else {
+            return self.ticks.range(..);
+        };
+        let start = self
+            .ticks
+            .partition_point(|t| t.timestamp + window.millis < latest);
+        self.ticks.range(start..)
+    }
+}
+
+/// 📚 기본 피처(저장된 틱 전체)와 시간 구간별 피처 묶음
+/// 구간별 피처는 FeatureSchema에서 "volatility@10s"처럼 이름@구간으로 꺼냄
+#[derive(Debug, Clone)]
+pub struct MultiHorizonFeatures {
+    pub base: MarketFeatures,
+    pub windows: Vec<(TimeWindow, MarketFeatures)>,
+}
+
+impl MultiHorizonFeatures {
+    pub fn get(&self, window: TimeWindow) -> Option<&MarketFeatures> {
+        self.windows
+            .iter()
+            .find(|(w, _)| *w == window)
+            .map(|(_, features)| features)
+    }
+}
+
+impl FeatureSource for MultiHorizonFeatures {
+    fn value(&self, name: &str) -> Option<f32> {
+        match name.split_once('@') {
+            Some((feature, label)) => self.get(TimeWindow::parse(label)?)?.value(feature),
+            None => self.base.value(name),
+        }
+    }
 }
 
 /// 🔍 저장된 데이터를 기반으로 피처를 계산하는 분석 함수
@@ -58,16 +406,77 @@ pub fn analyze(storage: &MarketStorage) -> Option<MarketFeatures> {
     if storage.ticks.len() < 2 || storage.orderbooks.is_empty() {
         return None;
     }
+    let ticks: Vec<&TickData> = storage.recent().collect();
+    Some(compute_features(&ticks, storage))
+}
 
-    let ticks = &storage.ticks;
-    let orderbook = storage.orderbooks.back().unwrap();
-    let units = &orderbook.order_units;
+/// 🔍 틱 관련 피처(평균가/변화량/체결량/변동성/체결 속도/마지막 체결량)를 window 안의 틱으로만 계산
+/// 호가 피처는 항상 최신 오더북 기준
+/// 구간 안에 틱이 하나뿐이면 변화량/변동성/체결 속도는 0
+/// capacity 때문에 구간 안의 틱이 버려졌으면 None (retention을 구간 이상으로 잡으면 항상 다 덮음)
+pub fn analyze_window(storage: &MarketStorage, window: TimeWindow) -> Option<MarketFeatures> {
+    if storage.ticks.len() < 2 || storage.orderbooks.is_empty() || !storage.covers(window) {
+        return None;
+    }
+    let ticks: Vec<&TickData> = storage.window(window).collect();
+    Some(compute_features(&ticks, storage))
+}
 
+/// 📚 기본 피처 + 구간별 피처를 한 번에 계산
+pub fn analyze_horizons(
+    storage: &MarketStorage,
+    windows: &[TimeWindow],
+) -> Option<MultiHorizonFeatures> {
+    Some(MultiHorizonFeatures {
+        base: analyze(storage)?,
+        windows: windows
+            .iter()
+            .map(|&w| Some((w, analyze_window(storage, w)?)))
+            .collect::<Option<_>>()?,
+    })
+}
+
+/// 틱(1개 이상)과 최신 오더북으로 피처 계산
+fn compute_features(ticks: &[&TickData], storage: &MarketStorage) -> MarketFeatures {
+    let previous = storage
+        .orderbooks
+        .len()
+        .checked_sub(2)
+        .and_then(|i| storage.orderbooks.get(i));
+    tick_features(ticks).with_orderbook(storage.orderbooks.back().unwrap(), previous)
+}
+
+/// 📈 체결 데이터만으로 계산되는 피처 (호가 피처는 with_orderbook에서 붙임)
+#[derive(Debug, Clone)]
+pub struct TickFeatures {
+    pub avg_price: f32,
+    pub price_delta: f32,
+    pub volume_sum: f32,
+    pub volatility: f32,
+    pub tick_speed: f32,
+    pub last_tick_size: f32,
+    pub vwap: f32,
+    pub trade_imbalance: f32,
+    pub trade_intensity: f32,
+    pub tick_gap: f32,
+}
+
+/// 체결 방향 부호: 매수 체결(BID) +1, 매도 체결(ASK) -1
+pub fn trade_sign(side: &str) -> f32 {
+    match side {
+        "BID" => 1.0,
+        "ASK" => -1.0,
+        _ => 0.0,
+    }
+}
+
+/// 틱(1개 이상)을 전부 훑어서 계산
+fn tick_features(ticks: &[&TickData]) -> TickFeatures {
     // 1️⃣ 평균 체결가
     let avg_price: f32 = ticks.iter().map(|t| t.price).sum::<f32>() / ticks.len() as f32;
 
     // 2️⃣ 체결가 변화량 (가장 오래된 것과 최신 것의 차이)
-    let price_delta = ticks.back().unwrap().price - ticks.front().unwrap().price;
+    let price_delta = ticks.last().unwrap().price - ticks.first().unwrap().price;
 
     // 3️⃣ 총 체결량
     let volume_sum: f32 = ticks.iter().map(|t| t.volume).sum();
@@ -77,65 +486,159 @@ pub fn analyze(storage: &MarketStorage) -> Option<MarketFeatures> {
     let variance = ticks.iter().map(|t| (t.price - mean).powi(2)).sum::<f32>() / ticks.len() as f32;
     let volatility = variance.sqrt();
 
-    // 5️⃣ 최우선 매도/매수호가 (체결 예상가)
-    let ask1_price = units.first().map(|u| u.ask_price).unwrap_or(0.0);
-    let bid1_price = units.first().map(|u| u.bid_price).unwrap_or(0.0);
-
-    // 6️⃣ 스프레드 (ask1 - bid1)
-    let spread = ask1_price - bid1_price;
+    // 🔟 체결 속도 (틱 간 평균 시간 차이)
+    let mut tick_speed = 0.0;
+    if ticks.len() >= 2 {
+        let mut time_diffs = vec![];
+        for w in ticks.windows(2) {
+            let diff = w[1].timestamp as i64 - w[0].timestamp as i64;
+            time_diffs.push(diff as f32);
+        }
+        tick_speed = time_diffs.iter().sum::<f32>() / time_diffs.len() as f32;
+    }
 
-    // 7️⃣ 전체 호가 잔량 합
-    let ask_sum: f32 = units.iter().map(|u| u.ask_size).sum();
-    let bid_sum: f32 = units.iter().map(|u| u.bid_size).sum();
+    // 1️⃣1️⃣ 마지막 체결량 (시장 반응 강도)
+    let last_tick_size = ticks.last().map(|t| t.volume).unwrap_or(0.0);
 
-    // 8️⃣ 호가 잔량 불균형
-    let imbalance = if ask_sum + bid_sum > 0.0 {
-        (bid_sum - ask_sum) / (bid_sum + ask_sum)
+    // 1️⃣2️⃣ VWAP (체결량이 없으면 평균 체결가)
+    let vwap = if volume_sum > 0.0 {
+        ticks.iter().map(|t| t.price * t.volume).sum::<f32>() / volume_sum
     } else {
-        0.0
+        avg_price
     };
 
-    // 9️⃣ 상위 5호가의 집중도 (depth ratio)
-    let ask_top5: f32 = units.iter().take(5).map(|u| u.ask_size).sum();
-    let bid_top5: f32 = units.iter().take(5).map(|u| u.bid_size).sum();
-
-    let ask_depth_ratio = if ask_sum > 0.0 {
-        ask_top5 / ask_sum
+    // 1️⃣3️⃣ 체결 방향 불균형
+    let signed_volume: f32 = ticks.iter().map(|t| trade_sign(&t.side) * t.volume).sum();
+    let trade_imbalance = if volume_sum > 0.0 {
+        signed_volume / volume_sum
     } else {
         0.0
     };
-    let bid_depth_ratio = if bid_sum > 0.0 {
-        bid_top5 / bid_sum
+
+    // 1️⃣4️⃣ 체결 강도 (초당 체결 횟수, 시간 간격이 없으면 0)
+    let span = ticks.last().unwrap().timestamp as i64 - ticks.first().unwrap().timestamp as i64;
+    let trade_intensity = if span > 0 {
+        (ticks.len() - 1) as f32 * 1000.0 / span as f32
     } else {
         0.0
     };
 
-    // 🔟 체결 속도 (틱 간 평균 시간 차이)
-    let mut tick_speed = 0.0;
-    if ticks.len() >= 2 {
-        let mut time_diffs = vec![];
-        for w in ticks.iter().collect::<Vec<_>>().windows(2) {
-            let diff = w[1].timestamp as i64 - w[0].timestamp as i64;
-            time_diffs.push(diff as f32);
-        }
-        tick_speed = time_diffs.iter().sum::<f32>() / time_diffs.len() as f32;
-    }
-
-    // 1️⃣1️⃣ 마지막 체결량 (시장 반응 강도)
-    let last_tick_size = ticks.back().map(|t| t.volume).unwrap_or(0.0);
+    // 1️⃣5️⃣ 직전 체결과의 시간 간격 (틱이 하나면 0)
+    let tick_gap = match ticks {
+        [.., previous, last] => (last.timestamp as i64 - previous.timestamp as i64) as f32,
+        _ => 0.0,
+    };
 
-    Some(MarketFeatures {
+    TickFeatures {
         avg_price,
         price_delta,
         volume_sum,
         volatility,
-        imbalance,
-        spread,
-        ask1_price,
-        bid1_price,
-        ask_depth_ratio,
-        bid_depth_ratio,
         tick_speed,
         last_tick_size,
-    })
+        vwap,
+        trade_imbalance,
+        trade_intensity,
+        tick_gap,
+    }
+}
+
+/// 🌊 1호가 주문 흐름 불균형 (Cont, Kukanov & Stoikov, 2014)
+/// 매수호가가 오르거나 잔량이 늘면 +, 매도호가가 내리거나 잔량이 늘면 -
+pub fn order_flow_imbalance(previous: &OrderBookData, current: &OrderBookData) -> f32 {
+    let (Some(prev), Some(cur)) = (previous.order_units.first(), current.order_units.first())
+    else {
+        return 0.0;
+    };
+    let mut e = 0.0;
+    if cur.bid_price >= prev.bid_price {
+        e += cur.bid_size;
+    }
+    if cur.bid_price <= prev.bid_price {
+        e -= prev.bid_size;
+    }
+    if cur.ask_price <= prev.ask_price {
+        e -= cur.ask_size;
+    }
+    if cur.ask_price >= prev.ask_price {
+        e += prev.ask_size;
+    }
+    e
+}
+
+impl TickFeatures {
+    /// 최신 오더북의 호가 피처를 붙여서 MarketFeatures 완성
+    /// previous는 직전 오더북 (OFI 계산용, 없으면 OFI = 0)
+    pub fn with_orderbook(
+        self,
+        orderbook: &OrderBookData,
+        previous: Option<&OrderBookData>,
+    ) -> MarketFeatures {
+        let units = &orderbook.order_units;
+
+        // 5️⃣ 최우선 매도/매수호가 (체결 예상가)
+        let ask1_price = units.first().map(|u| u.ask_price).unwrap_or(0.0);
+        let bid1_price = units.first().map(|u| u.bid_price).unwrap_or(0.0);
+
+        // 6️⃣ 스프레드 (ask1 - bid1)
+        let spread = ask1_price - bid1_price;
+
+        // 7️⃣ 전체 호가 잔량 합
+        let ask_sum: f32 = units.iter().map(|u| u.ask_size).sum();
+        let bid_sum: f32 = units.iter().map(|u| u.bid_size).sum();
+
+        // 8️⃣ 호가 잔량 불균형
+        let imbalance = if ask_sum + bid_sum > 0.0 {
+            (bid_sum - ask_sum) / (bid_sum + ask_sum)
+        } else {
+            0.0
+        };
+
+        // 9️⃣ 상위 5호가의 집중도 (depth ratio)
+        let ask_top5: f32 = units.iter().take(5).map(|u| u.ask_size).sum();
+        let bid_top5: f32 = units.iter().take(5).map(|u| u.bid_size).sum();
+
+        let ask_depth_ratio = if ask_sum > 0.0 {
+            ask_top5 / ask_sum
+        } else {
+            0.0
+        };
+        let bid_depth_ratio = if bid_sum > 0.0 {
+            bid_top5 / bid_sum
+        } else {
+            0.0
+        };
+
+        // 1️⃣6️⃣ 마이크로프라이스: 반대편 잔량이 많을수록 그쪽 호가에서 멀어짐
+        let microprice = match units.first() {
+            Some(u) if u.ask_size + u.bid_size > 0.0 => {
+                (u.ask_price * u.bid_size + u.bid_price * u.ask_size) / (u.ask_size + u.bid_size)
+            }
+            _ => (ask1_price + bid1_price) / 2.0,
+        };
+
+        // 1️⃣7️⃣ 주문 흐름 불균형
+        let ofi = previous.map_or(0.0, |prev| order_flow_imbalance(prev, orderbook));
+
+        MarketFeatures {
+            avg_price: self.avg_price,
+            price_delta: self.price_delta,
+            volume_sum: self.volume_sum,
+            volatility: self.volatility,
+            imbalance,
+            spread,
+            ask1_price,
+            bid1_price,
+            ask_depth_ratio,
+            bid_depth_ratio,
+            tick_speed: self.tick_speed,
+            last_tick_size: self.last_tick_size,
+            ofi,
+            microprice,
+            vwap: self.vwap,
+            trade_imbalance: self.trade_imbalance,
+            trade_intensity: self.trade_intensity,
+            tick_gap: self.tick_gap,
+        }
+    }
 }
@@ -0,0 +1,197 @@
+use crate::agent::Agent;
+use crate::candles::{BarSpec, CandleEvent};
+use crate::dqn_model::DqnModel;
+use crate::env::{Env, EnvConfig};
+use crate::feed::MarketEvent;
+use crate::incremental::MultiMarketAnalyzer;
+use crate::indicators::IndicatorSet;
+use crate::portfolio::Portfolio;
+use crate::replay_log::ReplaySample;
+use crate::types::B;
+use burn::tensor::backend::Backend;
+use std::collections::HashMap;
+
+/// ⚙️ 백테스트 설정
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub seed: u64,                  // Agent 탐험용 시드
+    pub epsilon: f32,               // 0.0이면 항상 greedy
+    pub storage_capacity: usize, // 기본 피처에 쓰는 최근 틱 수 (시간 구간 피처는 구간 길이만큼 따로 유지)
+    pub decision_interval_ms: u64, // 시뮬레이션 시계 기준 최소 의사결정 간격 (0 = 매 틱)
+    pub max_steps: Option<usize>, // 의사결정 횟수 상한
+    pub candle_specs: Vec<BarSpec>, // 재생하면서 만들 봉 종류 (완성된 봉은 결과의 candles)
+    pub env: EnvConfig,          // 심볼별 Env 설정 (비용 / 자금 / 보상)
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            epsilon: 0.0,
+            storage_capacity: 200,
+            decision_interval_ms: 0,
+            max_steps: None,
+            candle_specs: Vec::new(),
+            env: EnvConfig::default(),
+        }
+    }
+}
+
+/// 🕰️ 실제 시간 대신 이벤트 타임스탬프로 움직이는 시계
+#[derive(Debug, Clone, Default)]
+pub struct SimClock {
+    now: u64,
+}
+
+impl SimClock {
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// 시간은 뒤로 가지 않음
+    pub fn advance_to(&mut self, timestamp: u64) {
+        self.now = self.now.max(timestamp);
+    }
+}
+
+/// 📝 의사결정 한 번의 기록
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestStep {
+    pub code: String,
+    pub timestamp: u64,
+    pub price: f32,
+    pub action: usize,
+    pub reward: f32,
+}
+
+/// 📊 백테스트 결과
+pub struct BacktestResult {
+    pub steps: Vec<BacktestStep>,
+    pub samples: Vec<ReplaySample>,
+    pub total_reward: f32,
+    pub trades: usize,                          // 실제로 체결된 매수/매도 횟수
+    pub events: usize,                          // 처리한 이벤트 수
+    pub candles: Vec<CandleEvent>,              // 재생 중 완성된 봉 (마켓은 candle.code, 완성 순서)
+    pub portfolios: HashMap<String, Portfolio>, // 마켓별 최종 계좌 (평가금액 기록 포함)
+}
+
+/// 🔁 녹화된 이벤트를 타임스탬프 순서로 재생하며 정책을 평가
+/// 마켓마다 저장소/Env/의사결정 간격이 따로 관리됨
+/// 같은 이벤트, 같은 모델, 같은 설정이면 항상 같은 결과
+pub fn run_backtest(
+    events: &[MarketEvent],
+    model: &DqnModel<B>,
+    config: &BacktestConfig,
+) -> BacktestResult {
+    let device = <B as Backend>::Device::default();
+    let mut agent = Agent::with_seed(config.epsilon, config.seed);
+    let mut envs: HashMap<String, Env<B>> = HashMap::new();
+    let windows = config.env.schema.windows(); // 스키마가 쓰는 시간 구간
+    let mut storage = MultiMarketAnalyzer::new(config.storage_capacity, &windows)
+        .with_indicators(IndicatorSet::from_schema(&config.env.schema)) // 스키마가 쓰는 지표
+        .with_candles(&config.candle_specs, 0);
+    let mut clock = SimClock::default();
+
+    // 안정 정렬: 같은 시각의 이벤트는 입력 순서 유지
+    let mut order: Vec<&MarketEvent> = events.iter().collect();
+    order.sort_by_key(|e| e.timestamp());
+
+    let mut steps = Vec::new();
+    let mut samples = Vec::new();
+    let mut total_reward = 0.0;
+    let mut trades = 0;
+    let mut processed = 0;
+    let mut candles = Vec::new();
+    let mut last_decision: HashMap<String, u64> = HashMap::new();
+    let mut last_sample: HashMap<String, usize> = HashMap::new(); // 마켓별 마지막 샘플 위치
+
+    for event in order {
+        if config.max_steps.is_some_and(|max| steps.len() >= max) {
+            break;
+        }
+        clock.advance_to(event.timestamp());
+        processed += 1;
+
+        let tick = match event {
+            MarketEvent::OrderBook(order) => {
+                storage.push_orderbook(order.clone());
+                continue;
+            }
+            MarketEvent::Tick(tick) => tick,
+        };
+        let market = storage.push_tick(tick);
+        candles.extend_from_slice(market.completed_candles());
+        if !market.indicators().is_warm() {
+            continue; // 지표 워밍업 중 (값이 0.0으로 채워진 관측으로 결정하지 않음)
+        }
+
+        let due = last_decision
+            .get(&tick.code)
+            .is_none_or(|t| clock.now() - t >= config.decision_interval_ms);
+        if !due {
+            continue;
+        }
+        let Some(features) = market.analyze_horizons() else {
+            continue;
+        };
+        last_decision.insert(tick.code.clone(), clock.now());
+
+        let env = envs
+            .entry(tick.code.clone())
+            .or_insert_with(|| Env::with_config(device, config.env.clone()));
+        if env.is_done() {
+            continue; // 에피소드가 끝난 마켓은 더 거래하지 않음 (계좌 기록 보존)
+        }
+        env.update((features, market.indicators(), market.depth()));
+        if let Some(ob) = market.orderbook() {
+            env.update_orderbook(ob.clone());
+        }
+        let state = env.observation();
+        let q_values = model.forward(env.observe());
+        let q_data = q_values.to_data().convert::<f32>();
+        let action = agent.select_action(q_data.as_slice::<f32>().unwrap());
+
+        let (next_state, reward) = env.step(action, tick.clone());
+        if env.last_fill.is_some() {
+            trades += 1;
+        }
+        total_reward += reward;
+
+        steps.push(BacktestStep {
+            code: tick.code.clone(),
+            timestamp: clock.now(),
+            price: tick.price,
+            action,
+            reward,
+        });
+        last_sample.insert(tick.code.clone(), samples.len());
+        samples.push(ReplaySample {
+            state,
+            action,
+            reward,
+            next_state,
+            done: env.is_done(),
+        });
+    }
+
+    // 🏁 데이터 끝: 진행 중이던 에피소드 마감
+    for (code, index) in last_sample {
+        if let Some(env) = envs.get_mut(&code) {
+            env.end_of_data();
+        }
+        samples[index].done = true;
+    }
+
+    BacktestResult {
+        steps,
+        samples,
+        total_reward,
+        trades,
+        events: processed,
+        candles,
+        portfolios: envs
+            .into_iter()
+            .map(|(code, env)| (code, env.portfolio))
+            .collect(),
+    }
+}
@@ -0,0 +1,245 @@
+use crate::feed::MarketEvent;
+use crate::websocket::TickData;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+// 🕯️ 틱 → 봉(OHLCV) 집계
+//
+// 봉 경계는 틱 내용(타임스탬프 / 개수 / 체결량 / 거래대금)만으로 정해지므로
+// 실시간으로 만든 봉과 녹화 데이터로 다시 만든 봉이 똑같음 (벽시계 시간은 쓰지 않음)
+//
+// - 시간 봉: Unix epoch 기준으로 나눈 구간 [k·길이, (k+1)·길이), 다음 구간의 틱이 와야 완성
+//            틱이 없는 구간은 봉을 만들지 않음
+// - 틱/체결량/거래대금 봉: 기준에 도달한 틱까지 포함해서 완성 (틱을 쪼개지 않음)
+
+/// 📏 봉 종류
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BarSpec {
+    Time { millis: u64 },   // 시간 봉 (1s, 1m, 5m, ...)
+    Ticks { count: usize }, // 체결 n개마다
+    Volume { amount: f64 }, // 체결량 합이 amount 이상이 될 때마다
+    Value { amount: f64 },  // 거래대금(원화, 가격 x 체결량) 합이 amount 이상이 될 때마다
+}
+
+impl BarSpec {
+    pub const fn seconds(seconds: u64) -> Self {
+        BarSpec::Time {
+            millis: seconds * 1000,
+        }
+    }
+
+    pub const fn minutes(minutes: u64) -> Self {
+        BarSpec::Time {
+            millis: minutes * 60_000,
+        }
+    }
+}
+
+/// 🕯️ 완성된 (또는 만들어지는 중인) 봉 하나
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub code: String,
+    pub open_time: u64,  // 시간 봉은 구간 시작, 나머지는 첫 틱 시각
+    pub close_time: u64, // 마지막 틱 시각
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+    pub volume: f64,
+    pub value: f64, // 거래대금 (가격 x 체결량 합)
+    pub trades: usize,
+}
+
+impl Candle {
+    fn open(tick: &TickData, open_time: u64) -> Self {
+        Self {
+            code: tick.code.clone(),
+            open_time,
+            close_time: tick.timestamp,
+            open: tick.price,
+            high: tick.price,
+            low: tick.price,
+            close: tick.price,
+            volume: 0.0,
+            value: 0.0,
+            trades: 0,
+        }
+    }
+
+    fn add(&mut self, tick: &TickData) {
+        self.close_time = self.close_time.max(tick.timestamp);
+        self.high = self.high.max(tick.price);
+        self.low = self.low.min(tick.price);
+        self.close = tick.price;
+        self.volume += tick.volume as f64;
+        self.value += tick.price as f64 * tick.volume as f64;
+        self.trades += 1;
+    }
+
+    /// 거래량 가중 평균가 (체결량이 없으면 종가)
+    pub fn vwap(&self) -> f32 {
+        if self.volume > 0.0 {
+            (self.value / self.volume) as f32
+        } else {
+            self.close
+        }
+    }
+}
+
+/// 📨 완성된 봉 이벤트
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandleEvent {
+    pub spec: BarSpec,
+    pub candle: Candle,
+}
+
+/// 🔨 한 종류의 봉을 만드는 빌더
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    pub spec: BarSpec,
+    current: Option<Candle>,
+}
+
+impl CandleBuilder {
+    pub fn new(spec: BarSpec) -> Self {
+        Self {
+            spec,
+            current: None,
+        }
+    }
+
+    /// ➕ 틱 반영, 이번 틱으로 완성된 봉이 있으면 반환 (틱 하나로 최대 한 개)
+    /// 틱은 들어온 순서대로 처리하고, 시간 봉에서 현재 구간보다 이른 틱은 현재 봉에 합침
+    pub fn push(&mut self, tick: &TickData) -> Option<Candle> {
+        let mut completed = None;
+
+        if let BarSpec::Time { millis } = self.spec {
+            let start = tick.timestamp - tick.timestamp % millis.max(1);
+            if self.current.as_ref().is_some_and(|c| start > c.open_time) {
+                completed = self.current.take();
+            }
+            self.current
+                .get_or_insert_with(|| Candle::open(tick, start))
+                .add(tick);
+            return completed;
+        }
+
+        let candle = self
+            .current
+            .get_or_insert_with(|| Candle::open(tick, tick.timestamp));
+        candle.add(tick);
+        let full = match self.spec {
+            BarSpec::Ticks { count } => candle.trades >= count,
+            BarSpec::Volume { amount } => candle.volume >= amount,
+            BarSpec::Value { amount } => candle.value >= amount,
+            BarSpec::Time { .. } => false,
+        };
+        if full {
+            completed = self.current.take();
+        }
+        completed
+    }
+
+    /// 만들어지는 중인 봉
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// 데이터가 끝났을 때 미완성 봉을 꺼냄
+    pub fn flush(&mut self) -> Option<Candle> {
+        self.current.take()
+    }
+}
+
+/// 📚 빌더 + 최근 완성 봉 기록 (최대 history_len개)
+#[derive(Debug, Clone)]
+pub struct CandleSeries {
+    pub builder: CandleBuilder,
+    pub history: VecDeque<Candle>,
+    pub history_len: usize,
+}
+
+impl CandleSeries {
+    pub fn new(spec: BarSpec, history_len: usize) -> Self {
+        Self {
+            builder: CandleBuilder::new(spec),
+            history: VecDeque::with_capacity(history_len),
+            history_len,
+        }
+    }
+
+    pub fn spec(&self) -> BarSpec {
+        self.builder.spec
+    }
+
+    pub fn push(&mut self, tick: &TickData) -> Option<Candle> {
+        let candle = self.builder.push(tick)?;
+        if self.history.len() == self.history_len {
+            self.history.pop_front(); // 오래된 것 제거
+        }
+        if self.history_len > 0 {
+            self.history.push_back(candle.clone());
+        }
+        Some(candle)
+    }
+
+    /// 가장 최근에 완성된 봉
+    pub fn last(&self) -> Option<&Candle> {
+        self.history.back()
+    }
+}
+
+/// 🗃️ 한 마켓의 여러 봉 종류 묶음 (MarketStorage에 붙어서 틱과 함께 갱신)
+#[derive(Debug, Clone, Default)]
+pub struct CandleSet {
+    pub series: Vec<CandleSeries>,
+}
+
+impl CandleSet {
+    pub fn new(specs: &[BarSpec], history_len: usize) -> Self {
+        Self {
+            series: specs
+                .iter()
+                .map(|&spec| CandleSeries::new(spec, history_len))
+                .collect(),
+        }
+    }
+
+    /// 틱 반영, 완성된 봉들을 spec 순서대로 반환
+    pub fn push(&mut self, tick: &TickData) -> Vec<CandleEvent> {
+        self.series
+            .iter_mut()
+            .filter_map(|series| {
+                let candle = series.push(tick)?;
+                Some(CandleEvent {
+                    spec: series.spec(),
+                    candle,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get(&self, spec: BarSpec) -> Option<&CandleSeries> {
+        self.series.iter().find(|s| s.spec() == spec)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+/// 📼 녹화된 이벤트로 봉을 다시 만듦 (마켓별, 완성된 봉만, 이벤트 순서대로)
+/// 같은 틱 순서를 실시간으로 넣었을 때와 경계가 똑같음
+pub fn candles_from_events(events: &[MarketEvent], specs: &[BarSpec]) -> Vec<CandleEvent> {
+    let mut markets: HashMap<&str, CandleSet> = HashMap::new();
+    let mut completed = Vec::new();
+    for event in events {
+        if let MarketEvent::Tick(tick) = event {
+            let set = markets
+                .entry(&tick.code)
+                .or_insert_with(|| CandleSet::new(specs, 0));
+            completed.extend(set.push(tick));
+        }
+    }
+    completed
+}
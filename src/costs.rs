@@ -0,0 +1,166 @@
+use crate::websocket::OrderBookData;
+
+/// 매수 / 매도
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// 주문 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// 시장가: 반대편 호가를 긁으며 체결, taker 수수료
+    Market,
+    /// 지정가: 내 쪽 최우선 호가에 바로 체결된다고 가정, maker 수수료
+    Limit,
+}
+
+/// 💸 거래 비용 모델 (수수료 + 스프레드 + 호가 깊이 슬리피지)
+#[derive(Debug, Clone)]
+pub struct CostModel {
+    pub maker_fee: f32, // 체결 금액 대비 비율 (0.0005 = 0.05%)
+    pub taker_fee: f32,
+    pub order_type: OrderType,
+    pub cross_spread: bool, // false면 호가 대신 체결가(tick.price)로 체결
+    pub walk_depth: bool,   // false면 1호가에서 전량 체결
+}
+
+impl Default for CostModel {
+    /// Upbit KRW 마켓 기준 (maker/taker 0.05%), 시장가 주문
+    fn default() -> Self {
+        Self {
+            maker_fee: 0.0005,
+            taker_fee: 0.0005,
+            order_type: OrderType::Market,
+            cross_spread: true,
+            walk_depth: true,
+        }
+    }
+}
+
+/// 🧾 체결 결과 (금액은 모두 KRW)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub side: Side,
+    pub quantity: f32,
+    pub price: f32,       // 평균 체결가 (수수료 제외)
+    pub fee: f32,         // 수수료
+    pub spread_cost: f32, // 기준가 대비 최우선 호가까지의 비용
+    pub slippage: f32,    // 최우선 호가 대비 깊이를 긁으며 생긴 추가 비용
+}
+
+impl Fill {
+    /// 수수료까지 포함한 단가 (매수는 더 비싸게, 매도는 더 싸게)
+    pub fn net_price(&self) -> f32 {
+        let fee_per_unit = if self.quantity > 0.0 {
+            self.fee / self.quantity
+        } else {
+            0.0
+        };
+        match self.side {
+            Side::Buy => self.price + fee_per_unit,
+            Side::Sell => self.price - fee_per_unit,
+        }
+    }
+
+    pub fn total_cost(&self) -> f32 {
+        self.fee + self.spread_cost + self.slippage
+    }
+}
+
+impl CostModel {
+    /// 비용이 전혀 없는 모델 (기존 동작과 동일)
+    pub fn free() -> Self {
+        Self {
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            cross_spread: false,
+            walk_depth: false,
+            ..Self::default()
+        }
+    }
+
+    /// 🔍 quantity만큼 주문을 체결시켜 비용을 계산
+    /// reference_price: 호가가 없거나 cross_spread = false일 때 쓰는 기준가 (보통 tick.price)
+    pub fn fill(
+        &self,
+        side: Side,
+        quantity: f32,
+        reference_price: f32,
+        book: Option<&OrderBookData>,
+    ) -> Fill {
+        let fee_rate = match self.order_type {
+            OrderType::Market => self.taker_fee,
+            OrderType::Limit => self.maker_fee,
+        };
+
+        let levels = book
+            .filter(|_| self.cross_spread)
+            .map(|b| self.levels(side, b));
+        let (price, best) = match levels {
+            Some(levels) if !levels.is_empty() => {
+                let best = levels[0].0;
+                let price = if self.walk_depth && self.order_type == OrderType::Market {
+                    walk_levels(&levels, quantity)
+                } else {
+                    best
+                };
+                (price, best)
+            }
+            _ => (reference_price, reference_price),
+        };
+
+        // 불리한 방향으로 움직인 만큼만 비용으로 계산
+        let adverse = |from: f32, to: f32| match side {
+            Side::Buy => (to - from).max(0.0),
+            Side::Sell => (from - to).max(0.0),
+        };
+
+        Fill {
+            side,
+            quantity,
+            price,
+            fee: price * quantity * fee_rate,
+            spread_cost: adverse(reference_price, best) * quantity,
+            slippage: adverse(best, price) * quantity,
+        }
+    }
+
+    /// 체결에 쓰일 호가 목록 (가격, 잔량)
+    fn levels(&self, side: Side, book: &OrderBookData) -> Vec<(f32, f32)> {
+        let units = book.order_units.iter();
+        match (side, self.order_type) {
+            (Side::Buy, OrderType::Market) | (Side::Sell, OrderType::Limit) => {
+                units.map(|u| (u.ask_price, u.ask_size)).collect()
+            }
+            (Side::Sell, OrderType::Market) | (Side::Buy, OrderType::Limit) => {
+                units.map(|u| (u.bid_price, u.bid_size)).collect()
+            }
+        }
+    }
+}
+
+/// 📉 호가를 차례로 소진하며 평균 체결가 계산
+/// 전체 잔량보다 많으면 남은 수량은 마지막 호가에 체결된다고 가정
+fn walk_levels(levels: &[(f32, f32)], quantity: f32) -> f32 {
+    if quantity <= 0.0 {
+        return levels[0].0;
+    }
+
+    let mut remaining = quantity;
+    let mut notional = 0.0;
+    for &(price, size) in levels {
+        let take = remaining.min(size);
+        notional += take * price;
+        remaining -= take;
+        if remaining <= 0.0 {
+            break;
+        }
+    }
+    if remaining > 0.0 {
+        notional += remaining * levels[levels.len() - 1].0;
+    }
+
+    notional / quantity
+}
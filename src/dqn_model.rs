@@ -1,28 +1,159 @@
+use crate::env::EnvConfig;
+use crate::normalizer::FeatureNormalizer;
+use crate::schema::{DEPTH_FIELDS, FeatureSchema};
+use crate::types;
 use burn::{
     module::Module,
-    nn::{Linear, LinearConfig},
+    nn::{
+        Linear, LinearConfig, PaddingConfig1d,
+        conv::{Conv1d, Conv1dConfig},
+    },
     tensor::{Tensor, activation::relu, backend::Backend},
 };
+use std::error::Error;
+
+/// 🧩 관측 [batch, 피처 수] → 행동별 Q값 [batch, 3]
+/// 학습(train)과 저장(model_saver) 코드는 이 트레잇만 보고 동작함 (DqnModel, transformer::TransformerDqn)
+pub trait QNetwork<B: Backend>: Module<B> {
+    fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2>;
+}
+
+/// 호가 단계 인코더의 채널 수
+const DEPTH_CHANNELS: usize = 8;
+
+/// 📚 오더북 블록 [batch, levels, 4]를 호가 단계 축으로 1D 합성곱해서 펼침
+/// 이웃한 단계끼리 묶어 보기 때문에 호가창 모양(벽, 얇은 구간)을 잡아낼 수 있음
+#[derive(Module, Debug)]
+pub struct DepthEncoder<B: Backend> {
+    conv1: Conv1d<B>,
+    conv2: Conv1d<B>,
+    levels: usize,
+}
+
+impl<B: Backend> DepthEncoder<B> {
+    pub fn new(device: &B::Device, levels: usize) -> Self {
+        let conv = |channels_in| {
+            Conv1dConfig::new(channels_in, DEPTH_CHANNELS, 3)
+                .with_padding(PaddingConfig1d::Same)
+                .init(device)
+        };
+        Self {
+            conv1: conv(DEPTH_FIELDS.len()),
+            conv2: conv(DEPTH_CHANNELS),
+            levels,
+        }
+    }
+
+    /// 출력 크기 [batch, levels x 채널]
+    pub fn output_size(&self) -> usize {
+        self.levels * DEPTH_CHANNELS
+    }
+
+    pub fn forward(&self, depth: Tensor<B, 3>) -> Tensor<B, 2> {
+        let [batch, _, _] = depth.dims();
+        let x = depth.swap_dims(1, 2); // [batch, 4, levels]: 값 종류가 채널
+        let x = relu(self.conv1.forward(x));
+        let x = relu(self.conv2.forward(x));
+        x.reshape([batch, self.output_size()])
+    }
+}
 
 #[derive(Module, Debug)]
 pub struct DqnModel<B: Backend> {
+    depth: Option<DepthEncoder<B>>, // 있으면 입력의 마지막 levels x 4개를 오더북 블록으로 사용
     fc1: Linear<B>,
     fc2: Linear<B>,
     out: Linear<B>,
 }
 
 impl<B: Backend> DqnModel<B> {
+    /// 기본 스키마(분석기 피처 전부) 크기의 입력
     pub fn new(device: &B::Device) -> Self {
+        Self::with_input(device, FeatureSchema::default().len())
+    }
+
+    /// 입력 크기를 지정 (FeatureSchema::len()과 같아야 함)
+    pub fn with_input(device: &B::Device, input_size: usize) -> Self {
+        Self::build(device, input_size, None)
+    }
+
+    /// 📚 스칼라 피처 scalar_size개 + 오더북 블록 levels x 4 입력
+    /// 오더북 블록은 합성곱 인코더를 거친 뒤 스칼라 피처와 이어 붙여서 MLP에 넣음
+    pub fn with_depth(device: &B::Device, scalar_size: usize, levels: usize) -> Self {
+        let encoder = DepthEncoder::new(device, levels);
+        let hidden_input = scalar_size + encoder.output_size();
+        Self::build(device, hidden_input, Some(encoder))
+    }
+
+    /// 스키마에 오더북 블록이 있으면 with_depth, 없으면 with_input
+    pub fn from_schema(device: &B::Device, schema: &FeatureSchema) -> Self {
+        match schema.depth_levels() {
+            0 => Self::with_input(device, schema.len()),
+            levels => Self::with_depth(device, schema.scalar_len(), levels),
+        }
+    }
+
+    fn build(device: &B::Device, hidden_input: usize, depth: Option<DepthEncoder<B>>) -> Self {
         Self {
-            fc1: LinearConfig::new(12, 32).init(device),
+            depth,
+            fc1: LinearConfig::new(hidden_input, 32).init(device),
             fc2: LinearConfig::new(32, 16).init(device),
             out: LinearConfig::new(16, 3).init(device),
         }
     }
 
     pub fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        let input = match &self.depth {
+            Some(encoder) => {
+                let [batch, width] = input.dims();
+                let split = width - encoder.levels * DEPTH_FIELDS.len();
+                let scalars = input.clone().slice([0..batch, 0..split]);
+                let depth = input.slice([0..batch, split..width]).reshape([
+                    batch,
+                    encoder.levels,
+                    DEPTH_FIELDS.len(),
+                ]);
+                Tensor::cat(vec![scalars, encoder.forward(depth)], 1)
+            }
+            None => input,
+        };
         let x = relu(self.fc1.forward(input));
         let x = relu(self.fc2.forward(x));
         self.out.forward(x)
     }
 }
+
+impl<B: Backend> QNetwork<B> for DqnModel<B> {
+    fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        DqnModel::forward(self, input)
+    }
+}
+
+/// 🎮 관측으로 Q값을 내는 정책 (DqnModel은 상태 없음, 순환 모델은 마켓별 은닉 상태를 이어감)
+pub trait QPolicy {
+    /// 마켓 code의 관측 하나 [1, 피처 수]에 대한 행동별 Q값
+    fn q_values(&mut self, code: &str, observation: Tensor<types::B, 2>) -> Vec<f32>;
+
+    /// 마켓 code의 에피소드가 끝났을 때 호출 (상태가 있으면 초기화)
+    fn reset(&mut self, _code: &str) {}
+
+    /// Env 설정으로 만든 관측을 받을 수 있는지 검사 (기본은 항상 통과)
+    fn check_env(&self, _config: &EnvConfig) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// 이 정책이 받을 관측용 빈 정규화기 (모은 리플레이를 normalize_replay할 때 사용, 기본은 모든 피처 정규화)
+    fn normalizer(&self, schema: &FeatureSchema) -> FeatureNormalizer {
+        FeatureNormalizer::new(schema.len())
+    }
+}
+
+impl QPolicy for DqnModel<types::B> {
+    fn q_values(&mut self, _code: &str, observation: Tensor<types::B, 2>) -> Vec<f32> {
+        self.forward(observation)
+            .into_data()
+            .convert::<f32>()
+            .to_vec()
+            .unwrap()
+    }
+}
@@ -1,88 +1,275 @@
-use crate::analyzer::MarketFeatures;
-use crate::websocket::TickData;
+use crate::analyzer::FeatureSource;
+use crate::costs::{CostModel, Fill, Side};
+use crate::normalizer::FeatureNormalizer;
+use crate::portfolio::Portfolio;
+use crate::replay_log::observation_tensor;
+use crate::reward::{RewardConfig, RewardFn, StepOutcome};
+use crate::schema::FeatureSchema;
+use crate::websocket::{OrderBookData, TickData};
 use burn::tensor::{Tensor, backend::Backend};
 
+/// 매수 수량 결정 방식
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionSize {
+    Quantity(f32),     // 고정 코인 수량
+    CashFraction(f32), // 보유 현금 대비 비율 (1.0 = 전액)
+}
+
+/// 🏁 에피소드 종료 사유
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoneReason {
+    MaxSteps,  // 에피소드 최대 스텝 도달
+    StopOut,   // 평가금액이 손절 기준 아래로 떨어짐
+    EndOfData, // 피드/녹화 데이터가 끝남
+}
+
+/// ⚙️ Env 설정
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub cost_model: CostModel,
+    pub initial_cash: f64, // KRW
+    pub position_size: PositionSize,
+    pub reward: RewardConfig,                  // 보상 함수 (reward.rs)
+    pub max_steps: Option<usize>,              // 에피소드당 최대 스텝
+    pub stop_out: Option<f64>, // 평가금액이 initial_cash x 이 비율 이하면 종료 (0.5 = 반토막)
+    pub schema: FeatureSchema, // 관측 벡터에 넣을 피처와 순서
+    pub normalizer: Option<FeatureNormalizer>, // 관측 정규화 (None이면 원본 값 그대로, freeze하지 않으면 초반엔 통계가 불안정)
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            cost_model: CostModel::default(),
+            initial_cash: 1_000_000.0,
+            position_size: PositionSize::CashFraction(1.0),
+            reward: RewardConfig::default(),
+            max_steps: None,
+            stop_out: None,
+            schema: FeatureSchema::default(),
+            normalizer: None,
+        }
+    }
+}
+
 pub struct Env<B: Backend> {
     pub device: B::Device,
-    pub features: [f32; 12], // 12개 피처 저장
-    pub is_holding: bool,    // 현재 포지션 보유 여부
-    pub entry_price: f32,    // 매수 진입 가격
+    pub features: Vec<f32>, // schema 순서의 피처 값 (정규화 전 원본)
+    pub normalizer: Option<FeatureNormalizer>,
+    pub config: EnvConfig,
+    pub portfolio: Portfolio,             // 현금 / 수량 / 손익
+    pub orderbook: Option<OrderBookData>, // 체결에 쓰는 최신 호가
+    pub last_fill: Option<Fill>,          // 마지막 step에서 발생한 체결
+    pub steps: usize,                     // 이번 에피소드에서 진행한 스텝 수
+    pub done: Option<DoneReason>,         // 에피소드가 끝났으면 종료 사유
+    reward_fn: Box<dyn RewardFn>,
+    last_equity: f64,
+    holding_steps: usize, // 현재 포지션을 들고 있던 스텝 수
 }
 
 impl<B: Backend> Env<B> {
-    /// 🔧 초기화
+    /// 🔧 초기화 (Upbit 기본 수수료, 100만원)
     pub fn new(device: B::Device) -> Self {
+        Self::with_config(device, EnvConfig::default())
+    }
+
+    /// 🔧 설정을 지정해서 초기화
+    pub fn with_config(device: B::Device, config: EnvConfig) -> Self {
         Self {
             device,
-            features: [0.0; 12],
-            is_holding: false,
-            entry_price: 0.0,
+            features: vec![0.0; config.schema.len()],
+            normalizer: config.normalizer.clone(),
+            portfolio: Portfolio::new(config.initial_cash),
+            reward_fn: config.reward.build(),
+            last_equity: config.initial_cash,
+            holding_steps: 0,
+            config,
+            orderbook: None,
+            last_fill: None,
+            steps: 0,
+            done: None,
         }
     }
 
-    /// ✅ 분석기 결과를 기반으로 상태 업데이트
-    pub fn update(&mut self, f: MarketFeatures) {
-        self.features = [
-            f.avg_price,
-            f.price_delta,
-            f.volume_sum,
-            f.volatility,
-            f.imbalance,
-            f.spread,
-            f.ask1_price,
-            f.bid1_price,
-            f.ask_depth_ratio,
-            f.bid_depth_ratio,
-            f.tick_speed,
-            f.last_tick_size,
-        ];
+    /// 🔄 새 에피소드 시작 (계좌/보상 상태 초기화, 피처/호가/정규화 통계는 유지)
+    pub fn reset(&mut self) {
+        self.portfolio = Portfolio::new(self.config.initial_cash);
+        self.reward_fn.reset();
+        self.last_equity = self.config.initial_cash;
+        self.holding_steps = 0;
+        self.last_fill = None;
+        self.steps = 0;
+        self.done = None;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done.is_some()
+    }
+
+    /// 데이터가 끝나서 에피소드를 마감
+    pub fn end_of_data(&mut self) {
+        self.done.get_or_insert(DoneReason::EndOfData);
+    }
+
+    /// 현재 포지션 보유 여부
+    pub fn is_holding(&self) -> bool {
+        self.portfolio.is_holding()
+    }
+
+    /// 📚 최신 호가 반영 (다음 step의 체결가 계산에 사용)
+    pub fn update_orderbook(&mut self, ob: OrderBookData) {
+        self.orderbook = Some(ob);
+    }
+
+    /// ✅ 분석기 결과를 기반으로 상태 업데이트 (정규화 통계도 함께 갱신, frozen이면 유지)
+    pub fn update<F: FeatureSource>(&mut self, f: F) {
+        self.features = self.config.schema.extract(&f);
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            normalizer.update(&self.features);
+        }
     }
 
     /// 🧠 현재 상태를 Tensor로 반환
     pub fn observe(&self) -> Tensor<B, 2> {
-        Tensor::from_floats([self.features], &self.device)
+        observation_tensor(&self.observation(), &self.device)
+    }
+
+    /// 현재 상태를 백엔드와 무관한 벡터로 반환 (리플레이 저장용, 정규화 적용)
+    pub fn observation(&self) -> Vec<f32> {
+        match &self.normalizer {
+            Some(normalizer) => normalizer.normalize(&self.features),
+            None => self.features.clone(),
+        }
     }
 
     /// ⚔️ 에이전트의 행동에 따라 포지션/보상 계산
     /// action: 0 = Buy, 1 = Sell, 2 = Hold
-    pub fn step(&mut self, action: usize, tick: TickData) -> (Tensor<B, 2>, f32) {
-        let current_price = tick.price;
-        let mut reward = 0.0;
+    /// 매수는 ask, 매도는 bid 쪽 호가로 체결되고 수수료/슬리피지를 뺀 금액이 계좌에 반영됨
+    /// 이번 스텝으로 에피소드가 끝났으면 `done`이 채워짐 (다음 에피소드는 `reset` 후 시작)
+    pub fn step(&mut self, action: usize, tick: TickData) -> (Vec<f32>, f32) {
+        self.last_fill = None;
 
-        match action {
+        let (valid, realized_return) = match action {
             0 => {
-                // Buy
-                if !self.is_holding {
-                    self.is_holding = true;
-                    self.entry_price = current_price;
-                    reward = 0.0; // 진입은 보상 없음
-                } else {
-                    reward = -0.01; // 중복 진입 패널티
-                }
+                // Buy: 중복 진입 / 잔고 부족이면 잘못된 행동
+                let valid = !self.is_holding() && self.buy(tick.price);
+                (valid, None)
             }
             1 => {
-                // Sell
-                if self.is_holding {
-                    let profit = current_price - self.entry_price;
-                    reward = profit / self.entry_price; // 수익률 기반 보상
-                    self.is_holding = false;
-                    self.entry_price = 0.0;
+                // Sell: 없는 포지션에서 매도하면 잘못된 행동
+                if self.is_holding() {
+                    let basis = self.portfolio.avg_entry_price * self.portfolio.quantity;
+                    let pnl = self.sell(tick.price);
+                    (true, Some((pnl / basis) as f32)) // 비용 차감 후 수익률
                 } else {
-                    reward = -0.01; // 없는 포지션에서 매도 패널티
+                    (false, None)
                 }
             }
             _ => {
                 // Hold
-                reward = 0.0;
+                (true, None)
             }
+        };
+
+        // 📈 평가금액 기록
+        let equity_before = self.last_equity;
+        let equity = self
+            .portfolio
+            .record(tick.timestamp, self.mark_price(tick.price) as f64);
+        self.last_equity = equity;
+        self.holding_steps = if self.is_holding() {
+            self.holding_steps + 1
+        } else {
+            0
+        };
+
+        // 🏁 종료 조건 확인
+        self.steps += 1;
+        if self
+            .config
+            .stop_out
+            .is_some_and(|ratio| equity <= self.config.initial_cash * ratio)
+        {
+            self.done = Some(DoneReason::StopOut);
+        } else if self.config.max_steps.is_some_and(|max| self.steps >= max) {
+            self.done = Some(DoneReason::MaxSteps);
+        }
+
+        let reward = self.reward_fn.reward(&StepOutcome {
+            action,
+            valid,
+            realized_return,
+            equity_before,
+            equity,
+            peak_equity: self.portfolio.peak_equity(),
+            holding: self.is_holding(),
+            holding_steps: self.holding_steps,
+        });
+
+        // 상태 일부 갱신 (Tick 반영, 스키마에 있는 피처만)
+        self.set_feature("avg_price", tick.price);
+        self.set_feature("volume_sum", tick.volume);
+        self.set_feature("last_tick_size", tick.volume);
+
+        (self.observation(), reward)
+    }
+
+    fn set_feature(&mut self, name: &str, value: f32) {
+        if let Some(i) = self.config.schema.index_of(name) {
+            self.features[i] = value;
+        }
+    }
+
+    /// 평가 기준가: 호가가 있으면 1호가 중간값, 없으면 체결가
+    pub fn mark_price(&self, tick_price: f32) -> f32 {
+        match self
+            .orderbook
+            .as_ref()
+            .and_then(|ob| ob.order_units.first())
+        {
+            Some(unit) => (unit.ask_price + unit.bid_price) / 2.0,
+            None => tick_price,
+        }
+    }
+
+    /// 매수 체결, 살 수 있는 수량이 없으면 false
+    fn buy(&mut self, reference_price: f32) -> bool {
+        let cash = self.portfolio.cash;
+        let mut quantity = match self.config.position_size {
+            PositionSize::Quantity(q) => q,
+            PositionSize::CashFraction(f) => (cash * f as f64 / reference_price as f64) as f32,
+        };
+        if quantity <= 0.0 || reference_price <= 0.0 {
+            return false;
         }
 
-        // 상태 일부 갱신 (Tick 반영)
-        self.features[0] = tick.price; // avg_price
-        self.features[2] = tick.volume; // volume_sum
-        self.features[11] = tick.volume; // last_tick_size
+        // 비용까지 합쳐 현금을 넘으면 수량을 줄여서 다시 체결
+        let mut fill = self.fill(Side::Buy, quantity, reference_price);
+        let cost = (fill.price * fill.quantity + fill.fee) as f64;
+        if cost > cash {
+            quantity *= (cash / cost) as f32 * 0.9999;
+            fill = self.fill(Side::Buy, quantity, reference_price);
+        }
+        if quantity <= 0.0 {
+            return false;
+        }
+
+        self.portfolio.apply_fill(&fill);
+        self.last_fill = Some(fill);
+        true
+    }
+
+    /// 보유 수량 전량 매도, 확정 손익을 반환
+    fn sell(&mut self, reference_price: f32) -> f64 {
+        let quantity = self.portfolio.quantity as f32;
+        let fill = self.fill(Side::Sell, quantity, reference_price);
+        let pnl = self.portfolio.apply_fill(&fill);
+        self.last_fill = Some(fill);
+        pnl
+    }
 
-        let next_state = self.observe();
-        (next_state, reward)
+    fn fill(&self, side: Side, quantity: f32, reference_price: f32) -> Fill {
+        self.config
+            .cost_model
+            .fill(side, quantity, reference_price, self.orderbook.as_ref())
     }
 }
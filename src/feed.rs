@@ -0,0 +1,338 @@
+use crate::recorder::{RecorderConfig, load_recording, spawn_recorder};
+use crate::websocket::{
+    ConnectionState, OrderBookData, OrderBookUnit, RawFrame, RawTap, TickData, WebSocketConfig,
+    upbit_websocket_handler,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::thread::JoinHandle;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+/// 📨 시장 데이터 이벤트 (체결 또는 호가)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketEvent {
+    Tick(TickData),
+    OrderBook(OrderBookData),
+}
+
+impl MarketEvent {
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            MarketEvent::Tick(t) => t.timestamp,
+            MarketEvent::OrderBook(ob) => ob.timestamp,
+        }
+    }
+
+    /// 🏷️ 이벤트가 속한 마켓 코드
+    pub fn code(&self) -> &str {
+        match self {
+            MarketEvent::Tick(t) => &t.code,
+            MarketEvent::OrderBook(ob) => &ob.code,
+        }
+    }
+}
+
+/// 🔌 시장 데이터 공급원 (실시간 소켓, 녹화 파일, 가상 데이터 공통)
+pub trait MarketFeed {
+    /// 다음 이벤트를 순서대로 반환, 더 이상 없으면 None
+    fn next_event(&mut self) -> impl Future<Output = Option<MarketEvent>> + Send;
+}
+
+// === 실시간 Upbit 피드 ===
+
+pub struct LiveFeed {
+    tick_receiver: Receiver<TickData>,
+    order_receiver: Receiver<OrderBookData>,
+    state_receiver: Receiver<ConnectionState>,
+    recording: Option<Recording>,
+}
+
+/// 녹화 중일 때: 녹화기 채널의 유일한 Sender와 녹화 스레드
+/// (웹소켓 쪽은 RawTap으로 약하게만 잡고 있어서 Sender를 놓으면 녹화가 끝남)
+struct Recording {
+    sender: Sender<RawFrame>,
+    tap: RawTap,
+    thread: JoinHandle<io::Result<()>>,
+}
+
+impl LiveFeed {
+    /// 🔧 웹소켓 핸들러를 띄우고 채널을 연결
+    pub fn connect(coin_codes: Vec<String>, config: WebSocketConfig) -> Self {
+        Self::spawn(coin_codes, config, None)
+    }
+
+    /// 📼 수신하는 원본 프레임을 디스크에 녹화하면서 연결
+    pub fn with_recorder(
+        coin_codes: Vec<String>,
+        config: WebSocketConfig,
+        recorder_config: RecorderConfig,
+    ) -> Self {
+        let (sender, thread) = spawn_recorder(recorder_config);
+        let tap = RawTap::new(&sender);
+        let mut feed = Self::spawn(coin_codes, config, Some(tap.clone()));
+        feed.recording = Some(Recording {
+            sender,
+            tap,
+            thread,
+        });
+        feed
+    }
+
+    /// 녹화기가 밀려서 버린 프레임 수 (녹화하지 않으면 0)
+    pub fn dropped_frames(&self) -> u64 {
+        self.recording.as_ref().map_or(0, |r| r.tap.dropped())
+    }
+
+    /// 🛑 녹화를 마무리 (남은 프레임 기록, 파일 flush, gzip 트레일러까지 쓰고 스레드 종료를 기다림)
+    /// drop될 때도 호출되지만, 기록 오류를 받으려면 직접 호출
+    pub fn close(mut self) -> io::Result<()> {
+        self.finish_recording()
+    }
+
+    fn finish_recording(&mut self) -> io::Result<()> {
+        let Some(Recording { sender, thread, .. }) = self.recording.take() else {
+            return Ok(());
+        };
+        drop(sender);
+        thread
+            .join()
+            .map_err(|_| io::Error::other("녹화 스레드가 비정상 종료됨"))?
+    }
+
+    fn spawn(coin_codes: Vec<String>, config: WebSocketConfig, raw_tap: Option<RawTap>) -> Self {
+        let (tick_sender, tick_receiver) = mpsc::channel::<TickData>(100);
+        let (order_sender, order_receiver) = mpsc::channel::<OrderBookData>(100);
+        let (state_sender, state_receiver) = mpsc::channel::<ConnectionState>(16);
+
+        tokio::spawn(upbit_websocket_handler(
+            coin_codes,
+            tick_sender,
+            order_sender,
+            config,
+            state_sender,
+            raw_tap,
+        ));
+
+        Self {
+            tick_receiver,
+            order_receiver,
+            state_receiver,
+            recording: None,
+        }
+    }
+}
+
+impl Drop for LiveFeed {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish_recording() {
+            eprintln!("[Recorder] 녹화 마무리 실패: {}", e);
+        }
+    }
+}
+
+impl MarketFeed for LiveFeed {
+    async fn next_event(&mut self) -> Option<MarketEvent> {
+        loop {
+            tokio::select! {
+                Some(order) = self.order_receiver.recv() => return Some(MarketEvent::OrderBook(order)),
+                Some(tick) = self.tick_receiver.recv() => return Some(MarketEvent::Tick(tick)),
+                Some(state) = self.state_receiver.recv() => {
+                    println!("[Feed] 연결 상태: {:?}", state);
+                    if let ConnectionState::GaveUp { .. } = state {
+                        return None;
+                    }
+                }
+                else => return None,
+            }
+        }
+    }
+}
+
+// === 녹화 파일 피드 ===
+
+/// 📼 저장된 이벤트를 타임스탬프 순서로 재생
+pub struct RecordedFeed {
+    events: VecDeque<MarketEvent>,
+}
+
+impl RecordedFeed {
+    pub fn from_events(mut events: Vec<MarketEvent>) -> Self {
+        events.sort_by_key(|e| e.timestamp()); // 안정 정렬: 같은 시각이면 기록 순서 유지
+        Self {
+            events: events.into(),
+        }
+    }
+
+    /// 📂 한 줄에 이벤트 하나(JSON)씩 기록된 파일을 불러옴
+    pub fn open(path: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::from_events(load_events(path)?))
+    }
+
+    /// 📼 recorder로 녹화한 심볼 디렉토리들을 불러와 시간순으로 합침
+    pub fn from_recording(dir: &Path, symbols: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut events = Vec::new();
+        for symbol in symbols {
+            events.extend(load_recording(dir, symbol)?);
+        }
+        Ok(Self::from_events(events))
+    }
+
+    /// 남은 이벤트를 모두 꺼냄
+    pub fn into_events(self) -> Vec<MarketEvent> {
+        self.events.into()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl MarketFeed for RecordedFeed {
+    async fn next_event(&mut self) -> Option<MarketEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// 📂 save_events로 저장한 파일을 이벤트 목록으로 읽음
+pub fn load_events(path: &str) -> Result<Vec<MarketEvent>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str::<MarketEvent>(&line)?);
+    }
+    Ok(events)
+}
+
+/// 💾 이벤트 목록을 RecordedFeed가 읽을 수 있는 형식으로 저장
+pub fn save_events(events: &[MarketEvent], path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for event in events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// === 가상(랜덤워크) 피드 ===
+
+/// 🎲 시드 고정 랜덤워크 설정
+#[derive(Debug, Clone)]
+pub struct SyntheticConfig {
+    pub seed: u64,
+    pub code: String,
+    pub start_price: f32,
+    pub tick_size: f32,       // 호가 단위
+    pub max_step: i32,        // 한 번에 움직이는 최대 호가 단위 수
+    pub levels: usize,        // 호가 단계 수
+    pub start_timestamp: u64, // ms
+    pub max_interval_ms: u64, // 이벤트 간 최대 간격
+    pub max_events: Option<usize>,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            code: "KRW-SYN".to_string(),
+            start_price: 300.0,
+            tick_size: 0.1,
+            max_step: 2,
+            levels: 15,
+            start_timestamp: 0,
+            max_interval_ms: 500,
+            max_events: None,
+        }
+    }
+}
+
+pub struct SyntheticFeed {
+    config: SyntheticConfig,
+    rng: StdRng,
+    price_ticks: i64, // 현재가 (호가 단위 개수)
+    timestamp: u64,
+    emitted: usize,
+}
+
+impl SyntheticFeed {
+    pub fn new(config: SyntheticConfig) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            price_ticks: (config.start_price / config.tick_size).round() as i64,
+            timestamp: config.start_timestamp,
+            emitted: 0,
+            config,
+        }
+    }
+
+    fn price(&self, ticks: i64) -> f32 {
+        ticks as f32 * self.config.tick_size
+    }
+
+    fn next_orderbook(&mut self) -> OrderBookData {
+        let order_units = (0..self.config.levels as i64)
+            .map(|level| OrderBookUnit {
+                ask_price: self.price(self.price_ticks + 1 + level),
+                ask_size: self.rng.random_range(0.1..10.0),
+                bid_price: self.price(self.price_ticks - level),
+                bid_size: self.rng.random_range(0.1..10.0),
+            })
+            .collect();
+
+        OrderBookData {
+            code: self.config.code.clone(),
+            timestamp: self.timestamp,
+            order_units,
+        }
+    }
+
+    fn next_tick(&mut self) -> TickData {
+        let step = self
+            .rng
+            .random_range(-self.config.max_step..=self.config.max_step);
+        self.price_ticks = (self.price_ticks + step as i64).max(1);
+        let side = if step >= 0 { "BID" } else { "ASK" };
+
+        TickData {
+            code: self.config.code.clone(),
+            price: self.price(self.price_ticks),
+            volume: self.rng.random_range(0.001..1.0),
+            side: side.to_string(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl MarketFeed for SyntheticFeed {
+    async fn next_event(&mut self) -> Option<MarketEvent> {
+        if self
+            .config
+            .max_events
+            .is_some_and(|max| self.emitted >= max)
+        {
+            return None;
+        }
+
+        self.timestamp += self
+            .rng
+            .random_range(1..=self.config.max_interval_ms.max(1));
+        // 호가 → 체결 순서로 번갈아 생성
+        let event = if self.emitted.is_multiple_of(2) {
+            MarketEvent::OrderBook(self.next_orderbook())
+        } else {
+            MarketEvent::Tick(self.next_tick())
+        };
+        self.emitted += 1;
+        Some(event)
+    }
+}
@@ -0,0 +1,415 @@
+use crate::analyzer::{
+    MarketFeatures, MultiHorizonFeatures, OrderBookDepth, TickFeatures, TimeWindow, trade_sign,
+};
+use crate::candles::{BarSpec, CandleEvent, CandleSet};
+use crate::indicators::IndicatorSet;
+use crate::websocket::{OrderBookData, TickData};
+use std::collections::{HashMap, VecDeque};
+
+// ⚡ 증분 분석기
+//
+// analyze()는 호출할 때마다 저장된 틱 전체를 다시 훑음 (O(n))
+// 여기서는 틱이 들어오고 밀려날 때 합계만 고쳐서 피처를 O(1)로 계산
+//
+//   평균가   = 기준가 + Σ(p - 기준가) / n
+//   변동성   = sqrt(Σ(p - 기준가)² / n - (Σ(p - 기준가) / n)²)
+//   체결 속도 = Σ(틱 간 시간 차) / (n - 1)   (정수 합이라 오차 없음)
+//   VWAP     = 기준가 + Σ(p - 기준가)·v / Σv
+//   체결 불균형 = Σ부호·v / Σv
+//
+// 가격은 기준가(최근 재계산 시점의 가장 오래된 체결가)를 뺀 값으로 모아서
+// 큰 가격(KRW-BTC ~1억)에서도 제곱합의 자릿수 손실을 막음
+// 더하고 빼기를 반복하며 쌓이는 오차는 주기적으로 합계를 새로 계산해서 없앰 (분할 상환 O(1))
+
+/// 분석에 필요한 틱 정보만 (코드/방향 문자열은 복사하지 않음)
+#[derive(Debug, Clone, Copy)]
+struct TickPoint {
+    timestamp: u64,
+    price: f32,
+    volume: f32,
+    sign: f32, // 매수 체결 +1, 매도 체결 -1
+}
+
+/// 틱을 밀어내는 기준
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Limit {
+    Count(usize),     // 최근 n개 (MarketStorage와 같음)
+    Time(TimeWindow), // 최근 틱 기준 [최근 - window, 최근]
+}
+
+/// 🧮 밀려나는 틱까지 반영하는 누적 합계
+#[derive(Debug, Clone)]
+struct RollingTicks {
+    limit: Limit,
+    ticks: VecDeque<TickPoint>,
+    anchor: f64,         // 기준가
+    price_sum: f64,      // Σ(p - 기준가)
+    price_sq_sum: f64,   // Σ(p - 기준가)²
+    volume_sum: f64,     // Σ체결량
+    pv_sum: f64,         // Σ(p - 기준가)·v
+    signed_sum: f64,     // Σ부호·v
+    volume_ticks: usize, // 체결량이 0이 아닌 틱 수 (0이면 합계의 잔여 오차를 무시)
+    time_diff_sum: i64,  // Σ(틱 간 시간 차) = 최근 - 가장 오래된 타임스탬프
+    evictions: usize,    // 마지막 재계산 이후 밀려난 틱 수
+}
+
+impl RollingTicks {
+    fn new(limit: Limit) -> Self {
+        Self {
+            limit,
+            ticks: VecDeque::new(),
+            anchor: 0.0,
+            price_sum: 0.0,
+            price_sq_sum: 0.0,
+            volume_sum: 0.0,
+            pv_sum: 0.0,
+            signed_sum: 0.0,
+            volume_ticks: 0,
+            time_diff_sum: 0,
+            evictions: 0,
+        }
+    }
+
+    fn push(&mut self, tick: TickPoint) {
+        if let Limit::Count(capacity) = self.limit {
+            if self.ticks.len() == capacity {
+                self.evict();
+            }
+            if capacity == 0 {
+                return;
+            }
+        }
+
+        if self.ticks.is_empty() {
+            self.anchor = tick.price as f64;
+        }
+        if let Some(last) = self.ticks.back() {
+            self.time_diff_sum += tick.timestamp as i64 - last.timestamp as i64;
+        }
+        self.add(&tick, 1.0);
+        self.ticks.push_back(tick);
+
+        if let Limit::Time(window) = self.limit {
+            while self
+                .ticks
+                .front()
+                .is_some_and(|t| t.timestamp + window.millis < tick.timestamp)
+            {
+                self.evict();
+            }
+        }
+
+        if self.evictions > self.ticks.len().max(64) {
+            self.rebuild();
+        }
+    }
+
+    fn evict(&mut self) {
+        let Some(old) = self.ticks.pop_front() else {
+            return;
+        };
+        if let Some(next) = self.ticks.front() {
+            self.time_diff_sum -= next.timestamp as i64 - old.timestamp as i64;
+        }
+        self.add(&old, -1.0);
+        self.evictions += 1;
+    }
+
+    /// 합계에 틱 하나를 더하거나(+1) 뺌(-1)
+    fn add(&mut self, tick: &TickPoint, direction: f64) {
+        let x = tick.price as f64 - self.anchor;
+        let v = tick.volume as f64;
+        self.price_sum += direction * x;
+        self.price_sq_sum += direction * x * x;
+        self.volume_sum += direction * v;
+        self.pv_sum += direction * x * v;
+        self.signed_sum += direction * tick.sign as f64 * v;
+        if tick.volume != 0.0 {
+            self.volume_ticks = (self.volume_ticks as i64 + direction as i64) as usize;
+        }
+    }
+
+    /// 합계를 처음부터 다시 계산 (기준가도 가장 오래된 체결가로 다시 잡음)
+    fn rebuild(&mut self) {
+        self.anchor = self.ticks.front().map_or(0.0, |t| t.price as f64);
+        self.price_sum = 0.0;
+        self.price_sq_sum = 0.0;
+        self.volume_sum = 0.0;
+        self.pv_sum = 0.0;
+        self.signed_sum = 0.0;
+        self.volume_ticks = 0;
+        let ticks = std::mem::take(&mut self.ticks);
+        for t in &ticks {
+            self.add(t, 1.0);
+        }
+        self.ticks = ticks;
+        self.time_diff_sum = match (self.ticks.front(), self.ticks.back()) {
+            (Some(first), Some(last)) => last.timestamp as i64 - first.timestamp as i64,
+            _ => 0,
+        };
+        self.evictions = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    /// O(1) 틱 피처, 틱이 없으면 None
+    fn features(&self) -> Option<TickFeatures> {
+        let first = self.ticks.front()?;
+        let last = self.ticks.back()?;
+        let n = self.ticks.len() as f64;
+        let mean = self.price_sum / n;
+        let variance = (self.price_sq_sum / n - mean * mean).max(0.0);
+        let tick_speed = if self.ticks.len() >= 2 {
+            self.time_diff_sum as f64 / (n - 1.0)
+        } else {
+            0.0
+        };
+        let trade_intensity = if self.time_diff_sum > 0 {
+            (n - 1.0) * 1000.0 / self.time_diff_sum as f64
+        } else {
+            0.0
+        };
+        let tick_gap = match self.ticks.len() {
+            0 | 1 => 0,
+            n => last.timestamp as i64 - self.ticks[n - 2].timestamp as i64,
+        };
+        let volume_sum = if self.volume_ticks > 0 {
+            self.volume_sum
+        } else {
+            0.0
+        };
+        let avg_price = self.anchor + mean;
+        let (vwap, trade_imbalance) = if volume_sum > 0.0 {
+            (
+                self.anchor + self.pv_sum / volume_sum,
+                self.signed_sum / volume_sum,
+            )
+        } else {
+            (avg_price, 0.0)
+        };
+
+        Some(TickFeatures {
+            avg_price: avg_price as f32,
+            price_delta: last.price - first.price,
+            volume_sum: volume_sum as f32,
+            volatility: variance.sqrt() as f32,
+            tick_speed: tick_speed as f32,
+            last_tick_size: last.volume,
+            vwap: vwap as f32,
+            trade_imbalance: trade_imbalance as f32,
+            trade_intensity: trade_intensity as f32,
+            tick_gap: tick_gap as f32,
+        })
+    }
+}
+
+/// ⚡ 한 마켓의 증분 분석기
+/// analyze / analyze_window / analyze_horizons와 같은 결과를 틱 수와 무관하게 O(1)로 계산
+/// 시간 구간은 capacity와 상관없이 구간 길이만큼 틱을 유지함
+#[derive(Debug, Clone)]
+pub struct IncrementalAnalyzer {
+    base: RollingTicks,
+    windows: Vec<(TimeWindow, RollingTicks)>,
+    orderbook: Option<OrderBookData>,
+    previous_orderbook: Option<OrderBookData>, // OFI 계산용 직전 오더북
+    depth: Option<OrderBookDepth>,             // 최신 오더북의 블록 (오더북이 바뀔 때만 계산)
+    indicators: IndicatorSet,                  // 틱마다 갱신하는 기술적 지표 (기본은 비어 있음)
+    candles: CandleSet,                        // 틱으로 만드는 봉과 최근 봉 기록 (기본은 비어 있음)
+    completed: Vec<CandleEvent>,               // 마지막 틱으로 완성된 봉
+}
+
+impl IncrementalAnalyzer {
+    /// 🔧 최근 capacity개 틱 기준 (MarketStorage::new(capacity) + analyze와 같음)
+    pub fn new(capacity: usize) -> Self {
+        Self::with_windows(capacity, &[])
+    }
+
+    /// 🔧 시간 구간별 피처도 함께 유지
+    pub fn with_windows(capacity: usize, windows: &[TimeWindow]) -> Self {
+        Self {
+            base: RollingTicks::new(Limit::Count(capacity)),
+            windows: windows
+                .iter()
+                .map(|&w| (w, RollingTicks::new(Limit::Time(w))))
+                .collect(),
+            orderbook: None,
+            previous_orderbook: None,
+            depth: None,
+            indicators: IndicatorSet::default(),
+            candles: CandleSet::default(),
+            completed: Vec::new(),
+        }
+    }
+
+    /// 📐 기술적 지표도 함께 갱신
+    pub fn with_indicators(mut self, indicators: IndicatorSet) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /// 🕯️ 봉도 함께 집계 (MarketStorage::with_candles와 같음)
+    pub fn with_candles(mut self, candles: CandleSet) -> Self {
+        self.candles = candles;
+        self
+    }
+
+    /// ✅ Tick 반영 (타임스탬프는 증가하는 순서여야 함)
+    pub fn push_tick(&mut self, tick: &TickData) {
+        let point = TickPoint {
+            timestamp: tick.timestamp,
+            price: tick.price,
+            volume: tick.volume,
+            sign: trade_sign(&tick.side),
+        };
+        self.base.push(point);
+        for (_, rolling) in &mut self.windows {
+            rolling.push(point);
+        }
+        self.indicators.push_tick(tick);
+        self.completed = self.candles.push(tick);
+    }
+
+    /// ✅ OrderBook 반영 (최신 것과 직전 것만 유지)
+    pub fn push_orderbook(&mut self, ob: OrderBookData) {
+        self.depth = Some(OrderBookDepth::new(&ob));
+        self.previous_orderbook = self.orderbook.replace(ob);
+    }
+
+    pub fn orderbook(&self) -> Option<&OrderBookData> {
+        self.orderbook.as_ref()
+    }
+
+    /// 📚 최신 오더북의 [단계, 4] 블록 (관측의 depthN_* 피처용)
+    pub fn depth(&self) -> Option<&OrderBookDepth> {
+        self.depth.as_ref()
+    }
+
+    pub fn indicators(&self) -> &IndicatorSet {
+        &self.indicators
+    }
+
+    pub fn candles(&self) -> &CandleSet {
+        &self.candles
+    }
+
+    /// 🕯️ 마지막 push_tick으로 완성된 봉들 (spec 순서, 없으면 비어 있음)
+    pub fn completed_candles(&self) -> &[CandleEvent] {
+        &self.completed
+    }
+
+    /// 기본 피처에 쓰이는 틱 수
+    pub fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.base.len() == 0
+    }
+
+    /// 🔍 analyze(storage)와 같은 결과 (틱 2개, 오더북 1개 이상 필요)
+    pub fn analyze(&self) -> Option<MarketFeatures> {
+        if !self.is_ready() {
+            return None;
+        }
+        Some(
+            self.base
+                .features()?
+                .with_orderbook(self.orderbook.as_ref()?, self.previous_orderbook.as_ref()),
+        )
+    }
+
+    /// 🔍 analyze_window(storage, window)와 같은 결과 (with_windows에 넣은 구간만)
+    pub fn analyze_window(&self, window: TimeWindow) -> Option<MarketFeatures> {
+        if !self.is_ready() {
+            return None;
+        }
+        let (_, rolling) = self.windows.iter().find(|(w, _)| *w == window)?;
+        Some(
+            rolling
+                .features()?
+                .with_orderbook(self.orderbook.as_ref()?, self.previous_orderbook.as_ref()),
+        )
+    }
+
+    /// 📚 기본 피처 + 유지 중인 모든 구간 피처
+    pub fn analyze_horizons(&self) -> Option<MultiHorizonFeatures> {
+        Some(MultiHorizonFeatures {
+            base: self.analyze()?,
+            windows: self
+                .windows
+                .iter()
+                .map(|&(w, _)| Some((w, self.analyze_window(w)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    fn is_ready(&self) -> bool {
+        self.base.len() >= 2 && self.orderbook.is_some()
+    }
+}
+
+/// 🗂️ 마켓 코드별 증분 분석기 모음 (MultiMarketStorage 대신 사용)
+pub struct MultiMarketAnalyzer {
+    pub markets: HashMap<String, IncrementalAnalyzer>,
+    pub capacity: usize,
+    pub windows: Vec<TimeWindow>,
+    pub indicators: IndicatorSet, // 새 마켓 분석기에 복사해서 붙일 지표 묶음
+    pub candle_specs: Vec<BarSpec>, // 새 마켓 분석기에 붙일 봉 종류
+    pub candle_history: usize,    // 봉 종류별로 보관하는 최근 봉 수
+}
+
+impl MultiMarketAnalyzer {
+    pub fn new(capacity: usize, windows: &[TimeWindow]) -> Self {
+        Self {
+            markets: HashMap::new(),
+            capacity,
+            windows: windows.to_vec(),
+            indicators: IndicatorSet::default(),
+            candle_specs: Vec::new(),
+            candle_history: 0,
+        }
+    }
+
+    /// 🕯️ 마켓마다 봉도 함께 집계 (완성된 봉은 분석기의 completed_candles로)
+    pub fn with_candles(mut self, specs: &[BarSpec], history_len: usize) -> Self {
+        self.candle_specs = specs.to_vec();
+        self.candle_history = history_len;
+        self
+    }
+
+    /// 📐 마켓마다 기술적 지표도 함께 갱신
+    pub fn with_indicators(mut self, indicators: IndicatorSet) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /// ✅ Tick을 해당 마켓 분석기에 반영하고 그 분석기를 반환
+    pub fn push_tick(&mut self, tick: &TickData) -> &IncrementalAnalyzer {
+        let market = self.market_mut(&tick.code);
+        market.push_tick(tick);
+        market
+    }
+
+    /// ✅ OrderBook을 해당 마켓 분석기에 반영하고 그 분석기를 반환
+    pub fn push_orderbook(&mut self, ob: OrderBookData) -> &IncrementalAnalyzer {
+        let market = self.market_mut(&ob.code);
+        market.push_orderbook(ob);
+        market
+    }
+
+    pub fn get(&self, code: &str) -> Option<&IncrementalAnalyzer> {
+        self.markets.get(code)
+    }
+
+    fn market_mut(&mut self, code: &str) -> &mut IncrementalAnalyzer {
+        let (capacity, windows, indicators) = (self.capacity, &self.windows, &self.indicators);
+        let (specs, history) = (&self.candle_specs, self.candle_history);
+        self.markets.entry(code.to_string()).or_insert_with(|| {
+            IncrementalAnalyzer::with_windows(capacity, windows)
+                .with_indicators(indicators.clone())
+                .with_candles(CandleSet::new(specs, history))
+        })
+    }
+}
@@ -0,0 +1,724 @@
+use crate::analyzer::{FeatureSource, TimeWindow};
+use crate::candles::{BarSpec, Candle, CandleSet};
+use crate::schema::FeatureSchema;
+use crate::websocket::TickData;
+use std::collections::VecDeque;
+
+// 📐 기술적 지표 (스트리밍, 값 하나 들어올 때마다 O(1) ~ O(기간))
+//
+// 모든 지표는 워밍업이 끝나기 전에는 None
+// - SMA: 최근 n개 종가 평균
+// - EMA: α = 2 / (n + 1), 처음 n개의 SMA로 시작
+// - RSI: Wilder 평활 (처음 n개 변화량의 평균으로 시작), 종가 n + 1개 필요
+// - MACD: EMA(fast) - EMA(slow), 시그널은 MACD 값의 EMA(signal), 히스토그램 = MACD - 시그널
+// - 볼린저 밴드: SMA(n) ± k x 모표준편차
+// - ATR: True Range의 Wilder 평활 (첫 봉의 TR은 고가 - 저가)
+// - 스토캐스틱: %K = (종가 - n봉 최저) / (n봉 최고 - n봉 최저) x 100, %D = %K의 SMA(d)
+// - OBV: 종가가 오르면 +체결량, 내리면 -체결량 누적 (첫 봉은 0)
+//
+// 틱으로 바로 갱신할 수도 있고(틱 = 고가/저가/종가가 모두 체결가인 봉), 완성된 봉으로 갱신할 수도 있음
+
+/// 🕯️ 지표 입력 하나 (틱이나 완성된 봉)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Bar {
+    /// 종가만 있는 입력 (고가 = 저가 = 종가, 체결량 0)
+    pub fn close(close: f64) -> Self {
+        Self {
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+        }
+    }
+}
+
+impl From<&TickData> for Bar {
+    fn from(tick: &TickData) -> Self {
+        Self {
+            volume: tick.volume as f64,
+            ..Bar::close(tick.price as f64)
+        }
+    }
+}
+
+impl From<&Candle> for Bar {
+    fn from(candle: &Candle) -> Self {
+        Self {
+            high: candle.high as f64,
+            low: candle.low as f64,
+            close: candle.close as f64,
+            volume: candle.volume,
+        }
+    }
+}
+
+/// 📏 단순 이동 평균
+#[derive(Debug, Clone)]
+pub struct Sma {
+    pub period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.window.len() == self.period {
+            self.sum -= self.window.pop_front().unwrap();
+        }
+        self.window.push_back(value);
+        self.sum += value;
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+
+    /// 최근 값들의 모표준편차 (볼린저 밴드용)
+    fn std_dev(&self) -> Option<f64> {
+        let mean = self.value()?;
+        let var = self.window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / self.period as f64;
+        Some(var.sqrt())
+    }
+}
+
+/// 📈 지수 이동 평균
+#[derive(Debug, Clone)]
+pub struct Ema {
+    pub period: usize,
+    alpha: f64,
+    seed: Sma, // 처음 period개는 SMA로 시작값을 만듦
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// Wilder 평활: 처음 period개는 단순 평균, 이후 (이전 x (n - 1) + 새 값) / n
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    count: usize,
+    sum: f64,
+    value: Option<f64>,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            sum: 0.0,
+            value: None,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let n = self.period as f64;
+        self.value = match self.value {
+            Some(prev) => Some((prev * (n - 1.0) + value) / n),
+            None => {
+                self.count += 1;
+                self.sum += value;
+                (self.count == self.period).then(|| self.sum / n)
+            }
+        };
+        self.value
+    }
+}
+
+/// 💪 상대 강도 지수 (0 ~ 100)
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    pub period: usize,
+    prev_close: Option<f64>,
+    gain: Wilder,
+    loss: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_close: None,
+            gain: Wilder::new(period),
+            loss: Wilder::new(period),
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<f64> {
+        if let Some(prev) = self.prev_close.replace(close) {
+            let change = close - prev;
+            self.gain.update(change.max(0.0));
+            self.loss.update((-change).max(0.0));
+        }
+        self.value()
+    }
+
+    /// 하락이 없으면 100, 변화가 전혀 없으면 50
+    pub fn value(&self) -> Option<f64> {
+        let (gain, loss) = (self.gain.value?, self.loss.value?);
+        Some(if loss > 0.0 {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        } else if gain > 0.0 {
+            100.0
+        } else {
+            50.0
+        })
+    }
+}
+
+/// MACD 결과 (시그널/히스토그램은 MACD 값이 signal개 모인 뒤부터)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: Option<f64>,
+    pub histogram: Option<f64>,
+}
+
+/// 〰️ 이동 평균 수렴 확산
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<MacdValue> {
+        let fast = self.fast.update(close);
+        let slow = self.slow.update(close);
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let macd = fast - slow;
+            let signal = self.signal.update(macd);
+            self.value = Some(MacdValue {
+                macd,
+                signal,
+                histogram: signal.map(|s| macd - s),
+            });
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+}
+
+/// 볼린저 밴드 결과
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+    pub bandwidth: f64, // (상단 - 하단) / 중심선
+    pub percent_b: f64, // (종가 - 하단) / (상단 - 하단), 밴드 폭이 0이면 0.5
+}
+
+/// 🎢 볼린저 밴드
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    pub k: f64,
+    sma: Sma,
+    value: Option<BollingerBands>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, k: f64) -> Self {
+        Self {
+            k,
+            sma: Sma::new(period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<BollingerBands> {
+        let middle = self.sma.update(close)?;
+        let offset = self.k * self.sma.std_dev()?;
+        let (upper, lower) = (middle + offset, middle - offset);
+        self.value = Some(BollingerBands {
+            middle,
+            upper,
+            lower,
+            bandwidth: if middle != 0.0 {
+                (upper - lower) / middle
+            } else {
+                0.0
+            },
+            percent_b: if upper > lower {
+                (close - lower) / (upper - lower)
+            } else {
+                0.5
+            },
+        });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<BollingerBands> {
+        self.value
+    }
+}
+
+/// 📊 평균 실제 범위
+#[derive(Debug, Clone)]
+pub struct Atr {
+    pub period: usize,
+    prev_close: Option<f64>,
+    range: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_close: None,
+            range: Wilder::new(period),
+        }
+    }
+
+    pub fn update(&mut self, bar: &Bar) -> Option<f64> {
+        let mut true_range = bar.high - bar.low;
+        if let Some(prev) = self.prev_close {
+            true_range = true_range
+                .max((bar.high - prev).abs())
+                .max((bar.low - prev).abs());
+        }
+        self.prev_close = Some(bar.close);
+        self.range.update(true_range)
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.range.value
+    }
+}
+
+/// 스토캐스틱 결과 (%D는 %K가 d개 모인 뒤부터)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticValue {
+    pub k: f64,
+    pub d: Option<f64>,
+}
+
+/// 🎚️ 스토캐스틱 오실레이터 (%K, %D)
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    pub period: usize,
+    bars: VecDeque<(f64, f64)>, // 최근 period개 (고가, 저가)
+    d: Sma,
+    value: Option<StochasticValue>,
+}
+
+impl Stochastic {
+    pub fn new(period: usize, d_period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            bars: VecDeque::with_capacity(period),
+            d: Sma::new(d_period),
+            value: None,
+        }
+    }
+
+    /// 최근 n봉 범위가 0이면 %K = 50
+    pub fn update(&mut self, bar: &Bar) -> Option<StochasticValue> {
+        if self.bars.len() == self.period {
+            self.bars.pop_front();
+        }
+        self.bars.push_back((bar.high, bar.low));
+        if self.bars.len() < self.period {
+            return None;
+        }
+        let high = self.bars.iter().map(|b| b.0).fold(f64::MIN, f64::max);
+        let low = self.bars.iter().map(|b| b.1).fold(f64::MAX, f64::min);
+        let k = if high > low {
+            100.0 * (bar.close - low) / (high - low)
+        } else {
+            50.0
+        };
+        self.value = Some(StochasticValue {
+            k,
+            d: self.d.update(k),
+        });
+        self.value
+    }
+
+    pub fn value(&self) -> Option<StochasticValue> {
+        self.value
+    }
+}
+
+/// 🌊 거래량 균형 지표
+#[derive(Debug, Clone, Default)]
+pub struct Obv {
+    prev_close: Option<f64>,
+    value: f64,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, bar: &Bar) -> Option<f64> {
+        if let Some(prev) = self.prev_close {
+            if bar.close > prev {
+                self.value += bar.volume;
+            } else if bar.close < prev {
+                self.value -= bar.volume;
+            }
+        }
+        self.prev_close = Some(bar.close);
+        Some(self.value)
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.prev_close.map(|_| self.value)
+    }
+}
+
+/// 🧾 지표 종류와 파라미터 (피처 이름의 앞부분: "rsi_14", "macd_12_26_9", "bb_20_2", "obv" ...)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndicatorSpec {
+    Sma {
+        period: usize,
+    },
+    Ema {
+        period: usize,
+    },
+    Rsi {
+        period: usize,
+    },
+    Macd {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    },
+    Bollinger {
+        period: usize,
+        k: f64,
+    },
+    Atr {
+        period: usize,
+    },
+    Stochastic {
+        period: usize,
+        d_period: usize,
+    },
+    Obv,
+}
+
+impl IndicatorSpec {
+    /// "rsi_14" → Rsi { period: 14 }, 기간은 1 이상
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('_');
+        let kind = parts.next()?;
+        let args: Vec<&str> = parts.collect();
+        let period = |i: usize| args.get(i)?.parse::<usize>().ok().filter(|&p| p > 0);
+        let spec = match (kind, args.len()) {
+            ("sma", 1) => IndicatorSpec::Sma { period: period(0)? },
+            ("ema", 1) => IndicatorSpec::Ema { period: period(0)? },
+            ("rsi", 1) => IndicatorSpec::Rsi { period: period(0)? },
+            ("atr", 1) => IndicatorSpec::Atr { period: period(0)? },
+            ("macd", 3) => IndicatorSpec::Macd {
+                fast: period(0)?,
+                slow: period(1)?,
+                signal: period(2)?,
+            },
+            ("bb", 2) => IndicatorSpec::Bollinger {
+                period: period(0)?,
+                k: args[1].parse().ok().filter(|k: &f64| k.is_finite())?,
+            },
+            ("stoch", 2) => IndicatorSpec::Stochastic {
+                period: period(0)?,
+                d_period: period(1)?,
+            },
+            ("obv", 0) => IndicatorSpec::Obv,
+            _ => return None,
+        };
+        Some(spec)
+    }
+
+    /// parse의 반대
+    pub fn name(&self) -> String {
+        match self {
+            IndicatorSpec::Sma { period } => format!("sma_{}", period),
+            IndicatorSpec::Ema { period } => format!("ema_{}", period),
+            IndicatorSpec::Rsi { period } => format!("rsi_{}", period),
+            IndicatorSpec::Atr { period } => format!("atr_{}", period),
+            IndicatorSpec::Macd { fast, slow, signal } => {
+                format!("macd_{}_{}_{}", fast, slow, signal)
+            }
+            IndicatorSpec::Bollinger { period, k } => format!("bb_{}_{}", period, k),
+            IndicatorSpec::Stochastic { period, d_period } => {
+                format!("stoch_{}_{}", period, d_period)
+            }
+            IndicatorSpec::Obv => "obv".to_string(),
+        }
+    }
+
+    /// ":" 뒤에 올 수 있는 출력 이름 (첫 번째가 기본값)
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            IndicatorSpec::Macd { .. } => &["macd", "signal", "hist"],
+            IndicatorSpec::Bollinger { .. } => &["middle", "upper", "lower", "width", "percent_b"],
+            IndicatorSpec::Stochastic { .. } => &["k", "d"],
+            _ => &["value"],
+        }
+    }
+
+    pub fn build(&self) -> Indicator {
+        match *self {
+            IndicatorSpec::Sma { period } => Indicator::Sma(Sma::new(period)),
+            IndicatorSpec::Ema { period } => Indicator::Ema(Ema::new(period)),
+            IndicatorSpec::Rsi { period } => Indicator::Rsi(Rsi::new(period)),
+            IndicatorSpec::Macd { fast, slow, signal } => {
+                Indicator::Macd(Macd::new(fast, slow, signal))
+            }
+            IndicatorSpec::Bollinger { period, k } => {
+                Indicator::Bollinger(Bollinger::new(period, k))
+            }
+            IndicatorSpec::Atr { period } => Indicator::Atr(Atr::new(period)),
+            IndicatorSpec::Stochastic { period, d_period } => {
+                Indicator::Stochastic(Stochastic::new(period, d_period))
+            }
+            IndicatorSpec::Obv => Indicator::Obv(Obv::new()),
+        }
+    }
+}
+
+/// 🧮 종류와 상관없이 같은 방식으로 갱신/조회하는 지표
+#[derive(Debug, Clone)]
+pub enum Indicator {
+    Sma(Sma),
+    Ema(Ema),
+    Rsi(Rsi),
+    Macd(Macd),
+    Bollinger(Bollinger),
+    Atr(Atr),
+    Stochastic(Stochastic),
+    Obv(Obv),
+}
+
+impl Indicator {
+    /// 종가만 쓰는 지표는 bar.close로, 나머지는 고가/저가/체결량까지 사용
+    pub fn update(&mut self, bar: &Bar) {
+        match self {
+            Indicator::Sma(i) => {
+                i.update(bar.close);
+            }
+            Indicator::Ema(i) => {
+                i.update(bar.close);
+            }
+            Indicator::Rsi(i) => {
+                i.update(bar.close);
+            }
+            Indicator::Macd(i) => {
+                i.update(bar.close);
+            }
+            Indicator::Bollinger(i) => {
+                i.update(bar.close);
+            }
+            Indicator::Atr(i) => {
+                i.update(bar);
+            }
+            Indicator::Stochastic(i) => {
+                i.update(bar);
+            }
+            Indicator::Obv(i) => {
+                i.update(bar);
+            }
+        }
+    }
+
+    /// 출력 이름(IndicatorSpec::fields)으로 현재 값 조회, 워밍업 중이거나 없는 이름이면 None
+    pub fn output(&self, field: &str) -> Option<f64> {
+        match (self, field) {
+            (Indicator::Sma(i), "value") => i.value(),
+            (Indicator::Ema(i), "value") => i.value(),
+            (Indicator::Rsi(i), "value") => i.value(),
+            (Indicator::Atr(i), "value") => i.value(),
+            (Indicator::Obv(i), "value") => i.value(),
+            (Indicator::Macd(i), "macd") => Some(i.value()?.macd),
+            (Indicator::Macd(i), "signal") => i.value()?.signal,
+            (Indicator::Macd(i), "hist") => i.value()?.histogram,
+            (Indicator::Bollinger(i), field) => {
+                let bands = i.value()?;
+                match field {
+                    "middle" => Some(bands.middle),
+                    "upper" => Some(bands.upper),
+                    "lower" => Some(bands.lower),
+                    "width" => Some(bands.bandwidth),
+                    "percent_b" => Some(bands.percent_b),
+                    _ => None,
+                }
+            }
+            (Indicator::Stochastic(i), "k") => Some(i.value()?.k),
+            (Indicator::Stochastic(i), "d") => i.value()?.d,
+            _ => None,
+        }
+    }
+}
+
+/// 🏷️ 관측 피처로 쓰는 지표 이름: 지표[:출력][@봉]
+/// "rsi_14" (틱마다), "rsi_14@1m" (1분봉), "macd_12_26_9:signal@5m", "bb_20_2:percent_b"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndicatorFeature {
+    pub spec: IndicatorSpec,
+    pub field: &'static str,
+    pub bar: Option<BarSpec>, // None이면 틱마다 갱신
+}
+
+impl IndicatorFeature {
+    pub fn parse(name: &str) -> Option<Self> {
+        let (rest, bar) = match name.split_once('@') {
+            Some((rest, label)) => (rest, Some(TimeWindow::parse(label)?)),
+            None => (name, None),
+        };
+        let (spec, field) = match rest.split_once(':') {
+            Some((spec, field)) => (IndicatorSpec::parse(spec)?, Some(field)),
+            None => (IndicatorSpec::parse(rest)?, None),
+        };
+        let fields = spec.fields();
+        let field = match field {
+            Some(field) => *fields.iter().find(|&&f| f == field)?,
+            None => fields[0],
+        };
+        Some(Self {
+            spec,
+            field,
+            bar: bar.map(|w| BarSpec::Time { millis: w.millis }),
+        })
+    }
+}
+
+/// 📚 한 마켓의 지표 묶음 (FeatureSchema의 지표 이름으로 구성)
+/// 틱 지표는 틱마다, 봉 지표는 직접 만드는 시간 봉이 완성될 때마다 갱신
+/// 같은 지표/봉 조합은 출력이 여러 개여도 한 번만 계산함
+#[derive(Debug, Clone, Default)]
+pub struct IndicatorSet {
+    indicators: Vec<(IndicatorSpec, Option<BarSpec>, Indicator)>,
+    candles: CandleSet,
+    features: Vec<IndicatorFeature>, // 관측에 쓰는 출력 (워밍업 판단용)
+}
+
+impl IndicatorSet {
+    pub fn new(features: &[IndicatorFeature]) -> Self {
+        let mut set = Self::default();
+        let mut specs = Vec::new();
+        for feature in features {
+            if set.find(feature).is_none() {
+                set.indicators
+                    .push((feature.spec, feature.bar, feature.spec.build()));
+            }
+            if let Some(bar) = feature.bar.filter(|b| !specs.contains(b)) {
+                specs.push(bar);
+            }
+        }
+        set.candles = CandleSet::new(&specs, 0);
+        set.features = features.to_vec();
+        set
+    }
+
+    /// 스키마에서 지표 이름만 골라서 구성 (지표가 없으면 빈 묶음)
+    pub fn from_schema(schema: &FeatureSchema) -> Self {
+        let features: Vec<IndicatorFeature> = schema
+            .names
+            .iter()
+            .filter_map(|name| IndicatorFeature::parse(name))
+            .collect();
+        Self::new(&features)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indicators.is_empty()
+    }
+
+    /// ✅ 틱 반영 (틱 지표 갱신, 봉이 완성되면 그 봉의 지표도 갱신)
+    pub fn push_tick(&mut self, tick: &TickData) {
+        if self.is_empty() {
+            return;
+        }
+        let bar = Bar::from(tick);
+        let completed = self.candles.push(tick);
+        for (_, spec, indicator) in &mut self.indicators {
+            match spec {
+                None => indicator.update(&bar),
+                Some(spec) => {
+                    for event in completed.iter().filter(|e| e.spec == *spec) {
+                        indicator.update(&Bar::from(&event.candle));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 🔥 관측에 쓰는 모든 출력이 값을 내는지 (지표가 없으면 항상 true)
+    /// 워밍업 중인 출력은 관측에서 0.0이 되므로, 이 값이 true가 되기 전에는 의사결정을 하지 않음
+    pub fn is_warm(&self) -> bool {
+        self.features.iter().all(|f| self.get(f).is_some())
+    }
+
+    /// 🔎 현재 값 (워밍업 중이면 None)
+    pub fn get(&self, feature: &IndicatorFeature) -> Option<f64> {
+        self.find(feature)?.output(feature.field)
+    }
+
+    fn find(&self, feature: &IndicatorFeature) -> Option<&Indicator> {
+        self.indicators
+            .iter()
+            .find(|(spec, bar, _)| *spec == feature.spec && *bar == feature.bar)
+            .map(|(_, _, indicator)| indicator)
+    }
+}
+
+impl FeatureSource for IndicatorSet {
+    fn value(&self, name: &str) -> Option<f32> {
+        self.get(&IndicatorFeature::parse(name)?).map(|v| v as f32)
+    }
+}
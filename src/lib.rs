@@ -0,0 +1,30 @@
+pub mod agent;
+pub mod analyzer;
+pub mod backtest;
+pub mod candles;
+pub mod costs;
+pub mod dqn_model;
+pub mod env;
+pub mod feed;
+pub mod incremental;
+pub mod indicators;
+pub mod model_saver;
+pub mod normalizer;
+pub mod portfolio;
+pub mod recorder;
+pub mod recurrent;
+pub mod replay_file;
+pub mod replay_loader;
+pub mod replay_log;
+pub mod replay_saver;
+pub mod replaybuffer;
+pub mod reward;
+pub mod schema;
+pub mod target_net;
+pub mod trading_loop;
+pub mod train;
+pub mod train_loop;
+pub mod transformer;
+pub mod types;
+pub mod upbit;
+pub mod websocket;
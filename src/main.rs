@@ -1,39 +1,228 @@
-
-mod websocket;
-
+use burn::tensor::backend::Backend;
+use burn_basics::agent::Agent;
+use burn_basics::backtest::{BacktestConfig, run_backtest};
+use burn_basics::dqn_model::{DqnModel, QPolicy};
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::feed::{LiveFeed, MarketFeed, RecordedFeed, SyntheticConfig, SyntheticFeed};
+use burn_basics::model_saver::{copy_normalizer, load_model, load_normalizer, save_model};
+use burn_basics::normalizer::{normalize_replay, stats_path};
+use burn_basics::recorder::RecorderConfig;
+use burn_basics::replay_file::{csv_to_replay_file, replay_file_to_csv};
+use burn_basics::replay_loader::read_replay_csv_schema;
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::reward::RewardConfig;
+use burn_basics::schema::FeatureSchema;
+use burn_basics::trading_loop::run_trading_loop;
+use burn_basics::train_loop::run_training;
+use burn_basics::types::B;
+use burn_basics::websocket::WebSocketConfig;
+use std::collections::HashMap;
+use std::error::Error;
 use std::io::{self, Write};
-use tokio::sync::mpsc;
-use tokio::time::{Duration, timeout};
-use websocket::{OrderBookData, TickData, upbit_websocket_handler};
-
+use std::path::Path;
 
+/// 사용법:
+///   cargo run                       → 심볼 입력 후 실시간 Upbit
+///   cargo run -- live KRW-BTC,KRW-ETH → 실시간 Upbit (여러 마켓은 쉼표로 구분)
+///   cargo run -- recorded <파일>    → 저장된 이벤트 재생
+///   cargo run -- recorded <디렉토리> <심볼들> → recorder로 녹화한 원본 재생
+///   cargo run -- record KRW-BTC,KRW-ETH [디렉토리] → 실시간 원본 프레임 녹화만 수행
+///   cargo run -- synthetic [시드]   → 시드 고정 랜덤워크
+///   cargo run -- backtest <파일 | 디렉토리 심볼> [--model 경로] [--reward 설정.json] → 녹화 데이터로 백테스트
+///   cargo run -- train [replay.csv] [에포크] [배치] → 리플레이 CSV로 학습 후 dqn_model 저장 (정규화 통계 포함)
+///   cargo run -- convert <입력.csv | 입력.bin> <출력> → 리플레이 CSV ↔ 바이너리 변환
 #[tokio::main]
 async fn main() {
-    let coin = get_coin_symbol();
-
-    let (tick_sender, mut tick_receiver) = mpsc::channel::<TickData>(100);
-    let (order_sender, mut order_receiver) = mpsc::channel::<OrderBookData>(100);
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
     println!("MAIN");
-    tokio::spawn(
-    upbit_websocket_handler(coin.clone(), tick_sender,order_sender));
-
-    loop {
-        if let Ok(Some(order)) = timeout(Duration::from_millis(10), order_receiver.recv()).await {
-            println!("{:?}", order);
+    match args.first().map(String::as_str) {
+        Some("recorded") => {
+            let path = args.get(1).expect("녹화 파일 경로가 필요합니다");
+            let feed = open_recorded(path, args.get(2)).expect("녹화 파일 로드 실패");
+            run(feed).await;
+        }
+        Some("record") => {
+            let coins = args
+                .get(1)
+                .map(|a| parse_symbols(a))
+                .unwrap_or_else(get_coin_symbols);
+            let dir = args.get(2).map(String::as_str).unwrap_or("recordings");
+            let mut feed = LiveFeed::with_recorder(
+                coins,
+                WebSocketConfig::default(),
+                RecorderConfig::new(dir),
+            );
+            let mut count: u64 = 0;
+            while feed.next_event().await.is_some() {
+                count += 1;
+                if count.is_multiple_of(1000) {
+                    println!("📼 녹화 중: {}개 이벤트", count);
+                }
+            }
+        }
+        Some("backtest") => backtest(&args[1..]),
+        Some("convert") => {
+            let (Some(input), Some(output)) = (args.get(1), args.get(2)) else {
+                panic!("입력/출력 경로가 필요합니다");
+            };
+            let converted = if input.ends_with(".csv") {
+                csv_to_replay_file(input, output, 3)
+            } else {
+                replay_file_to_csv(input, output)
+            };
+            match converted {
+                Ok(count) => println!("🔄 {}개 변환 완료 → {}", count, output),
+                Err(e) => eprintln!("❗ 변환 실패: {}", e),
+            }
+        }
+        Some("train") => {
+            let path = args.get(1).map(String::as_str).unwrap_or("replay.csv");
+            let number = |i: usize, default: usize| {
+                args.get(i)
+                    .map(|s| s.parse().expect("에포크/배치는 숫자여야 합니다"))
+                    .unwrap_or(default)
+            };
+            if let Some(model) = run_training(path, number(2, 100), number(3, 32)) {
+                let schema = read_replay_csv_schema(path).expect("리플레이 헤더 읽기 실패");
+                save_model(&model, &schema, "dqn_model");
+                // 리플레이를 정규화한 통계를 모델 옆으로 복사
+                copy_normalizer(path, "dqn_model").expect("정규화 통계 복사 실패");
+                println!("💾 모델 저장 완료 → dqn_model");
+            }
         }
+        Some("synthetic") => {
+            let seed = args
+                .get(1)
+                .map(|s| s.parse().expect("시드는 숫자여야 합니다"));
+            let config = SyntheticConfig {
+                seed: seed.unwrap_or(42),
+                ..SyntheticConfig::default()
+            };
+            run(SyntheticFeed::new(config)).await;
+        }
+        Some("live") => {
+            let coins = args
+                .get(1)
+                .map(|a| parse_symbols(a))
+                .unwrap_or_else(get_coin_symbols);
+            run(LiveFeed::connect(coins, WebSocketConfig::default())).await;
+        }
+        _ => {
+            let coins = get_coin_symbols();
+            run(LiveFeed::connect(coins, WebSocketConfig::default())).await;
+        }
+    }
+}
+
+async fn run<F: MarketFeed>(mut feed: F) {
+    let device = <B as Backend>::Device::default();
+    let mut agent = Agent::new(1.0);
+    let mut model = DqnModel::<B>::new(&device);
+    let mut envs: HashMap<String, Env<B>> = HashMap::new();
+    let schema = FeatureSchema::default();
+    let env_config = EnvConfig {
+        schema: schema.clone(),
+        ..EnvConfig::default()
+    };
+
+    // 원본 피처로 모은 뒤 모든 마켓을 하나의 통계로 정규화 (backtest가 같은 통계를 freeze해서 사용)
+    let mut replay = run_trading_loop(
+        &mut agent,
+        &mut model,
+        &mut envs,
+        &env_config,
+        &mut feed,
+        &device,
+    )
+    .await;
+    println!("📦 수집된 경험: {}개", replay.len());
+    // 정책이 원본으로 받는 피처(트랜스포머의 tick_gap 등)는 그대로 둠
+    let stats = normalize_replay(&mut replay, model.normalizer(&schema));
+    save_replay_csv(&replay, &schema, "replay.csv");
 
-        if let Ok(Some(tick)) = timeout(Duration::from_millis(10), tick_receiver.recv()).await {
-            println!("{:?}", tick);
+    // 정규화 통계는 리플레이 옆에 저장 (train이 모델 옆으로 옮김)
+    if let Err(e) = stats.save(stats_path("replay.csv")) {
+        eprintln!("❗ 정규화 통계 저장 실패: {}", e);
+    }
+}
+
+fn backtest(args: &[String]) {
+    let model_path = flag_value(args, "--model");
+    let reward_path = flag_value(args, "--reward");
+    let positional: Vec<&String> = args.iter().take_while(|a| !a.starts_with("--")).collect();
+
+    let path = positional.first().expect("녹화 파일 경로가 필요합니다");
+    let events = open_recorded(path, positional.get(1).copied())
+        .expect("녹화 데이터 로드 실패")
+        .into_events();
+
+    let mut config = BacktestConfig::default();
+    if let Some(p) = reward_path {
+        config.env.reward = RewardConfig::from_json_file(p).expect("보상 설정 로드 실패");
+    }
+    let device = <B as Backend>::Device::default();
+    B::seed(config.seed);
+    let model = match model_path {
+        Some(p) => {
+            // 학습 때의 스케일링을 그대로 사용 (freeze된 통계)
+            config.env.normalizer =
+                load_normalizer(p, &config.env.schema).expect("정규화 통계 로드 실패");
+            load_model(p, &config.env.schema, &device).expect("모델 로드 실패")
         }
+        None => DqnModel::<B>::new(&device),
+    };
+
+    let result = run_backtest(&events, &model, &config);
+    println!(
+        "📊 백테스트 완료: 이벤트 {}개, 의사결정 {}회, 거래 {}회, 누적 보상 {:.5}",
+        result.events,
+        result.steps.len(),
+        result.trades,
+        result.total_reward
+    );
+    for (code, portfolio) in &result.portfolios {
+        let last = portfolio.equity_history.back().map(|p| p.equity);
+        println!(
+            "💼 {}: 평가금액 {:.0} / 실현손익 {:.0} / 수수료 {:.0} / 최대낙폭 {:.2}%",
+            code,
+            last.unwrap_or(portfolio.initial_cash),
+            portfolio.realized_pnl,
+            portfolio.fees_paid,
+            portfolio.max_drawdown() * 100.0
+        );
     }
 }
 
-fn get_coin_symbol() -> String {
-    print!("💬 구독할 코인 심볼을 입력하세요 (예: KRW-BTC): ");
+/// `--이름 값` 형태의 옵션 값
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    let i = args.iter().position(|a| a == name)?;
+    args.get(i + 1)
+}
+
+/// 심볼이 주어지면 recorder 디렉토리, 아니면 save_events 파일로 간주
+fn open_recorded(path: &str, symbols: Option<&String>) -> Result<RecordedFeed, Box<dyn Error>> {
+    match symbols {
+        Some(symbols) => RecordedFeed::from_recording(Path::new(path), &parse_symbols(symbols)),
+        None => RecordedFeed::open(path),
+    }
+}
+
+fn get_coin_symbols() -> Vec<String> {
+    print!("💬 구독할 코인 심볼을 입력하세요 (예: KRW-BTC,KRW-ETH): ");
     io::stdout().flush().unwrap();
 
     let mut input = String::new();
     io::stdin().read_line(&mut input).unwrap();
-    input.trim().to_string()
-}
\ No newline at end of file
+    parse_symbols(&input)
+}
+
+/// "KRW-BTC, KRW-ETH" → ["KRW-BTC", "KRW-ETH"]
+fn parse_symbols(input: &str) -> Vec<String> {
+    input
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
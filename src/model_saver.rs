@@ -1,28 +1,153 @@
 // src/model_saver.rs
 
 use crate::dqn_model::DqnModel;
+use crate::normalizer::{FeatureNormalizer, stats_path};
+use crate::schema::FeatureSchema;
+use crate::transformer::{TransformerConfig, TransformerDqn};
 use crate::types::B;
+use burn::module::Module;
 use burn::record::CompactRecorder;
 use burn::record::Recorder;
+use std::error::Error;
 use std::path::Path;
-use burn::module::Module;
 
 // 폴더 없이 복사하는 수 있게 model_path는 Path 파라미터로 만들어줌.
+// 모델 옆에 {model_path}.schema.json 으로 피처 스키마를,
+// {model_path}.norm.json 으로 정규화 통계를 함께 저장함.
+
+/// 모델과 함께 저장되는 스키마 파일 경로
+pub fn schema_path(model_path: &str) -> String {
+    format!("{}.schema.json", model_path)
+}
 
-pub fn save_model(model: &DqnModel<B>, model_path: &str) {
+/// 모델은 DqnModel이나 transformer::TransformerDqn 등 아무 모듈
+pub fn save_model<M: Module<B>>(model: &M, schema: &FeatureSchema, model_path: &str) {
     let recorder = CompactRecorder::new();
-    model.clone()
+    model
+        .clone()
         .save_file(model_path, &recorder)
         .expect("모델 저장 실패");
+    schema
+        .save(schema_path(model_path))
+        .expect("스키마 저장 실패");
+}
+
+/// 저장된 스키마가 expected와 다르면 오류
+/// 스키마 파일이 없는 예전 모델은 기본 스키마(분석기 피처 12개)로 간주
+pub fn load_model(
+    model_path: &str,
+    expected: &FeatureSchema,
+    device: &<B as burn::tensor::backend::Backend>::Device,
+) -> Result<DqnModel<B>, Box<dyn Error>> {
+    let saved = load_schema(model_path)?;
+    expected.ensure_matches(&saved)?;
+    load_weights(model_path, DqnModel::from_schema(device, &saved), device)
 }
 
-pub fn load_model(model_path: &str, device: &<B as burn::tensor::backend::Backend>::Device) -> DqnModel<B> {
+/// 🧩 구조를 미리 만든 모델(model)에 저장된 가중치를 채움 (DqnModel 외의 QNetwork용)
+/// 저장된 스키마가 expected와 다르면 오류
+pub fn load_model_into<M: Module<B>>(
+    model_path: &str,
+    expected: &FeatureSchema,
+    model: M,
+    device: &<B as burn::tensor::backend::Backend>::Device,
+) -> Result<M, Box<dyn Error>> {
+    expected.ensure_matches(&load_schema(model_path)?)?;
+    load_weights(model_path, model, device)
+}
+
+/// 모델 옆의 스키마, 파일이 없는 예전 모델은 기본 스키마
+fn load_schema(model_path: &str) -> Result<FeatureSchema, Box<dyn Error>> {
+    let schema_file = schema_path(model_path);
+    if Path::new(&schema_file).exists() {
+        FeatureSchema::load(&schema_file)
+    } else {
+        Ok(FeatureSchema::default())
+    }
+}
+
+fn load_weights<M: Module<B>>(
+    model_path: &str,
+    model: M,
+    device: &<B as burn::tensor::backend::Backend>::Device,
+) -> Result<M, Box<dyn Error>> {
     let recorder = CompactRecorder::new();
 
     // 작성된 Record를 로드해서 다시 메뉴 플레이스로 사용
-    let record = recorder
-        .load(Path::new(model_path).to_path_buf(), device)
-        .expect("모델 로드 실패");
+    let record = recorder.load(Path::new(model_path).to_path_buf(), device)?;
+
+    Ok(model.load_record(record))
+}
+
+/// 트랜스포머 구조 설정 파일 경로
+pub fn transformer_config_path(model_path: &str) -> String {
+    format!("{}.transformer.json", model_path)
+}
+
+/// 🤖 트랜스포머 모델은 구조 설정도 함께 저장 (로드할 때 같은 구조로 만들어야 함)
+pub fn save_transformer(
+    model: &TransformerDqn<B>,
+    config: &TransformerConfig,
+    schema: &FeatureSchema,
+    model_path: &str,
+) {
+    save_model(model, schema, model_path);
+    config
+        .save(transformer_config_path(model_path))
+        .expect("트랜스포머 설정 저장 실패");
+}
+
+/// 저장된 설정으로 구조를 만들고 가중치를 채움, 설정 파일이 없거나 스키마가 다르면 오류
+pub fn load_transformer(
+    model_path: &str,
+    expected: &FeatureSchema,
+    device: &<B as burn::tensor::backend::Backend>::Device,
+) -> Result<(TransformerDqn<B>, TransformerConfig), Box<dyn Error>> {
+    let config = TransformerConfig::load(transformer_config_path(model_path))?;
+    let model = TransformerDqn::new(device, expected, &config)?;
+    Ok((
+        load_model_into(model_path, expected, model, device)?,
+        config,
+    ))
+}
+
+/// 학습 때 쓴 정규화 통계를 모델 옆에 저장
+pub fn save_normalizer(
+    normalizer: &FeatureNormalizer,
+    model_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    normalizer.save(stats_path(model_path))
+}
+
+/// 리플레이 옆의 정규화 통계를 모델 옆으로 복사 (리플레이에 통계가 없으면 false)
+pub fn copy_normalizer(replay_path: &str, model_path: &str) -> Result<bool, Box<dyn Error>> {
+    let path = stats_path(replay_path);
+    if !Path::new(&path).exists() {
+        return Ok(false);
+    }
+    save_normalizer(&FeatureNormalizer::load(&path)?, model_path)?;
+    Ok(true)
+}
 
-    DqnModel::new(device).load_record(record)
+/// 모델 옆의 정규화 통계를 읽어 freeze해서 반환 (파일이 없으면 None)
+/// 피처 수가 스키마와 다르면 오류
+pub fn load_normalizer(
+    model_path: &str,
+    schema: &FeatureSchema,
+) -> Result<Option<FeatureNormalizer>, Box<dyn Error>> {
+    let path = stats_path(model_path);
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+    let mut normalizer = FeatureNormalizer::load(&path)?;
+    if normalizer.width() != schema.len() {
+        return Err(format!(
+            "정규화 통계의 피처 수 {}가 스키마의 {}와 다릅니다",
+            normalizer.width(),
+            schema.len()
+        )
+        .into());
+    }
+    normalizer.freeze();
+    Ok(Some(normalizer))
 }
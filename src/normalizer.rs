@@ -0,0 +1,169 @@
+use crate::replay_log::ReplaySample;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// 📏 피처별 이동 평균/분산 (Welford) 기반 정규화기
+/// 가격(~수백), 체결 간격(ms, ~수천), 비율([0, 1])처럼 단위가 다른 피처를 같은 크기로 맞춤
+/// 학습할 때 모은 통계를 모델 옆에 저장해 두고, 추론할 때는 freeze해서 그대로 사용
+/// freeze하지 않고 관측마다 갱신하면 초반 관측은 그때까지의 통계로 정규화됨
+/// (관측 2개 미만이면 분산 1로 보고 평균만 뺌) → 학습 데이터는 normalize_replay로 두 번에 나눠 정규화
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureNormalizer {
+    pub count: u64,
+    pub mean: Vec<f64>,
+    pub m2: Vec<f64>,      // 편차 제곱합 (분산 = m2 / count)
+    pub clip: Option<f32>, // 정규화 후 [-clip, clip]으로 자름
+    pub frozen: bool,      // true면 update가 통계를 바꾸지 않음
+    #[serde(default)]
+    pub raw: Vec<usize>, // 정규화/클리핑 없이 원본 그대로 두는 피처 위치 (통계는 그대로 모음)
+}
+
+/// 기본 클리핑 범위 (표준편차 단위)
+pub const DEFAULT_CLIP: f32 = 5.0;
+
+/// 표준편차가 이보다 작은 피처는 스케일링하지 않음 (상수 피처에서 0으로 나누는 것 방지)
+const MIN_STD: f64 = 1e-8;
+
+impl FeatureNormalizer {
+    pub fn new(width: usize) -> Self {
+        Self {
+            count: 0,
+            mean: vec![0.0; width],
+            m2: vec![0.0; width],
+            clip: Some(DEFAULT_CLIP),
+            frozen: false,
+            raw: Vec::new(),
+        }
+    }
+
+    pub fn with_clip(mut self, clip: Option<f32>) -> Self {
+        self.clip = clip;
+        self
+    }
+
+    /// index 위치의 피처는 원본 값 그대로 (예: 경과 시간 인코딩에 쓰는 tick_gap)
+    pub fn with_raw(mut self, index: usize) -> Self {
+        if !self.raw.contains(&index) {
+            self.raw.push(index);
+        }
+        self
+    }
+
+    pub fn is_raw(&self, index: usize) -> bool {
+        self.raw.contains(&index)
+    }
+
+    pub fn width(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// ➕ 관측 하나로 통계 갱신 (frozen이면 무시)
+    pub fn update(&mut self, observation: &[f32]) {
+        if self.frozen {
+            return;
+        }
+        self.count += 1;
+        let n = self.count as f64;
+        for ((mean, m2), &x) in self.mean.iter_mut().zip(&mut self.m2).zip(observation) {
+            let x = x as f64;
+            let delta = x - *mean;
+            *mean += delta / n;
+            *m2 += delta * (x - *mean);
+        }
+    }
+
+    /// 🔀 다른 정규화기의 통계를 합침 (Chan et al. 병렬 분산 공식)
+    /// 마켓별로 따로 모은 통계를 하나로 저장할 때 사용
+    pub fn merge(&mut self, other: &FeatureNormalizer) {
+        if other.count == 0 {
+            return;
+        }
+        let (n_a, n_b) = (self.count as f64, other.count as f64);
+        let n = n_a + n_b;
+        for i in 0..self.width().min(other.width()) {
+            let delta = other.mean[i] - self.mean[i];
+            self.mean[i] += delta * n_b / n;
+            self.m2[i] += other.m2[i] + delta * delta * n_a * n_b / n;
+        }
+        self.count += other.count;
+    }
+
+    /// 모집단 분산 (관측이 2개 미만이면 1.0)
+    pub fn variance(&self) -> Vec<f64> {
+        if self.count < 2 {
+            return vec![1.0; self.width()];
+        }
+        self.m2.iter().map(|m2| m2 / self.count as f64).collect()
+    }
+
+    /// 🧮 (x - 평균) / 표준편차, clip이 있으면 잘라냄 (통계는 바꾸지 않음, raw 피처는 그대로)
+    pub fn normalize(&self, observation: &[f32]) -> Vec<f32> {
+        observation
+            .iter()
+            .zip(&self.mean)
+            .zip(self.variance())
+            .enumerate()
+            .map(|(i, ((&x, &mean), var))| {
+                if self.is_raw(i) {
+                    return x;
+                }
+                let std = var.sqrt();
+                let std = if std < MIN_STD { 1.0 } else { std };
+                let z = ((x as f64 - mean) / std) as f32;
+                match self.clip {
+                    Some(c) => z.clamp(-c, c),
+                    None => z,
+                }
+            })
+            .collect()
+    }
+
+    /// 🧊 추론용: 더 이상 통계를 갱신하지 않음
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// 💾 JSON으로 저장 / 읽기
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// 🧮 두 번에 나눠 정규화: 모든 샘플(모든 마켓)의 state로 통계 하나를 먼저 구하고, 그 통계로 state/next_state를 변환
+/// 처음 샘플도 마지막 샘플과 같은 통계로 변환되고, 반환된 (freeze된) 통계를 추론 때 그대로 쓰면 스케일이 맞음
+/// normalizer는 통계가 비어 있는 설정 (clip, raw 피처)
+pub fn normalize_replay(
+    samples: &mut [ReplaySample],
+    mut normalizer: FeatureNormalizer,
+) -> FeatureNormalizer {
+    for sample in samples.iter() {
+        normalizer.update(&sample.state);
+    }
+    normalizer.freeze();
+
+    for sample in samples.iter_mut() {
+        sample.state = normalizer.normalize(&sample.state);
+        sample.next_state = normalizer.normalize(&sample.next_state);
+    }
+    normalizer
+}
+
+/// 모델/리플레이 파일 옆에 두는 정규화 통계 파일 경로
+pub fn stats_path(path: &str) -> String {
+    format!("{}.norm.json", path)
+}
@@ -0,0 +1,129 @@
+use crate::costs::{Fill, Side};
+use std::collections::VecDeque;
+
+/// 기본으로 보관하는 최근 평가금액 기록 수 (최대 낙폭은 기록과 별개로 전체 기간 기준)
+pub const DEFAULT_HISTORY_LEN: usize = 10_000;
+
+/// 📈 시점별 평가금액
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityPoint {
+    pub timestamp: u64,
+    pub equity: f64,
+}
+
+/// 💼 현금 / 보유 수량 / 손익을 관리하는 계좌 (금액은 KRW, 정밀도를 위해 f64)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio {
+    pub initial_cash: f64,
+    pub cash: f64,
+    pub quantity: f64,        // 보유 코인 수량
+    pub avg_entry_price: f64, // 평균 매수 단가 (매수 수수료 포함)
+    pub realized_pnl: f64,    // 매도로 확정된 손익 (수수료 차감 후)
+    pub fees_paid: f64,
+    pub equity_history: VecDeque<EquityPoint>, // 최근 history_len개만
+    pub history_len: usize,
+    peak_equity: f64, // 지금까지의 최고 평가금액 (initial_cash부터)
+    worst_drawdown: f64,
+}
+
+impl Portfolio {
+    pub fn new(initial_cash: f64) -> Self {
+        Self {
+            initial_cash,
+            cash: initial_cash,
+            quantity: 0.0,
+            avg_entry_price: 0.0,
+            realized_pnl: 0.0,
+            fees_paid: 0.0,
+            equity_history: VecDeque::new(),
+            history_len: DEFAULT_HISTORY_LEN,
+            peak_equity: initial_cash,
+            worst_drawdown: 0.0,
+        }
+    }
+
+    /// 평가금액 기록을 최근 history_len개만 보관 (0이면 기록하지 않음)
+    pub fn with_history_len(mut self, history_len: usize) -> Self {
+        self.history_len = history_len;
+        self
+    }
+
+    pub fn is_holding(&self) -> bool {
+        self.quantity > 0.0
+    }
+
+    /// ✅ 체결 반영, 이번 체결로 확정된 손익을 반환 (매수는 0)
+    pub fn apply_fill(&mut self, fill: &Fill) -> f64 {
+        let price = fill.price as f64;
+        let fee = fill.fee as f64;
+        self.fees_paid += fee;
+
+        match fill.side {
+            Side::Buy => {
+                let quantity = fill.quantity as f64;
+                let cost = price * quantity + fee;
+                let total = self.quantity + quantity;
+                if total > 0.0 {
+                    self.avg_entry_price = (self.avg_entry_price * self.quantity + cost) / total;
+                }
+                self.cash -= cost;
+                self.quantity = total;
+                0.0
+            }
+            Side::Sell => {
+                let quantity = (fill.quantity as f64).min(self.quantity);
+                let proceeds = price * quantity - fee;
+                let pnl = proceeds - self.avg_entry_price * quantity;
+                // f32 체결 수량의 반올림 오차로 남는 찌꺼기 수량은 전량 매도로 처리
+                let dust = self.quantity * 1e-6;
+                self.cash += proceeds;
+                self.quantity -= quantity;
+                self.realized_pnl += pnl;
+                if self.quantity <= dust {
+                    self.quantity = 0.0;
+                    self.avg_entry_price = 0.0;
+                }
+                pnl
+            }
+        }
+    }
+
+    /// 💹 현재가 기준 평가금액 (현금 + 보유 코인 평가액)
+    pub fn equity(&self, mark_price: f64) -> f64 {
+        self.cash + self.quantity * mark_price
+    }
+
+    /// 미실현 손익 (평균 단가 대비)
+    pub fn unrealized_pnl(&self, mark_price: f64) -> f64 {
+        (mark_price - self.avg_entry_price) * self.quantity
+    }
+
+    /// 📝 평가금액 기록 (최고 평가금액과 최대 낙폭도 갱신, 오래된 기록은 버림)
+    pub fn record(&mut self, timestamp: u64, mark_price: f64) -> f64 {
+        let equity = self.equity(mark_price);
+        self.peak_equity = self.peak_equity.max(equity);
+        if self.peak_equity > 0.0 {
+            let drawdown = (self.peak_equity - equity) / self.peak_equity;
+            self.worst_drawdown = self.worst_drawdown.max(drawdown);
+        }
+
+        if self.history_len > 0 {
+            if self.equity_history.len() == self.history_len {
+                self.equity_history.pop_front();
+            }
+            self.equity_history
+                .push_back(EquityPoint { timestamp, equity });
+        }
+        equity
+    }
+
+    /// 지금까지 기록한 평가금액의 최고치 (기록 전에는 initial_cash)
+    pub fn peak_equity(&self) -> f64 {
+        self.peak_equity
+    }
+
+    /// 기록한 모든 평가금액 기준 최대 낙폭 (0.0 ~ 1.0, 보관 개수와 무관)
+    pub fn max_drawdown(&self) -> f64 {
+        self.worst_drawdown
+    }
+}
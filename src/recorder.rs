@@ -0,0 +1,331 @@
+use crate::feed::MarketEvent;
+use crate::websocket::{RawFrame, parse_frame};
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use tokio::sync::mpsc::{self, Sender};
+
+/// 📼 녹화 설정
+/// 파일 경로: {dir}/{심볼}/{YYYY-MM-DD}_{part}.jsonl[.gz]
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub dir: PathBuf,
+    pub compress: bool,              // gzip 압축 여부
+    pub max_file_bytes: Option<u64>, // 파일 크기(디스크 기준, 압축하면 압축 후)가 이만큼 되면 다음 part로 교체
+}
+
+impl RecorderConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            compress: true,
+            max_file_bytes: Some(256 * 1024 * 1024),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        if self.compress { "jsonl.gz" } else { "jsonl" }
+    }
+}
+
+/// 📄 파일에 한 줄씩 기록되는 원본 프레임
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub received_at: u64, // 로컬 수신 시각 (Unix ms)
+    pub frame: String,    // Upbit에서 받은 JSON 그대로
+}
+
+/// 심볼별로 현재 쓰고 있는 파일
+struct SymbolWriter {
+    day: String,
+    part: u32,
+    out: Output,
+}
+
+/// 디스크에 실제로 쓴 바이트 수를 세는 파일 (이어 쓰면 기존 파일 크기부터 셈)
+struct CountingFile {
+    inner: BufWriter<File>,
+    bytes: u64,
+}
+
+impl Write for CountingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 기록 대상: 그대로 쓰거나 gzip으로 압축
+enum Output {
+    Plain(CountingFile),
+    Gzip(GzEncoder<CountingFile>),
+}
+
+impl Output {
+    /// 지금까지 디스크로 나간 크기 (압축 버퍼에 남은 것은 빠짐)
+    fn bytes(&self) -> u64 {
+        match self {
+            Output::Plain(file) => file.bytes,
+            Output::Gzip(encoder) => encoder.get_ref().bytes,
+        }
+    }
+
+    /// 🏁 파일 마무리: gzip이면 트레일러까지 쓰고 flush
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            Output::Plain(file) => file,
+            Output::Gzip(encoder) => encoder.finish()?,
+        };
+        file.flush()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(file) => file.write(buf),
+            Output::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(file) => file.flush(),
+            Output::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// 💾 심볼/날짜별로 파일을 나눠 추가 기록하는 녹화기
+pub struct MarketRecorder {
+    config: RecorderConfig,
+    writers: HashMap<String, SymbolWriter>,
+}
+
+impl MarketRecorder {
+    pub fn new(config: RecorderConfig) -> Self {
+        Self {
+            config,
+            writers: HashMap::new(),
+        }
+    }
+
+    /// ✅ 프레임 하나 기록 (날짜가 바뀌거나 크기를 넘으면 파일 교체)
+    pub fn record(&mut self, frame: &RawFrame) -> io::Result<()> {
+        let day = utc_date(frame.received_at);
+        let mut line = serde_json::to_string(&RecordedFrame {
+            received_at: frame.received_at,
+            frame: String::from_utf8_lossy(&frame.payload).into_owned(),
+        })?;
+        line.push('\n');
+
+        let full = self.config.max_file_bytes.is_some_and(|max| {
+            self.writers
+                .get(&frame.symbol)
+                .is_some_and(|w| w.out.bytes() >= max)
+        });
+
+        let rotate = match self.writers.get(&frame.symbol) {
+            Some(w) => w.day != day || full,
+            None => true,
+        };
+
+        if rotate {
+            let next_part = match self.writers.remove(&frame.symbol) {
+                Some(old) => {
+                    let next = (old.day == day).then_some(old.part + 1);
+                    old.out.finish()?;
+                    next
+                }
+                None => None,
+            };
+            let writer = self.open_writer(&frame.symbol, &day, next_part)?;
+            self.writers.insert(frame.symbol.clone(), writer);
+        }
+
+        let writer = self.writers.get_mut(&frame.symbol).unwrap();
+        writer.out.write_all(line.as_bytes())
+    }
+
+    /// 🔄 버퍼에 남은 내용을 디스크로
+    pub fn flush(&mut self) -> io::Result<()> {
+        for writer in self.writers.values_mut() {
+            writer.out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 🏁 모든 파일을 마무리하고 녹화 종료 (gzip 트레일러까지 기록)
+    pub fn finish(self) -> io::Result<()> {
+        for (_, writer) in self.writers {
+            writer.out.finish()?;
+        }
+        Ok(())
+    }
+
+    /// 📂 파일 열기: part를 지정하지 않으면 그 날의 마지막 파일에 이어서 씀
+    fn open_writer(&self, symbol: &str, day: &str, part: Option<u32>) -> io::Result<SymbolWriter> {
+        let dir = self.config.dir.join(symbol);
+        fs::create_dir_all(&dir)?;
+
+        let mut part = match part {
+            Some(p) => p,
+            None => last_part(&dir, day, self.config.extension())?.unwrap_or(0),
+        };
+        let mut path = dir.join(file_name(day, part, self.config.extension()));
+        let mut bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if self.config.max_file_bytes.is_some_and(|max| bytes >= max) {
+            part += 1;
+            path = dir.join(file_name(day, part, self.config.extension()));
+            bytes = 0;
+        }
+
+        let file = CountingFile {
+            inner: BufWriter::new(OpenOptions::new().create(true).append(true).open(&path)?),
+            bytes,
+        };
+        let out = if self.config.compress {
+            // 이어 쓰면 gzip 멤버가 추가되고, 읽을 때 MultiGzDecoder가 이어서 풀어줌
+            Output::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Output::Plain(file)
+        };
+
+        Ok(SymbolWriter {
+            day: day.to_string(),
+            part,
+            out,
+        })
+    }
+}
+
+/// 🧵 별도 스레드에서 녹화기를 돌리고 프레임을 보낼 Sender를 반환
+/// Sender가 모두 사라지면 남은 내용을 기록하고 파일을 마무리한 뒤 스레드 종료
+/// 기록 / flush 오류는 로그만 남기고 계속 받음 (채널을 닫으면 RawTap이 모든 프레임을 버리게 됨)
+pub fn spawn_recorder(config: RecorderConfig) -> (Sender<RawFrame>, JoinHandle<io::Result<()>>) {
+    let (sender, mut receiver) = mpsc::channel::<RawFrame>(1024);
+
+    let handle = thread::spawn(move || {
+        let mut recorder = MarketRecorder::new(config);
+        while let Some(frame) = receiver.blocking_recv() {
+            if let Err(e) = recorder.record(&frame) {
+                eprintln!("[Recorder] 기록 실패 ({}): {}", frame.symbol, e);
+            }
+            // 밀린 프레임이 없을 때만 flush (압축률 유지)
+            if receiver.is_empty()
+                && let Err(e) = recorder.flush()
+            {
+                eprintln!("[Recorder] flush 실패: {}", e);
+            }
+        }
+        // 오류가 있었어도 열린 파일은 마무리 (gzip 트레일러)
+        recorder.finish()
+    });
+
+    (sender, handle)
+}
+
+// === 읽기 ===
+
+/// 📂 심볼 디렉토리의 녹화 파일 목록 (날짜, part 순)
+pub fn recording_files(dir: &Path, symbol: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir.join(symbol))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| parse_file_name(p).is_some())
+        .collect();
+    files.sort_by_key(|p| parse_file_name(p));
+    Ok(files)
+}
+
+/// 📖 녹화 파일 하나를 읽음 (.gz면 압축 해제)
+/// 비정상 종료로 잘린 마지막 줄은 건너뜀
+pub fn read_recording_file(path: &Path) -> io::Result<Vec<RecordedFrame>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut frames = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("[Recorder] {} 읽기 중단: {}", path.display(), e);
+                break;
+            }
+        };
+        if let Ok(frame) = serde_json::from_str::<RecordedFrame>(&line) {
+            frames.push(frame);
+        }
+    }
+    Ok(frames)
+}
+
+/// 🔁 심볼의 전체 녹화를 TickData / OrderBookData 이벤트로 변환
+pub fn load_recording(dir: &Path, symbol: &str) -> io::Result<Vec<MarketEvent>> {
+    let mut events = Vec::new();
+    for path in recording_files(dir, symbol)? {
+        for frame in read_recording_file(&path)? {
+            if let Ok(Some(event)) = parse_frame(frame.frame.as_bytes()) {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
+}
+
+// === 파일 이름 / 날짜 ===
+
+fn file_name(day: &str, part: u32, extension: &str) -> String {
+    format!("{}_{:03}.{}", day, part, extension)
+}
+
+/// "2025-01-02_003.jsonl.gz" → ("2025-01-02", 3)
+fn parse_file_name(path: &Path) -> Option<(String, u32)> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name
+        .strip_suffix(".jsonl.gz")
+        .or_else(|| name.strip_suffix(".jsonl"))?;
+    let (day, part) = stem.split_once('_')?;
+    Some((day.to_string(), part.parse().ok()?))
+}
+
+fn last_part(dir: &Path, day: &str, extension: &str) -> io::Result<Option<u32>> {
+    let suffix = format!(".{}", extension);
+    let last = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.to_str().is_some_and(|s| s.ends_with(&suffix)))
+        .filter_map(|p| parse_file_name(&p))
+        .filter(|(d, _)| d == day)
+        .map(|(_, part)| part)
+        .max();
+    Ok(last)
+}
+
+/// 🗓️ Unix ms → UTC 날짜 문자열 (YYYY-MM-DD)
+pub fn utc_date(unix_ms: u64) -> String {
+    // Howard Hinnant의 civil_from_days 알고리즘
+    let days = (unix_ms / 86_400_000) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
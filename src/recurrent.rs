@@ -0,0 +1,252 @@
+use crate::dqn_model::QPolicy;
+use crate::replay_log::ReplaySample;
+use crate::schema::FeatureSchema;
+use crate::train::TrainConfig;
+use crate::types;
+use burn::{
+    module::Module,
+    nn::{Linear, LinearConfig, Lstm, LstmConfig, LstmState},
+    optim::{GradientsParams, Optimizer},
+    tensor::{Int, Tensor, activation::relu, backend::Backend},
+};
+use std::collections::HashMap;
+
+// 🔁 순환 DQN (R2D2, Kapturowski et al., 2019 방식을 단순화)
+//
+// 관측 하나 대신 과거 관측 시퀀스를 LSTM으로 읽어서 Q값을 냄
+// - 학습: 리플레이에서 연속 구간(burn_in + length)을 뽑아 앞쪽 burn_in개로 은닉 상태만 만들고
+//         (그래디언트 없음) 뒤쪽 length개 스텝의 TD 오차로 학습
+// - 추론: 마켓별 은닉 상태를 들고 있다가 틱마다 한 스텝씩 진행, 에피소드가 끝나면 초기화
+
+/// LSTM 은닉 상태 크기
+const HIDDEN_SIZE: usize = 32;
+
+/// 🧠 LSTM 은닉 상태 (각각 [batch, hidden])
+#[derive(Debug, Clone)]
+pub struct RecurrentState<B: Backend> {
+    pub cell: Tensor<B, 2>,
+    pub hidden: Tensor<B, 2>,
+}
+
+impl<B: Backend> RecurrentState<B> {
+    pub fn zeros(batch: usize, device: &B::Device) -> Self {
+        Self {
+            cell: Tensor::zeros([batch, HIDDEN_SIZE], device),
+            hidden: Tensor::zeros([batch, HIDDEN_SIZE], device),
+        }
+    }
+
+    /// 그래디언트가 이전 구간으로 흐르지 않게 끊음
+    pub fn detach(self) -> Self {
+        Self {
+            cell: self.cell.detach(),
+            hidden: self.hidden.detach(),
+        }
+    }
+}
+
+#[derive(Module, Debug)]
+pub struct RecurrentDqn<B: Backend> {
+    embed: Linear<B>,
+    lstm: Lstm<B>,
+    out: Linear<B>,
+}
+
+impl<B: Backend> RecurrentDqn<B> {
+    /// 관측 길이 input_size (FeatureSchema::len()과 같아야 함)
+    pub fn new(device: &B::Device, input_size: usize) -> Self {
+        Self {
+            embed: LinearConfig::new(input_size, HIDDEN_SIZE).init(device),
+            lstm: LstmConfig::new(HIDDEN_SIZE, HIDDEN_SIZE, true).init(device),
+            out: LinearConfig::new(HIDDEN_SIZE, 3).init(device),
+        }
+    }
+
+    pub fn from_schema(device: &B::Device, schema: &FeatureSchema) -> Self {
+        Self::new(device, schema.len())
+    }
+
+    /// 📜 시퀀스 [batch, steps, 피처 수] → 스텝별 Q값 [batch, steps, 3]과 마지막 은닉 상태
+    /// state가 None이면 0에서 시작
+    pub fn forward_sequence(
+        &self,
+        input: Tensor<B, 3>,
+        state: Option<RecurrentState<B>>,
+    ) -> (Tensor<B, 3>, RecurrentState<B>) {
+        let x = relu(self.embed.forward(input));
+        let state = state.map(|s| LstmState::new(s.cell, s.hidden));
+        let (x, state) = self.lstm.forward(x, state);
+        let q = self.out.forward(x);
+        (
+            q,
+            RecurrentState {
+                cell: state.cell,
+                hidden: state.hidden,
+            },
+        )
+    }
+
+    /// 👣 관측 하나 [batch, 피처 수]로 한 스텝 진행 → Q값 [batch, 3]과 다음 은닉 상태
+    pub fn forward_step(
+        &self,
+        observation: Tensor<B, 2>,
+        state: Option<RecurrentState<B>>,
+    ) -> (Tensor<B, 2>, RecurrentState<B>) {
+        let [batch, width] = observation.dims();
+        let (q, state) = self.forward_sequence(observation.reshape([batch, 1, width]), state);
+        (q.reshape([batch, 3]), state)
+    }
+
+    /// 최근 N개 관측 [batch, N, 피처 수]를 0 상태에서 읽고 마지막 스텝의 Q값 [batch, 3]
+    pub fn forward(&self, input: Tensor<B, 3>) -> Tensor<B, 2> {
+        let [batch, steps, _] = input.dims();
+        let (q, _) = self.forward_sequence(input, None);
+        q.slice([0..batch, steps - 1..steps]).reshape([batch, 3])
+    }
+}
+
+/// 📦 연속 구간 묶음을 [batch, steps, ...] 텐서로 (구간 길이와 피처 수가 모두 같아야 함)
+pub struct SequenceBatch {
+    pub states: Tensor<types::B, 3>,       // [batch, steps, 피처 수]
+    pub actions: Tensor<types::B, 3, Int>, // [batch, steps, 1]
+    pub rewards: Tensor<types::B, 3>,      // [batch, steps, 1]
+    pub next_states: Tensor<types::B, 3>,  // [batch, steps, 피처 수]
+    pub not_done: Tensor<types::B, 3>,     // [batch, steps, 1], 종료 전이는 0
+}
+
+impl SequenceBatch {
+    /// 구간 길이나 관측 피처 수가 첫 구간과 다르면 panic
+    pub fn from_sequences(
+        sequences: &[Vec<ReplaySample>],
+        device: &<types::B as Backend>::Device,
+    ) -> Self {
+        let batch = sequences.len();
+        let steps = sequences.first().map_or(0, Vec::len);
+        let width = sequences
+            .first()
+            .and_then(|s| s.first())
+            .map_or(0, |s| s.state.len());
+        for (i, sequence) in sequences.iter().enumerate() {
+            assert_eq!(
+                sequence.len(),
+                steps,
+                "{}번 구간 길이가 첫 구간 길이와 다릅니다",
+                i
+            );
+            assert!(
+                sequence
+                    .iter()
+                    .all(|s| s.state.len() == width && s.next_state.len() == width),
+                "{}번 구간에 피처 수가 {}가 아닌 관측이 있습니다",
+                i,
+                width
+            );
+        }
+        let samples = || sequences.iter().flatten();
+
+        let observations = |values: Vec<f32>| {
+            Tensor::<types::B, 1>::from_floats(values.as_slice(), device)
+                .reshape([batch, steps, width])
+        };
+        let column = |values: Vec<f32>| {
+            Tensor::<types::B, 1>::from_floats(values.as_slice(), device).reshape([batch, steps, 1])
+        };
+        let actions: Vec<i64> = samples().map(|s| s.action as i64).collect();
+
+        Self {
+            states: observations(samples().flat_map(|s| s.state.iter().copied()).collect()),
+            actions: Tensor::<types::B, 1, Int>::from_ints(actions.as_slice(), device)
+                .reshape([batch, steps, 1]),
+            rewards: column(samples().map(|s| s.reward).collect()),
+            next_states: observations(
+                samples()
+                    .flat_map(|s| s.next_state.iter().copied())
+                    .collect(),
+            ),
+            not_done: column(samples().map(|s| if s.done { 0.0 } else { 1.0 }).collect()),
+        }
+    }
+}
+
+/// ⚡ 연속 구간 미니배치로 한 번 학습 (구간 길이 = burn_in + 학습 스텝 수)
+/// 앞쪽 burn_in 스텝은 은닉 상태만 만들고 loss에는 들어가지 않음
+/// 타겟 네트워크는 next_state 시퀀스를 처음부터 읽어서 같은 burn_in 뒤의 값을 사용
+/// 학습된 모델과 loss를 반환
+pub fn train_sequence_step<O: Optimizer<RecurrentDqn<types::B>, types::B>>(
+    model: RecurrentDqn<types::B>,
+    optimizer: &mut O,
+    target: &RecurrentDqn<types::B>,
+    sequences: &[Vec<ReplaySample>],
+    burn_in: usize,
+    config: &TrainConfig,
+) -> (RecurrentDqn<types::B>, f32) {
+    let device = <types::B as Backend>::Device::default();
+    let batch = SequenceBatch::from_sequences(sequences, &device);
+    let [n, steps, width] = batch.states.dims();
+    let burn_in = burn_in.min(steps.saturating_sub(1));
+    let learn = |t: Tensor<types::B, 3>| {
+        let last = t.dims()[2];
+        t.slice([0..n, burn_in..steps, 0..last])
+    };
+
+    // 🔥 burn-in: 은닉 상태만 만들고 그래디언트는 끊음
+    let state = (burn_in > 0).then(|| {
+        let warmup = batch.states.clone().slice([0..n, 0..burn_in, 0..width]);
+        model.forward_sequence(warmup, None).1.detach()
+    });
+    let (q, _) = model.forward_sequence(learn(batch.states.clone()), state);
+    let q_taken = q.gather(2, batch.actions.slice([0..n, burn_in..steps, 0..1]));
+
+    // 🎯 r + γ·(1 - done)·V(s'), next_state 시퀀스는 처음부터 읽음
+    let (target_next, _) = target.forward_sequence(batch.next_states.clone(), None);
+    let target_next = learn(target_next.detach());
+    let next_value = if config.double_dqn {
+        let (online_next, _) = model.forward_sequence(batch.next_states.clone(), None);
+        target_next.gather(2, learn(online_next.detach()).argmax(2))
+    } else {
+        target_next.max_dim(2)
+    };
+    let targets =
+        (learn(batch.rewards) + learn(batch.not_done) * next_value * config.gamma).detach();
+
+    let loss = (targets - q_taken).powf_scalar(2.0).mean();
+    let loss_value = loss.clone().into_scalar();
+    let grads = GradientsParams::from_grads(loss.backward(), &model);
+    (
+        optimizer.step(config.learning_rate, model, grads),
+        loss_value,
+    )
+}
+
+/// 📡 실시간 추론용: 마켓별 은닉 상태를 이어가며 틱마다 한 스텝씩 진행
+pub struct RecurrentPolicy {
+    pub model: RecurrentDqn<types::B>,
+    states: HashMap<String, RecurrentState<types::B>>,
+}
+
+impl RecurrentPolicy {
+    pub fn new(model: RecurrentDqn<types::B>) -> Self {
+        Self {
+            model,
+            states: HashMap::new(),
+        }
+    }
+
+    /// 은닉 상태를 들고 있는 마켓 수
+    pub fn markets(&self) -> usize {
+        self.states.len()
+    }
+}
+
+impl QPolicy for RecurrentPolicy {
+    fn q_values(&mut self, code: &str, observation: Tensor<types::B, 2>) -> Vec<f32> {
+        let state = self.states.remove(code);
+        let (q, state) = self.model.forward_step(observation, state);
+        self.states.insert(code.to_string(), state.detach());
+        q.into_data().convert::<f32>().to_vec().unwrap()
+    }
+
+    fn reset(&mut self, code: &str) {
+        self.states.remove(code);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::replay_loader::{load_replay_csv, read_replay_csv_schema};
+use crate::replay_log::ReplaySample;
+use crate::replay_saver::{replay_csv_header, replay_csv_record};
+use crate::schema::FeatureSchema;
+use crate::websocket::unix_millis;
+use bincode::{Decode, Encode};
+use csv::Writer;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+// 📦 바이너리 리플레이 파일 (.bin)
+//
+//   "BBRP" | 헤더 길이(u32 LE) | 헤더 | { 레코드 길이(u32 LE) | ReplaySample }*
+//
+// 헤더와 레코드는 bincode standard 설정으로 인코딩
+// 길이를 먼저 적어두기 때문에 비정상 종료로 잘린 마지막 레코드는 감지해서 건너뜀
+// 길이가 헤더 / 레코드가 가질 수 있는 크기를 넘으면 할당하지 않고 InvalidData 오류
+
+pub const REPLAY_MAGIC: &[u8; 4] = b"BBRP";
+pub const REPLAY_VERSION: u32 = 1;
+
+/// 한 번에 읽는 기본 레코드 수
+pub const DEFAULT_CHUNK: usize = 1024;
+
+/// 헤더 길이 상한 (피처 이름이 수만 개여도 넘지 않는 크기)
+pub const MAX_HEADER_LEN: usize = 1 << 20;
+
+/// 📋 파일 헤더
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ReplayHeader {
+    pub version: u32,
+    pub feature_count: u32,
+    pub feature_names: Vec<String>,
+    pub action_count: u32,
+    pub created_at: u64, // unix ms
+}
+
+impl ReplayHeader {
+    pub fn new(feature_names: Vec<String>, action_count: usize) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            feature_count: feature_names.len() as u32,
+            feature_names,
+            action_count: action_count as u32,
+            created_at: unix_millis(),
+        }
+    }
+
+    pub fn from_schema(schema: &FeatureSchema, action_count: usize) -> Self {
+        Self::new(schema.names.clone(), action_count)
+    }
+
+    /// 헤더에 적힌 피처 이름을 스키마로
+    pub fn schema(&self) -> FeatureSchema {
+        FeatureSchema::from_names(self.feature_names.clone())
+    }
+
+    /// 이름이 없을 때 쓰는 feature_0, feature_1, ...
+    pub fn unnamed(feature_count: usize, action_count: usize) -> Self {
+        let names = (0..feature_count)
+            .map(|i| format!("feature_{}", i))
+            .collect();
+        Self::new(names, action_count)
+    }
+
+    /// 같은 파일에 이어 쓸 수 있는 형식인지 (생성 시각은 무시)
+    pub fn is_compatible(&self, other: &ReplayHeader) -> bool {
+        self.feature_count == other.feature_count
+            && self.feature_names == other.feature_names
+            && self.action_count == other.action_count
+    }
+
+    /// 레코드 하나가 인코딩될 수 있는 최대 바이트 수
+    /// (bincode standard: 길이/행동은 varint 최대 9바이트, f32는 4바이트, bool은 1바이트)
+    pub fn max_record_len(&self) -> usize {
+        let observation = 9 + 4 * self.feature_count as usize;
+        2 * observation + 9 + 4 + 1
+    }
+
+    /// 샘플이 헤더의 피처 수 / 행동 수와 맞는지
+    fn check(&self, sample: &ReplaySample) -> io::Result<()> {
+        let width = self.feature_count as usize;
+        if sample.state.len() != width || sample.next_state.len() != width {
+            return Err(invalid_input(format!(
+                "관측 길이 {} / {}가 헤더의 피처 수 {}와 다릅니다",
+                sample.state.len(),
+                sample.next_state.len(),
+                width
+            )));
+        }
+        if sample.action >= self.action_count as usize {
+            return Err(invalid_input(format!(
+                "행동 {}이 행동 수 {}를 벗어났습니다",
+                sample.action, self.action_count
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// ✍️ 스트리밍 기록기: 샘플을 하나씩 파일 끝에 붙임
+pub struct ReplayWriter {
+    pub header: ReplayHeader,
+    writer: BufWriter<File>,
+    written: usize,
+}
+
+impl ReplayWriter {
+    /// 새 파일 생성 (기존 파일은 덮어씀)
+    pub fn create(path: impl AsRef<Path>, header: ReplayHeader) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(REPLAY_MAGIC)?;
+        write_frame(&mut writer, &header)?;
+        Ok(Self {
+            header,
+            writer,
+            written: 0,
+        })
+    }
+
+    /// 기존 파일에 이어 쓰기
+    /// 잘린 마지막 레코드가 있으면 잘라내고 그 자리부터 씀
+    pub fn append(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut reader = BufReader::new(&mut file);
+        let header = read_header(&mut reader)?;
+
+        let mut end = reader.stream_position()?;
+        while let Some(len) = read_record_len(&mut reader, &header)? {
+            if !skip_exact(&mut reader, len)? {
+                break;
+            }
+            end = reader.stream_position()?;
+        }
+        drop(reader);
+
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+        Ok(Self {
+            header,
+            writer: BufWriter::new(file),
+            written: 0,
+        })
+    }
+
+    /// 파일이 있으면 이어 쓰고(헤더가 맞아야 함), 없으면 새로 만듦
+    pub fn open_or_create(path: impl AsRef<Path>, header: ReplayHeader) -> io::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::create(path, header);
+        }
+        let writer = Self::append(path)?;
+        if !writer.header.is_compatible(&header) {
+            return Err(invalid_data(format!(
+                "{}의 헤더가 현재 형식과 다릅니다",
+                path.display()
+            )));
+        }
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, sample: &ReplaySample) -> io::Result<()> {
+        self.header.check(sample)?;
+        write_frame(&mut self.writer, sample)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    pub fn write_all(&mut self, samples: &[ReplaySample]) -> io::Result<()> {
+        samples.iter().try_for_each(|s| self.write(s))
+    }
+
+    /// 이번에 연 뒤로 기록한 샘플 수
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// 📖 청크 단위 읽기
+pub struct ReplayReader {
+    pub header: ReplayHeader,
+    reader: BufReader<File>,
+    finished: bool,
+}
+
+impl ReplayReader {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header = read_header(&mut reader)?;
+        Ok(Self {
+            header,
+            reader,
+            finished: false,
+        })
+    }
+
+    /// 최대 max개를 읽음, 파일 끝이면 빈 Vec
+    pub fn read_chunk(&mut self, max: usize) -> io::Result<Vec<ReplaySample>> {
+        let mut chunk = Vec::with_capacity(max.min(DEFAULT_CHUNK));
+        while chunk.len() < max {
+            match self.read_next()? {
+                Some(sample) => chunk.push(sample),
+                None => break,
+            }
+        }
+        Ok(chunk)
+    }
+
+    fn read_next(&mut self) -> io::Result<Option<ReplaySample>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let Some(len) = read_record_len(&mut self.reader, &self.header)? else {
+            self.finished = true;
+            return Ok(None);
+        };
+        let mut bytes = vec![0; len];
+        if !read_exact_or_eof(&mut self.reader, &mut bytes)? {
+            // 비정상 종료로 잘린 마지막 레코드
+            self.finished = true;
+            return Ok(None);
+        }
+        decode(&bytes).map(Some)
+    }
+}
+
+impl Iterator for ReplayReader {
+    type Item = io::Result<ReplaySample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// 파일 전체를 읽음
+pub fn load_replay_file(path: impl AsRef<Path>) -> io::Result<(ReplayHeader, Vec<ReplaySample>)> {
+    let mut reader = ReplayReader::open(path)?;
+    let samples = reader.by_ref().collect::<io::Result<Vec<_>>>()?;
+    Ok((reader.header, samples))
+}
+
+/// 샘플 전체를 새 파일로 저장
+pub fn save_replay_file(
+    path: impl AsRef<Path>,
+    header: ReplayHeader,
+    samples: &[ReplaySample],
+) -> io::Result<()> {
+    let mut writer = ReplayWriter::create(path, header)?;
+    writer.write_all(samples)?;
+    writer.flush()
+}
+
+// === CSV 변환 ===
+
+/// 🔄 replay_saver 형식 CSV → 바이너리, 변환한 샘플 수 반환
+pub fn csv_to_replay_file(
+    csv_path: &str,
+    bin_path: &str,
+    action_count: usize,
+) -> Result<usize, Box<dyn Error>> {
+    let schema = read_replay_csv_schema(csv_path)?;
+    let samples = load_replay_csv(csv_path)?;
+    save_replay_file(
+        bin_path,
+        ReplayHeader::from_schema(&schema, action_count),
+        &samples,
+    )?;
+    Ok(samples.len())
+}
+
+/// 🔄 바이너리 → replay_saver 형식 CSV (청크 단위로 스트리밍), 변환한 샘플 수 반환
+pub fn replay_file_to_csv(bin_path: &str, csv_path: &str) -> Result<usize, Box<dyn Error>> {
+    let mut reader = ReplayReader::open(bin_path)?;
+    let mut writer = Writer::from_path(csv_path)?;
+    writer.write_record(replay_csv_header(&reader.header.schema()))?;
+
+    let mut count = 0;
+    loop {
+        let chunk = reader.read_chunk(DEFAULT_CHUNK)?;
+        if chunk.is_empty() {
+            break;
+        }
+        for sample in &chunk {
+            writer.write_record(replay_csv_record(sample))?;
+        }
+        count += chunk.len();
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+// === 프레임 입출력 ===
+
+fn bincode_config() -> bincode::config::Configuration {
+    bincode::config::standard()
+}
+
+fn write_frame<T: Encode>(writer: &mut impl Write, value: &T) -> io::Result<()> {
+    let bytes = bincode::encode_to_vec(value, bincode_config()).map_err(invalid_data)?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+fn decode<T: Decode<()>>(bytes: &[u8]) -> io::Result<T> {
+    let (value, _) = bincode::decode_from_slice(bytes, bincode_config()).map_err(invalid_data)?;
+    Ok(value)
+}
+
+fn read_header(reader: &mut impl Read) -> io::Result<ReplayHeader> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != REPLAY_MAGIC {
+        return Err(invalid_data("리플레이 파일이 아닙니다"));
+    }
+
+    let len = read_frame_len(reader)?.ok_or_else(|| invalid_data("헤더가 없습니다"))?;
+    if len > MAX_HEADER_LEN {
+        return Err(invalid_data(format!("헤더 길이 {}가 너무 깁니다", len)));
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    let header: ReplayHeader = decode(&bytes)?;
+    if header.version > REPLAY_VERSION {
+        return Err(invalid_data(format!(
+            "지원하지 않는 리플레이 버전 {}",
+            header.version
+        )));
+    }
+    Ok(header)
+}
+
+/// 레코드 길이, 파일 끝이거나 길이가 잘렸으면 None
+fn read_frame_len(reader: &mut impl Read) -> io::Result<Option<usize>> {
+    let mut len = [0; 4];
+    Ok(read_exact_or_eof(reader, &mut len)?.then(|| u32::from_le_bytes(len) as usize))
+}
+
+/// 헤더의 피처 수로 검사한 레코드 길이, 파일 끝이거나 길이가 잘렸으면 None
+fn read_record_len(reader: &mut impl Read, header: &ReplayHeader) -> io::Result<Option<usize>> {
+    let Some(len) = read_frame_len(reader)? else {
+        return Ok(None);
+    };
+    if len > header.max_record_len() {
+        return Err(invalid_data(format!(
+            "레코드 길이 {}가 피처 {}개 레코드의 최대 길이 {}를 넘습니다",
+            len,
+            header.feature_count,
+            header.max_record_len()
+        )));
+    }
+    Ok(Some(len))
+}
+
+/// 끝까지 다 읽으면 true, 중간에 파일이 끝나면 false
+fn read_exact_or_eof(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn skip_exact(reader: &mut impl Read, len: usize) -> io::Result<bool> {
+    let skipped = io::copy(&mut reader.by_ref().take(len as u64), &mut io::sink())?;
+    Ok(skipped == len as u64)
+}
+
+fn invalid_data(e: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+fn invalid_input(e: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, e)
+}
@@ -1,90 +1,69 @@
 use crate::replay_log::ReplaySample;
-use crate::types::B;
+use crate::schema::FeatureSchema;
 
-use burn::tensor::{Tensor, backend::Backend};
-use csv::Reader;
-use serde::Deserialize;
+use csv::{Reader, StringRecord};
+use std::error::Error;
 
-#[derive(Deserialize)]
-struct Row {
-    action: usize,
-    reward: f32,
-    state_0: f32,
-    state_1: f32,
-    state_2: f32,
-    state_3: f32,
-    state_4: f32,
-    state_5: f32,
-    state_6: f32,
-    state_7: f32,
-    state_8: f32,
-    state_9: f32,
-    state_10: f32,
-    state_11: f32,
-    next_0: f32,
-    next_1: f32,
-    next_2: f32,
-    next_3: f32,
-    next_4: f32,
-    next_5: f32,
-    next_6: f32,
-    next_7: f32,
-    next_8: f32,
-    next_9: f32,
-    next_10: f32,
-    next_11: f32,
-}
-
-pub fn load_replay_csv(filename: &str, device: &<B as Backend>::Device) -> Vec<ReplaySample> {
-    let mut rdr = Reader::from_path(filename).unwrap();
-    let mut samples = Vec::new();
+/// replay_saver 형식의 CSV를 읽음
+/// state_* / next_* 열을 헤더 순서대로 관측값으로 쓰고, done 열이 없는 예전 CSV는 비종료로 간주
+pub fn load_replay_csv(filename: &str) -> Result<Vec<ReplaySample>, Box<dyn Error>> {
+    let mut rdr = Reader::from_path(filename)?;
+    let headers = rdr.headers()?.clone();
 
-    for result in rdr.deserialize() {
-        let row: Row = result.unwrap();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let columns = |prefix: &str| -> Vec<usize> {
+        headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.starts_with(prefix))
+            .map(|(i, _)| i)
+            .collect()
+    };
 
-        let state = Tensor::<B, 2>::from_floats(
-            [[
-                row.state_0,
-                row.state_1,
-                row.state_2,
-                row.state_3,
-                row.state_4,
-                row.state_5,
-                row.state_6,
-                row.state_7,
-                row.state_8,
-                row.state_9,
-                row.state_10,
-                row.state_11,
-            ]],
-            device,
-        );
-
-        let next_state = Tensor::<B, 2>::from_floats(
-            [[
-                row.next_0,
-                row.next_1,
-                row.next_2,
-                row.next_3,
-                row.next_4,
-                row.next_5,
-                row.next_6,
-                row.next_7,
-                row.next_8,
-                row.next_9,
-                row.next_10,
-                row.next_11,
-            ]],
-            device,
-        );
+    let action = column("action").ok_or("action 열이 없습니다")?;
+    let reward = column("reward").ok_or("reward 열이 없습니다")?;
+    let done = column("done");
+    let state = columns("state_");
+    let next = columns("next_");
 
+    let mut samples = Vec::new();
+    for result in rdr.records() {
+        let row = result?;
         samples.push(ReplaySample {
-            state,
-            action: row.action,
-            reward: row.reward,
-            next_state,
+            state: floats(&row, &state)?,
+            action: field(&row, action)?.parse()?,
+            reward: field(&row, reward)?.parse()?,
+            next_state: floats(&row, &next)?,
+            done: match done {
+                Some(i) => field(&row, i)?.parse()?,
+                None => false,
+            },
         });
     }
 
-    samples
+    Ok(samples)
+}
+
+/// 📐 CSV 헤더의 state_* 열 이름으로 피처 스키마를 복원
+/// 예전 CSV(state_0, state_1, ...)는 FeatureSchema::indexed와 같은 이름이 됨
+pub fn read_replay_csv_schema(filename: &str) -> Result<FeatureSchema, Box<dyn Error>> {
+    let mut rdr = Reader::from_path(filename)?;
+    let names = rdr
+        .headers()?
+        .iter()
+        .filter_map(|h| h.strip_prefix("state_"))
+        .map(str::to_string)
+        .collect();
+    Ok(FeatureSchema::from_names(names))
+}
+
+fn field(row: &StringRecord, index: usize) -> Result<&str, Box<dyn Error>> {
+    Ok(row.get(index).ok_or("열 개수가 헤더와 다릅니다")?)
+}
+
+fn floats(row: &StringRecord, indices: &[usize]) -> Result<Vec<f32>, Box<dyn Error>> {
+    indices
+        .iter()
+        .map(|&i| Ok(field(row, i)?.parse()?))
+        .collect()
 }
@@ -1,17 +1,36 @@
-use crate::types::B;
-use burn::tensor::{backend::Backend, Tensor};
-use serde::{Serialize, Deserialize};
+use bincode::{Decode, Encode};
+use burn::tensor::{Tensor, backend::Backend};
+use serde::{Deserialize, Serialize};
 
 /// 🧠 상태, 행동, 보상, 다음 상태를 저장하는 구조체
 /// DQN에서는 이 경험을 기반으로 학습합니다
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 관측값은 백엔드와 무관한 f32 벡터로 저장하고, 학습할 때 배치 텐서로 변환합니다
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct ReplaySample {
     /// 현재 상태 (state)
-    pub state: Tensor<B, 2>,
+    pub state: Vec<f32>,
     /// 선택한 행동 (0 = Buy, 1 = Sell, 2 = Hold)
     pub action: usize,
     /// 해당 행동을 했을 때의 보상 (reward)
     pub reward: f32,
     /// 행동 이후 도달한 상태 (next_state)
-    pub next_state: Tensor<B, 2>,
+    pub next_state: Vec<f32>,
+    /// next_state가 에피소드의 마지막 상태인지 (true면 TD 타겟에서 부트스트랩하지 않음)
+    pub done: bool,
+}
+
+/// 관측값 하나를 [1, dim] 텐서로
+pub fn observation_tensor<B: Backend>(observation: &[f32], device: &B::Device) -> Tensor<B, 2> {
+    Tensor::<B, 1>::from_floats(observation, device).reshape([1, observation.len()])
+}
+
+/// 관측값 여러 개를 [n, dim] 텐서로 (모두 같은 길이여야 함)
+pub fn stack_observations<'a, B: Backend>(
+    observations: impl ExactSizeIterator<Item = &'a [f32]>,
+    device: &B::Device,
+) -> Tensor<B, 2> {
+    let n = observations.len();
+    let flat: Vec<f32> = observations.flat_map(|o| o.iter().copied()).collect();
+    let dim = flat.len().checked_div(n).unwrap_or(0);
+    Tensor::<B, 1>::from_floats(flat.as_slice(), device).reshape([n, dim])
 }
@@ -1,19 +1,30 @@
 use crate::replay_log::ReplaySample;
-use rand::prelude::IndexedRandom;
-use rand::seq::IteratorRandom;
-use rand::{seq::SliceRandom, thread_rng};
-use std::collections::VecDeque;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, VecDeque};
 
 pub struct ReplayBuffer {
     buffer: VecDeque<ReplaySample>,
     capacity: usize,
+    rng: StdRng,
 }
 
 impl ReplayBuffer {
     pub fn new(capacity: usize) -> Self {
+        Self::with_rng(capacity, StdRng::from_rng(&mut rand::rng()))
+    }
+
+    // 시드를 고정하면 같은 순서로 push했을 때 항상 같은 배치를 뽑음
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self::with_rng(capacity, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(capacity: usize, rng: StdRng) -> Self {
         Self {
             buffer: VecDeque::with_capacity(capacity),
             capacity,
+            rng,
         }
     }
 
@@ -25,14 +36,12 @@ impl ReplayBuffer {
         self.buffer.push_back(sample);
     }
 
-    pub fn sample(&self, batch_size: usize) -> Vec<ReplaySample> {
-        let mut rng = thread_rng();
-        self.buffer
-            .iter()
-            .cloned()
-            .collect::<Vec<_>>()
-            .choose_multiple(&mut rng, batch_size)
-            .cloned()
+    // 균등 샘플링 (중복 없음), 뽑힌 샘플만 복사
+    pub fn sample(&mut self, batch_size: usize) -> Vec<ReplaySample> {
+        let amount = batch_size.min(self.buffer.len());
+        index::sample(&mut self.rng, self.buffer.len(), amount)
+            .into_iter()
+            .map(|i| self.buffer[i].clone())
             .collect()
     }
 
@@ -40,7 +49,367 @@ impl ReplayBuffer {
         self.buffer.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn is_ready(&self, batch_size: usize) -> bool {
+        self.len() >= batch_size
+    }
+}
+
+/// 🌲 우선순위 합을 O(log n)으로 갱신/탐색하는 이진 트리
+/// 리프 i의 값이 샘플 i의 우선순위, 내부 노드는 자식 합
+pub struct SumTree {
+    capacity: usize,
+    nodes: Vec<f64>, // nodes[1]이 루트, 리프는 capacity..2*capacity
+}
+
+impl SumTree {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+        Self {
+            capacity,
+            nodes: vec![0.0; 2 * capacity],
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.nodes[1]
+    }
+
+    pub fn get(&self, index: usize) -> f64 {
+        self.nodes[self.capacity + index]
+    }
+
+    pub fn set(&mut self, index: usize, priority: f64) {
+        let mut node = self.capacity + index;
+        self.nodes[node] = priority;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.nodes[2 * node] + self.nodes[2 * node + 1];
+        }
+    }
+
+    /// 누적합이 처음으로 mass를 넘는 리프 찾기 (0 <= mass < total)
+    pub fn find(&self, mut mass: f64) -> usize {
+        let mut node = 1;
+        while node < self.capacity {
+            let left = 2 * node;
+            if mass < self.nodes[left] || self.nodes[left + 1] <= 0.0 {
+                node = left;
+            } else {
+                mass -= self.nodes[left];
+                node = left + 1;
+            }
+        }
+        node - self.capacity
+    }
+}
+
+/// ⚙️ 우선순위 리플레이 설정
+#[derive(Debug, Clone)]
+pub struct PrioritizedConfig {
+    pub capacity: usize,    // 0이면 1로 취급
+    pub alpha_start: f32,   // 0이면 균등, 1이면 TD 오차에 완전히 비례
+    pub alpha_end: f32,     // 같게 두면 고정
+    pub alpha_steps: usize, // alpha_start → alpha_end까지 sample 호출 횟수
+    pub beta_start: f32,    // 중요도 샘플링 보정 시작값
+    pub beta_end: f32,      // 보통 1.0 (완전 보정)
+    pub beta_steps: usize,  // beta_start → beta_end까지 sample 호출 횟수
+    pub priority_eps: f32,  // 오차가 0이어도 뽑힐 수 있도록 더하는 값
+}
+
+impl Default for PrioritizedConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            alpha_start: 0.6,
+            alpha_end: 0.6,
+            alpha_steps: 10_000,
+            beta_start: 0.4,
+            beta_end: 1.0,
+            beta_steps: 10_000,
+            priority_eps: 1e-6,
+        }
+    }
+}
+
+/// 📦 우선순위 샘플링 결과
+pub struct PrioritizedBatch {
+    pub samples: Vec<ReplaySample>,
+    pub indices: Vec<usize>, // update_priorities에 그대로 넘길 버퍼 위치
+    pub weights: Vec<f32>,   // 중요도 샘플링 가중치 (배치 최댓값 1로 정규화)
+}
+
+/// 🎯 TD 오차 비례 샘플링 리플레이 버퍼 (Schaul et al., 2016)
+pub struct PrioritizedReplayBuffer {
+    pub config: PrioritizedConfig,
+    samples: Vec<ReplaySample>,
+    tree: SumTree,
+    next: usize, // 다음에 덮어쓸 위치 (꽉 차면 가장 오래된 것부터)
+    max_priority: f64,
+    sample_calls: usize,
+    rng: StdRng,
+}
+
+impl PrioritizedReplayBuffer {
+    pub fn new(config: PrioritizedConfig) -> Self {
+        Self::with_rng(config, StdRng::from_rng(&mut rand::rng()))
+    }
+
+    pub fn with_seed(config: PrioritizedConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    /// capacity 0은 1로 (빈 버퍼로는 덮어쓸 위치를 정할 수 없음)
+    fn with_rng(config: PrioritizedConfig, rng: StdRng) -> Self {
+        let config = PrioritizedConfig {
+            capacity: config.capacity.max(1),
+            ..config
+        };
+        Self {
+            samples: Vec::with_capacity(config.capacity),
+            tree: SumTree::new(config.capacity),
+            next: 0,
+            max_priority: 1.0,
+            sample_calls: 0,
+            rng,
+            config,
+        }
+    }
+
+    /// 새 샘플은 지금까지의 최대 우선순위로 들어가서 최소 한 번은 뽑히도록 함
+    pub fn push(&mut self, sample: ReplaySample) {
+        if self.samples.len() < self.config.capacity {
+            self.samples.push(sample);
+        } else {
+            self.samples[self.next] = sample;
+        }
+        self.tree.set(self.next, self.max_priority);
+        self.next = (self.next + 1) % self.config.capacity;
+    }
+
+    /// 현재 beta (sample 호출마다 beta_end를 향해 선형 증가)
+    pub fn beta(&self) -> f32 {
+        let config = &self.config;
+        anneal(
+            config.beta_start,
+            config.beta_end,
+            config.beta_steps,
+            self.sample_calls,
+        )
+    }
+
+    /// 현재 alpha (sample 호출마다 alpha_end를 향해 선형으로 움직임)
+    /// 이미 들어간 우선순위는 그대로, update_priorities부터 새 alpha가 적용됨
+    pub fn alpha(&self) -> f32 {
+        let config = &self.config;
+        anneal(
+            config.alpha_start,
+            config.alpha_end,
+            config.alpha_steps,
+            self.sample_calls,
+        )
+    }
+
+    /// 샘플 i가 뽑힐 확률 P(i) = p_i / Σp
+    pub fn probability(&self, index: usize) -> f64 {
+        self.tree.get(index) / self.tree.total()
+    }
+
+    /// 📥 우선순위 비례 샘플링 (전체 합을 batch_size 구간으로 나눠 구간마다 하나씩)
+    /// 가중치 w_i = (N·P(i))^-β / max_j w_j
+    pub fn sample(&mut self, batch_size: usize) -> PrioritizedBatch {
+        let beta = self.beta();
+        self.sample_calls += 1;
+
+        let n = self.samples.len();
+        let total = self.tree.total();
+        if n == 0 || batch_size == 0 || total <= 0.0 {
+            return PrioritizedBatch {
+                samples: Vec::new(),
+                indices: Vec::new(),
+                weights: Vec::new(),
+            };
+        }
+
+        let segment = total / batch_size as f64;
+        let indices: Vec<usize> = (0..batch_size)
+            .map(|k| {
+                let mass = segment * (k as f64 + self.rng.random::<f64>());
+                self.tree.find(mass.min(total * (1.0 - 1e-12))).min(n - 1)
+            })
+            .collect();
+
+        let raw: Vec<f64> = indices
+            .iter()
+            .map(|&i| (n as f64 * self.probability(i)).powf(-beta as f64))
+            .collect();
+        let max = raw.iter().copied().fold(0.0, f64::max);
+
+        PrioritizedBatch {
+            samples: indices.iter().map(|&i| self.samples[i].clone()).collect(),
+            weights: raw.iter().map(|w| (w / max) as f32).collect(),
+            indices,
+        }
+    }
+
+    /// ✏️ 학습 후 TD 오차로 우선순위 갱신 p_i = (|δ_i| + ε)^α (α는 현재 alpha())
+    pub fn update_priorities(&mut self, indices: &[usize], td_errors: &[f32]) {
+        let alpha = self.alpha() as f64;
+        for (&index, &error) in indices.iter().zip(td_errors) {
+            if index >= self.samples.len() {
+                continue;
+            }
+            let priority = ((error.abs() + self.config.priority_eps) as f64).powf(alpha);
+            self.tree.set(index, priority);
+            self.max_priority = self.max_priority.max(priority);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
     pub fn is_ready(&self, batch_size: usize) -> bool {
         self.len() >= batch_size
     }
 }
+
+/// start에서 end까지 steps번에 걸쳐 선형으로 (steps가 0이면 바로 end)
+fn anneal(start: f32, end: f32, steps: usize, calls: usize) -> f32 {
+    let progress = if steps == 0 {
+        1.0
+    } else {
+        (calls as f32 / steps as f32).min(1.0)
+    };
+    start + (end - start) * progress
+}
+
+/// ⚙️ 연속 구간(시퀀스) 리플레이 설정 (순환 DQN용)
+#[derive(Debug, Clone)]
+pub struct SequenceConfig {
+    pub capacity: usize, // 스트림(마켓)마다 보관하는 최대 샘플 수
+    pub burn_in: usize,  // 은닉 상태만 만드는 앞쪽 스텝 수 (loss에 안 들어감)
+    pub length: usize,   // 학습하는 스텝 수
+}
+
+impl SequenceConfig {
+    /// 한 번에 뽑는 구간 길이
+    pub fn window(&self) -> usize {
+        self.burn_in + self.length
+    }
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            burn_in: 8,
+            length: 16,
+        }
+    }
+}
+
+/// 🎞️ 스트림(마켓)별로 시간 순서를 유지하고 연속 구간을 뽑는 리플레이 버퍼
+/// 구간은 한 스트림 안에서만 뽑고, 에피소드 경계(done)를 넘지 않음 (마지막 스텝의 done은 허용)
+/// window보다 짧은 에피소드는 뽑히지 않음
+pub struct SequenceReplayBuffer {
+    pub config: SequenceConfig,
+    streams: BTreeMap<String, VecDeque<ReplaySample>>, // 순서가 고정돼야 시드 재현 가능
+    rng: StdRng,
+}
+
+impl SequenceReplayBuffer {
+    pub fn new(config: SequenceConfig) -> Self {
+        Self::with_rng(config, StdRng::from_rng(&mut rand::rng()))
+    }
+
+    pub fn with_seed(config: SequenceConfig, seed: u64) -> Self {
+        Self::with_rng(config, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(config: SequenceConfig, rng: StdRng) -> Self {
+        Self {
+            config,
+            streams: BTreeMap::new(),
+            rng,
+        }
+    }
+
+    /// 스트림 끝에 샘플 추가 (같은 스트림에는 시간 순서대로 넣어야 함)
+    pub fn push(&mut self, stream: &str, sample: ReplaySample) {
+        let capacity = self.config.capacity;
+        let samples = self.streams.entry(stream.to_string()).or_default();
+        if samples.len() >= capacity {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    pub fn extend(&mut self, stream: &str, samples: impl IntoIterator<Item = ReplaySample>) {
+        for sample in samples {
+            self.push(stream, sample);
+        }
+    }
+
+    /// 📥 뽑을 수 있는 구간 중 batch_size개를 균등 샘플링 (중복 없음)
+    /// 각 구간은 burn_in + length개의 연속 샘플
+    pub fn sample(&mut self, batch_size: usize) -> Vec<Vec<ReplaySample>> {
+        let window = self.config.window();
+        let starts = sequence_starts(&self.streams, window);
+        let amount = batch_size.min(starts.len());
+        index::sample(&mut self.rng, starts.len(), amount)
+            .into_iter()
+            .map(|i| {
+                let (samples, start) = starts[i];
+                samples.range(start..start + window).cloned().collect()
+            })
+            .collect()
+    }
+
+    /// 뽑을 수 있는 구간 수
+    pub fn sequences(&self) -> usize {
+        sequence_starts(&self.streams, self.config.window()).len()
+    }
+
+    /// 전체 샘플 수
+    pub fn len(&self) -> usize {
+        self.streams.values().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_ready(&self, batch_size: usize) -> bool {
+        self.sequences() >= batch_size
+    }
+}
+
+/// 구간 시작 위치들: 마지막을 뺀 나머지 스텝에 done이 없어야 함
+fn sequence_starts(
+    streams: &BTreeMap<String, VecDeque<ReplaySample>>,
+    window: usize,
+) -> Vec<(&VecDeque<ReplaySample>, usize)> {
+    let window = window.max(1);
+    let mut starts = Vec::new();
+    for samples in streams.values() {
+        let mut run = 0; // 지금 위치까지 done 없이 이어진 샘플 수
+        for (i, sample) in samples.iter().enumerate() {
+            run += 1;
+            if run >= window {
+                starts.push((samples, i + 1 - window));
+            }
+            if sample.done {
+                run = 0;
+            }
+        }
+    }
+    starts
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+/// 📋 보상 계산에 필요한 한 스텝의 결과
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepOutcome {
+    pub action: usize,                // 0 = Buy, 1 = Sell, 2 = Hold
+    pub valid: bool,                  // 중복 매수 / 빈 포지션 매도 / 잔고 부족이면 false
+    pub realized_return: Option<f32>, // 매도로 확정된 수익률 (비용 차감 후)
+    pub equity_before: f64,
+    pub equity: f64,
+    pub peak_equity: f64,     // 지금까지의 최고 평가금액
+    pub holding: bool,        // 스텝 이후 포지션 보유 여부
+    pub holding_steps: usize, // 현재 포지션을 들고 있던 스텝 수
+}
+
+impl StepOutcome {
+    /// 평가금액 단순 변화율
+    pub fn equity_return(&self) -> f64 {
+        if self.equity_before > 0.0 {
+            (self.equity - self.equity_before) / self.equity_before
+        } else {
+            0.0
+        }
+    }
+
+    /// 최고점 대비 낙폭 (0.0 ~ 1.0)
+    pub fn drawdown(&self) -> f64 {
+        if self.peak_equity > 0.0 {
+            ((self.peak_equity - self.equity) / self.peak_equity).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 🎯 보상 함수
+pub trait RewardFn: Send {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32;
+
+    /// 에피소드가 새로 시작될 때 내부 상태 초기화
+    fn reset(&mut self) {}
+}
+
+// === 기본 제공 보상 함수 ===
+
+/// 매도 시 확정 수익률, 잘못된 행동은 패널티
+pub struct RealizedReturn {
+    pub invalid_penalty: f32,
+}
+
+impl RewardFn for RealizedReturn {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        if !outcome.valid {
+            return -self.invalid_penalty;
+        }
+        outcome.realized_return.unwrap_or(0.0)
+    }
+}
+
+/// 평가금액 단순 변화율
+pub struct EquityChange;
+
+impl RewardFn for EquityChange {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        outcome.equity_return() as f32
+    }
+}
+
+/// 평가금액 로그 변화율 ln(equity / equity_before)
+pub struct LogEquityChange;
+
+impl RewardFn for LogEquityChange {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        if outcome.equity_before > 0.0 && outcome.equity > 0.0 {
+            (outcome.equity / outcome.equity_before).ln() as f32
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 차분 샤프 비율 (Moody & Saffell, 1998)
+/// 수익률의 지수이동 1차/2차 모멘트(A, B)를 eta 속도로 갱신하면서
+/// 이번 수익률이 샤프 비율을 얼마나 올렸는지를 보상으로 사용
+pub struct DifferentialSharpe {
+    pub eta: f64,
+    a: f64,
+    b: f64,
+}
+
+impl DifferentialSharpe {
+    pub fn new(eta: f64) -> Self {
+        Self {
+            eta,
+            a: 0.0,
+            b: 0.0,
+        }
+    }
+}
+
+impl RewardFn for DifferentialSharpe {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        let r = outcome.equity_return();
+        let delta_a = r - self.a;
+        let delta_b = r * r - self.b;
+
+        let variance = self.b - self.a * self.a;
+        let d = if variance > 1e-12 {
+            (self.b * delta_a - 0.5 * self.a * delta_b) / variance.powf(1.5)
+        } else {
+            0.0
+        };
+
+        self.a += self.eta * delta_a;
+        self.b += self.eta * delta_b;
+        d as f32
+    }
+
+    fn reset(&mut self) {
+        self.a = 0.0;
+        self.b = 0.0;
+    }
+}
+
+/// 평가금액 변화율 - penalty x 현재 낙폭
+pub struct DrawdownPenalized {
+    pub penalty: f32,
+}
+
+impl RewardFn for DrawdownPenalized {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        (outcome.equity_return() - self.penalty as f64 * outcome.drawdown()) as f32
+    }
+}
+
+/// 포지션을 오래 들고 있을수록 매 스텝 패널티 (grace_steps까지는 면제)
+pub struct HoldingTimePenalty {
+    pub per_step: f32,
+    pub grace_steps: usize,
+}
+
+impl RewardFn for HoldingTimePenalty {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        if outcome.holding && outcome.holding_steps > self.grace_steps {
+            -self.per_step
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 여러 보상 함수의 가중합
+pub struct WeightedSum {
+    pub terms: Vec<(f32, Box<dyn RewardFn>)>,
+}
+
+impl RewardFn for WeightedSum {
+    fn reward(&mut self, outcome: &StepOutcome) -> f32 {
+        self.terms
+            .iter_mut()
+            .map(|(weight, f)| *weight * f.reward(outcome))
+            .sum()
+    }
+
+    fn reset(&mut self) {
+        for (_, f) in self.terms.iter_mut() {
+            f.reset();
+        }
+    }
+}
+
+// === 설정 ===
+
+/// ⚙️ 설정 파일(JSON)에서 고를 수 있는 보상 함수
+/// 예: {"kind": "weighted", "terms": [
+///        {"weight": 1.0, "reward": {"kind": "log_equity_change"}},
+///        {"weight": 0.5, "reward": {"kind": "drawdown_penalized", "penalty": 0.1}}]}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RewardConfig {
+    RealizedReturn {
+        #[serde(default = "default_invalid_penalty")]
+        invalid_penalty: f32,
+    },
+    EquityChange,
+    LogEquityChange,
+    DifferentialSharpe {
+        #[serde(default = "default_eta")]
+        eta: f64,
+    },
+    DrawdownPenalized {
+        penalty: f32,
+    },
+    HoldingTimePenalty {
+        per_step: f32,
+        #[serde(default)]
+        grace_steps: usize,
+    },
+    Weighted {
+        terms: Vec<WeightedReward>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightedReward {
+    pub weight: f32,
+    pub reward: RewardConfig,
+}
+
+fn default_invalid_penalty() -> f32 {
+    0.01
+}
+
+fn default_eta() -> f64 {
+    0.01
+}
+
+impl Default for RewardConfig {
+    /// 기존 Env와 같은 보상 (매도 수익률, 잘못된 행동 -0.01)
+    fn default() -> Self {
+        RewardConfig::RealizedReturn {
+            invalid_penalty: default_invalid_penalty(),
+        }
+    }
+}
+
+impl RewardConfig {
+    /// 🔧 설정으로부터 보상 함수 생성
+    pub fn build(&self) -> Box<dyn RewardFn> {
+        match self {
+            RewardConfig::RealizedReturn { invalid_penalty } => Box::new(RealizedReturn {
+                invalid_penalty: *invalid_penalty,
+            }),
+            RewardConfig::EquityChange => Box::new(EquityChange),
+            RewardConfig::LogEquityChange => Box::new(LogEquityChange),
+            RewardConfig::DifferentialSharpe { eta } => Box::new(DifferentialSharpe::new(*eta)),
+            RewardConfig::DrawdownPenalized { penalty } => {
+                Box::new(DrawdownPenalized { penalty: *penalty })
+            }
+            RewardConfig::HoldingTimePenalty {
+                per_step,
+                grace_steps,
+            } => Box::new(HoldingTimePenalty {
+                per_step: *per_step,
+                grace_steps: *grace_steps,
+            }),
+            RewardConfig::Weighted { terms } => Box::new(WeightedSum {
+                terms: terms.iter().map(|t| (t.weight, t.reward.build())).collect(),
+            }),
+        }
+    }
+
+    /// 📂 JSON 설정 파일에서 읽기
+    pub fn from_json_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::analyzer::{FeatureSource, TimeWindow};
+use crate::indicators::IndicatorFeature;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// 분석기(MarketFeatures)가 만들어 주는 피처 이름 (기본 스키마 순서)
+pub const MARKET_FEATURES: [&str; 12] = [
+    "avg_price",
+    "price_delta",
+    "volume_sum",
+    "volatility",
+    "imbalance",
+    "spread",
+    "ask1_price",
+    "bid1_price",
+    "ask_depth_ratio",
+    "bid_depth_ratio",
+    "tick_speed",
+    "last_tick_size",
+];
+
+/// 마이크로스트럭처 피처 (기본 스키마에는 없음, 이름으로 골라서 추가)
+pub const MICROSTRUCTURE_FEATURES: [&str; 6] = [
+    "ofi",
+    "microprice",
+    "vwap",
+    "trade_imbalance",
+    "trade_intensity",
+    "tick_gap",
+];
+
+/// 시간 구간별로 다시 계산되는 틱 피처 (호가 피처는 구간과 무관하게 최신 오더북 기준)
+pub const WINDOWED_FEATURES: [&str; 6] = [
+    "avg_price",
+    "price_delta",
+    "volume_sum",
+    "volatility",
+    "tick_speed",
+    "last_tick_size",
+];
+
+/// 호가 단계마다 들어가는 값 4개 (오더북 블록 [levels, 4]의 열 순서)
+/// 가격 오프셋은 중간가 대비 bp, 잔량은 보이는 전체 잔량(매도 + 매수) 대비 비율
+pub const DEPTH_FIELDS: [&str; 4] = ["ask_offset", "ask_size", "bid_offset", "bid_size"];
+
+/// Upbit 오더북의 호가 단계 수
+pub const UPBIT_DEPTH_LEVELS: usize = 15;
+
+/// 📐 관측 벡터의 피처 이름과 순서
+/// 관측 길이, 모델 입력 크기, 리플레이 파일 열 이름이 모두 여기서 정해짐
+/// "volatility@10s"처럼 @구간을 붙이면 최근 10초 틱으로만 계산한 값 (analyzer::TimeWindow)
+/// "rsi_14", "macd_12_26_9:signal@1m"처럼 기술적 지표도 넣을 수 있음 (indicators::IndicatorFeature)
+/// with_depth로 붙인 "depth{단계}_{값}" 블록은 항상 맨 뒤에 있고, 모델이 [levels, 4]로 다시 접어서 씀
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSchema {
+    pub names: Vec<String>,
+}
+
+/// 스키마 오류
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    Empty,
+    Duplicate(String),
+    Unknown(String), // MarketFeatures/지표에 없는 이름이거나 구간 형식이 잘못됨
+    Mismatch {
+        expected: FeatureSchema,
+        found: FeatureSchema,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Empty => write!(f, "피처가 하나도 없습니다"),
+            SchemaError::Duplicate(name) => write!(f, "피처 이름 중복: {}", name),
+            SchemaError::Unknown(name) => write!(f, "알 수 없는 피처: {}", name),
+            SchemaError::Mismatch { expected, found } => write!(
+                f,
+                "피처 스키마 불일치: 기대 {}개 [{}], 실제 {}개 [{}]",
+                expected.len(),
+                expected.names.join(", "),
+                found.len(),
+                found.names.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+impl Default for FeatureSchema {
+    /// 분석기 피처 12개 전부
+    fn default() -> Self {
+        Self {
+            names: MARKET_FEATURES.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+}
+
+impl FeatureSchema {
+    /// 🔧 분석기 피처 중 일부를 골라 순서를 정함
+    pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Result<Self, SchemaError> {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        if names.is_empty() {
+            return Err(SchemaError::Empty);
+        }
+        for (i, name) in names.iter().enumerate() {
+            let known = parse_name(name).is_some()
+                || IndicatorFeature::parse(name).is_some()
+                || parse_depth_name(name).is_some();
+            if !known {
+                return Err(SchemaError::Unknown(name.clone()));
+            }
+            if names[..i].contains(name) {
+                return Err(SchemaError::Duplicate(name.clone()));
+            }
+        }
+        Ok(Self { names })
+    }
+
+    /// 🕒 기본 피처 12개 + 구간마다 틱 피처 6개 ("volatility@10s" 등)
+    pub fn multi_horizon(windows: &[TimeWindow]) -> Self {
+        let mut schema = Self::default();
+        for window in windows {
+            schema.names.extend(
+                WINDOWED_FEATURES
+                    .iter()
+                    .map(|f| format!("{}@{}", f, window.label())),
+            );
+        }
+        schema
+    }
+
+    /// 이름 검증 없이 만듦 (리플레이 파일 등 외부에서 읽은 스키마용)
+    pub fn from_names(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    /// 이름을 모를 때 쓰는 0, 1, 2, ... (CSV 열 state_0, state_1, ...과 같음)
+    pub fn indexed(len: usize) -> Self {
+        Self {
+            names: (0..len).map(|i| i.to_string()).collect(),
+        }
+    }
+
+    /// 관측 벡터 길이 = 모델 입력 크기
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// 📚 오더북 블록 추가: levels개 호가 단계 x DEPTH_FIELDS (단계 순서, 맨 뒤에 붙음)
+    pub fn with_depth(mut self, levels: usize) -> Self {
+        self.names.extend(
+            (0..levels).flat_map(|level| DEPTH_FIELDS.iter().map(move |f| depth_name(level, f))),
+        );
+        self
+    }
+
+    /// 맨 뒤 오더북 블록의 호가 단계 수 (블록이 없거나 with_depth 순서가 아니면 0)
+    pub fn depth_levels(&self) -> usize {
+        let count = self
+            .names
+            .iter()
+            .filter(|name| parse_depth_name(name).is_some())
+            .count();
+        let levels = count / DEPTH_FIELDS.len();
+        let tail = &self.names[self.names.len() - levels * DEPTH_FIELDS.len()..];
+        let expected = FeatureSchema::from_names(Vec::new()).with_depth(levels);
+        if count % DEPTH_FIELDS.len() == 0 && tail == expected.names.as_slice() {
+            levels
+        } else {
+            0
+        }
+    }
+
+    /// 오더북 블록을 뺀 앞쪽 스칼라 피처 수
+    pub fn scalar_len(&self) -> usize {
+        self.len() - self.depth_levels() * DEPTH_FIELDS.len()
+    }
+
+    /// 분석기 피처 이름에 쓰인 시간 구간들 (짧은 것부터, 중복 없음, 지표의 봉 길이는 제외)
+    pub fn windows(&self) -> Vec<TimeWindow> {
+        let mut windows: Vec<TimeWindow> = self
+            .names
+            .iter()
+            .filter_map(|name| parse_name(name)?.1)
+            .collect();
+        windows.sort();
+        windows.dedup();
+        windows
+    }
+
+    /// 🧮 분석 결과를 스키마 순서의 관측 벡터로 (분석 결과에 없는 이름은 0.0)
+    /// 워밍업 중인 지표도 0.0이 되므로 루프는 IndicatorSet::is_warm이 true일 때만 관측을 씀
+    pub fn extract(&self, features: &impl FeatureSource) -> Vec<f32> {
+        self.names
+            .iter()
+            .map(|name| features.value(name).unwrap_or(0.0))
+            .collect()
+    }
+
+    /// 다르면 Mismatch 오류
+    pub fn ensure_matches(&self, found: &FeatureSchema) -> Result<(), SchemaError> {
+        if self == found {
+            Ok(())
+        } else {
+            Err(SchemaError::Mismatch {
+                expected: self.clone(),
+                found: found.clone(),
+            })
+        }
+    }
+
+    /// 💾 JSON으로 저장 / 읽기
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// "volatility@10s" → ("volatility", Some(10초)), 알 수 없는 피처나 구간이면 None
+pub fn parse_name(name: &str) -> Option<(&str, Option<TimeWindow>)> {
+    let (feature, window) = match name.split_once('@') {
+        Some((feature, label)) => (feature, Some(TimeWindow::parse(label)?)),
+        None => (name, None),
+    };
+    let known = MARKET_FEATURES.contains(&feature) || MICROSTRUCTURE_FEATURES.contains(&feature);
+    known.then_some((feature, window))
+}
+
+/// 오더북 블록의 피처 이름 (예: depth0_ask_offset)
+pub fn depth_name(level: usize, field: &str) -> String {
+    format!("depth{}_{}", level, field)
+}
+
+/// "depth3_bid_size" → (3, "bid_size"), 오더북 블록 이름이 아니면 None
+pub fn parse_depth_name(name: &str) -> Option<(usize, &str)> {
+    let (level, field) = name.strip_prefix("depth")?.split_once('_')?;
+    if level.is_empty() || !level.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let level = level.parse().ok()?;
+    DEPTH_FIELDS.contains(&field).then_some((level, field))
+}
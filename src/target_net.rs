@@ -0,0 +1,102 @@
+use burn::module::{Module, ModuleMapper, ModuleVisitor, ParamId};
+use burn::tensor::{Tensor, backend::Backend};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// 타겟 네트워크 갱신 방식
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetUpdate {
+    /// N번 학습 스텝마다 온라인 네트워크를 그대로 복사
+    Hard { every: usize },
+    /// 매 학습 스텝 θ' ← τ·θ + (1 - τ)·θ' (Polyak 평균)
+    Polyak { tau: f32 },
+}
+
+/// 🎯 TD 타겟 계산용 고정 네트워크
+/// 온라인 모델을 clone해서 만들기 때문에 파라미터 ID가 같고, 이를 기준으로 섞음
+pub struct TargetNetwork<M> {
+    pub model: M,
+    pub update: TargetUpdate,
+    steps: usize,
+}
+
+impl<M: Clone> TargetNetwork<M> {
+    pub fn new(online: &M, update: TargetUpdate) -> Self {
+        Self {
+            model: online.clone(),
+            update,
+            steps: 0,
+        }
+    }
+
+    /// 지금까지 진행한 학습 스텝 수
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// 🔄 온라인 모델의 optimizer step 직후 호출
+    pub fn step<B: Backend>(&mut self, online: &M)
+    where
+        M: Module<B>,
+    {
+        self.steps += 1;
+        match self.update {
+            TargetUpdate::Hard { every } => {
+                if self.steps.is_multiple_of(every.max(1)) {
+                    self.model = online.clone();
+                }
+            }
+            TargetUpdate::Polyak { tau } => {
+                self.model = polyak_update(self.model.clone(), online, tau);
+            }
+        }
+    }
+}
+
+/// θ' ← τ·θ + (1 - τ)·θ' (온라인 모델에 없는 파라미터는 그대로 둠)
+pub fn polyak_update<B: Backend, M: Module<B>>(target: M, online: &M, tau: f32) -> M {
+    let mut collector = ParamCollector::<B> {
+        params: HashMap::new(),
+    };
+    online.visit(&mut collector);
+
+    let mut mixer = PolyakMixer {
+        online: collector.params,
+        tau,
+        _backend: PhantomData,
+    };
+    target.map(&mut mixer)
+}
+
+/// 온라인 파라미터를 1차원으로 펴서 ID별로 모음
+struct ParamCollector<B: Backend> {
+    params: HashMap<ParamId, Tensor<B, 1>>,
+}
+
+impl<B: Backend> ModuleVisitor<B> for ParamCollector<B> {
+    fn visit_float<const D: usize>(&mut self, id: ParamId, tensor: &Tensor<B, D>) {
+        let flat = tensor
+            .clone()
+            .detach()
+            .reshape([tensor.shape().num_elements()]);
+        self.params.insert(id, flat);
+    }
+}
+
+struct PolyakMixer<B: Backend> {
+    online: HashMap<ParamId, Tensor<B, 1>>,
+    tau: f32,
+    _backend: PhantomData<B>,
+}
+
+impl<B: Backend> ModuleMapper<B> for PolyakMixer<B> {
+    fn map_float<const D: usize>(&mut self, id: ParamId, tensor: Tensor<B, D>) -> Tensor<B, D> {
+        match self.online.remove(&id) {
+            Some(online) => {
+                let online = online.reshape(tensor.shape());
+                (tensor.detach() * (1.0 - self.tau) + online * self.tau).detach()
+            }
+            None => tensor,
+        }
+    }
+}
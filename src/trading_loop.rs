@@ -1,53 +1,87 @@
 use crate::agent::Agent;
-use crate::analyzer::{MarketStorage, analyze};
-use crate::dqn_model::DqnModel;
-use crate::env::Env;
+use crate::dqn_model::QPolicy;
+use crate::env::{Env, EnvConfig};
+use crate::feed::{MarketEvent, MarketFeed};
+use crate::incremental::MultiMarketAnalyzer;
+use crate::indicators::IndicatorSet;
 use crate::replay_log::ReplaySample;
 use crate::types::B;
-use crate::websocket::{OrderBookData, TickData};
 
-use burn::tensor::{Tensor, backend::Backend};
-use tokio::sync::mpsc::Receiver;
-use tokio::time::{Duration, timeout};
+use burn::tensor::backend::Backend;
+use std::collections::HashMap;
 
-pub async fn run_trading_loop(
+/// 🔁 피드에서 이벤트를 받아 행동을 고르고 경험(ReplaySample)을 모음
+/// 마켓마다 저장소/분석/Env(포지션)가 따로 돌아감 (새 마켓의 Env는 env_config로 생성)
+/// 에피소드가 끝난 Env는 done 샘플을 남기고 reset 후 계속 진행
+/// 피드가 끝나면 마켓별 마지막 샘플을 done으로 표시하고 모인 만큼만 반환
+/// policy는 DqnModel이나 recurrent::RecurrentPolicy (에피소드가 끝난 마켓은 reset)
+/// 정책이 env_config의 관측을 받을 수 없으면 (QPolicy::check_env) 시작하기 전에 panic
+pub async fn run_trading_loop<F: MarketFeed, P: QPolicy>(
     agent: &mut Agent,
-    model: &mut DqnModel<B>,
-    env: &mut Env<B>,
-    tick_receiver: &mut Receiver<TickData>,
-    order_receiver: &mut Receiver<OrderBookData>,
+    policy: &mut P,
+    envs: &mut HashMap<String, Env<B>>,
+    env_config: &EnvConfig,
+    feed: &mut F,
     device: &<B as Backend>::Device,
-) -> Vec<ReplaySample<B>> {
-    let mut latest_order: Option<OrderBookData> = None;
-    let mut storage = MarketStorage::new(200);
-    let mut replay_batch: Vec<ReplaySample<B>> = Vec::new();
+) -> Vec<ReplaySample> {
+    if let Err(e) = policy.check_env(env_config) {
+        panic!("정책과 Env 설정이 맞지 않습니다: {}", e);
+    }
+    let windows = env_config.schema.windows(); // 스키마가 쓰는 시간 구간
+    let mut storage = MultiMarketAnalyzer::new(200, &windows)
+        .with_indicators(IndicatorSet::from_schema(&env_config.schema)); // 스키마가 쓰는 지표
+    let mut replay_batch: Vec<ReplaySample> = Vec::new();
+    let mut last_sample: HashMap<String, usize> = HashMap::new(); // 마켓별 마지막 샘플 위치
 
     while replay_batch.len() < 100 {
-        if let Ok(Some(order)) = timeout(Duration::from_millis(10), order_receiver.recv()).await {
-            latest_order = Some(order.clone());
-            storage.push_orderbook(order);
-        }
+        let Some(event) = feed.next_event().await else {
+            // 🏁 데이터 끝: 진행 중이던 에피소드 마감
+            for (code, index) in &last_sample {
+                if let Some(env) = envs.get_mut(code) {
+                    env.end_of_data();
+                }
+                replay_batch[*index].done = true;
+            }
+            break;
+        };
 
-        if let Ok(Some(tick)) = timeout(Duration::from_millis(10), tick_receiver.recv()).await {
-            storage.push_tick(tick.clone());
+        match event {
+            MarketEvent::OrderBook(order) => {
+                storage.push_orderbook(order);
+            }
+            MarketEvent::Tick(tick) => {
+                let market = storage.push_tick(&tick);
+                if !market.indicators().is_warm() {
+                    continue; // 지표 워밍업 중 (값이 0.0으로 채워진 관측으로 결정하지 않음)
+                }
 
-            if let Some(order) = &latest_order {
-                if let Some(features) = analyze(&storage) {
-                    env.update(features);
+                if let Some(features) = market.analyze_horizons() {
+                    let env = envs
+                        .entry(tick.code.clone())
+                        .or_insert_with(|| Env::with_config(*device, env_config.clone()));
+                    env.update((features, market.indicators(), market.depth()));
+                    if let Some(ob) = market.orderbook() {
+                        env.update_orderbook(ob.clone());
+                    }
 
-                    let state = env.observe();
-                    let q_values = model.forward(state.clone());
-                    let q_data = q_values.to_data().convert::<f32>();
-                    let q_array = q_data.as_slice::<f32>().unwrap();
-                    let action = agent.select_action(q_array);
+                    let state = env.observation();
+                    let q_values = policy.q_values(&tick.code, env.observe());
+                    let action = agent.select_action(&q_values);
 
                     let (next_state, reward) = env.step(action, tick.clone());
+                    let done = env.is_done();
+                    if done {
+                        env.reset();
+                        policy.reset(&tick.code);
+                    }
 
+                    last_sample.insert(tick.code, replay_batch.len());
                     replay_batch.push(ReplaySample {
                         state,
                         action,
                         reward,
                         next_state,
+                        done,
                     });
                 }
             }
@@ -1,100 +1,288 @@
-use crate::dqn_model::DqnModel;
-use crate::replay_log::ReplaySample;
+use crate::dqn_model::{DqnModel, QNetwork};
+use crate::replay_loader::load_replay_csv;
+use crate::replay_log::{ReplaySample, stack_observations};
+use crate::replaybuffer::PrioritizedReplayBuffer;
+use crate::target_net::{TargetNetwork, TargetUpdate};
 use crate::types::B;
-use burn::tensor::{backend::Backend, Tensor};
-use burn::nn::loss::{MseLoss, Reduction};
-use burn::optim::{AdamConfig, Optimizer, GradientsParams};
-use burn::module::Module;
-use burn::record::{CompactRecorder, Recorder};
-use std::fs::File;
+use burn::module::AutodiffModule;
+use burn::optim::{AdamConfig, GradientsParams, Optimizer};
+use burn::tensor::{Int, Tensor, backend::Backend};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use std::error::Error;
-use csv::Reader;
-use std::io::BufReader;
+
+/// 할인율
+pub const GAMMA: f32 = 0.9;
+
+/// 🎯 TD 타겟: 종료 전이는 다음 상태 가치를 더하지 않음
+pub fn td_target(reward: f32, max_next_q: f32, done: bool, gamma: f32) -> f32 {
+    if done {
+        reward
+    } else {
+        reward + gamma * max_next_q
+    }
+}
 
 /// CSV 파일을 불러와서 ReplaySample 리스트로 변환하는 함수
 pub fn load_samples_from_csv(filename: &str) -> Result<Vec<ReplaySample>, Box<dyn Error>> {
-    let file = File::open(filename)?;
-    let mut reader = Reader::from_reader(BufReader::new(file));
+    load_replay_csv(filename)
+}
 
-    let device = <B as Backend>::Device::default();
-    let mut samples = Vec::new();
+/// ⚙️ 학습 설정
+#[derive(Debug, Clone)]
+pub struct TrainConfig {
+    pub learning_rate: f64,
+    pub gamma: f32,
+    pub epochs: usize,
+    pub target_update: TargetUpdate, // 타겟 네트워크 동기화 방식
+    pub double_dqn: bool,            // true면 온라인 네트워크가 행동을 고르고 타겟 네트워크가 평가
+    pub batch_size: usize,
+    pub seed: u64, // 미니배치 셔플용 시드
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.001,
+            gamma: GAMMA,
+            epochs: 1,
+            target_update: TargetUpdate::Hard { every: 100 },
+            double_dqn: false,
+            batch_size: 32,
+            seed: 42,
+        }
+    }
+}
 
-    for result in reader.records() {
-        let record = result?;
+/// 다음 상태의 가치
+/// DQN: max_a Q_target(s', a)
+/// Double DQN: Q_target(s', argmax_a Q_online(s', a))
+pub fn next_state_value(online_next_q: &[f32], target_next_q: &[f32], double_dqn: bool) -> f32 {
+    if double_dqn {
+        let best = argmax(online_next_q);
+        target_next_q[best]
+    } else {
+        target_next_q
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+}
 
-        let action: usize = record.get(0).unwrap().parse()?;
-        let reward: f32 = record.get(1).unwrap().parse()?;
+fn argmax(values: &[f32]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (i, &v)| {
+            if v > best.1 { (i, v) } else { best }
+        })
+        .0
+}
 
-        let mut state_vec = Vec::new();
-        for i in 2..14 {
-            let value: f32 = record.get(i).unwrap().parse()?;
-            state_vec.push(value);
-        }
-        let state = Tensor::<B, 2>::from_floats(state_vec.as_slice(), &device)
-            .reshape([1, state_vec.len()]);
+/// 📦 ReplaySample 묶음을 한 번에 계산할 수 있도록 쌓은 텐서들 (학습 직전에 한 번만 변환)
+pub struct Batch {
+    pub states: Tensor<B, 2>,       // [batch, 피처 수]
+    pub actions: Tensor<B, 2, Int>, // [batch, 1]
+    pub rewards: Tensor<B, 2>,      // [batch, 1]
+    pub next_states: Tensor<B, 2>,  // [batch, 피처 수]
+    pub not_done: Tensor<B, 2>,     // [batch, 1], 종료 전이는 0
+}
+
+impl Batch {
+    pub fn from_samples(samples: &[ReplaySample], device: &<B as Backend>::Device) -> Self {
+        let n = samples.len();
+        let column = |values: Vec<f32>| {
+            Tensor::<B, 1>::from_floats(values.as_slice(), device).reshape([n, 1])
+        };
 
-        let mut next_state_vec = Vec::new();
-        for i in 14..26 {
-            let value: f32 = record.get(i).unwrap().parse()?;
-            next_state_vec.push(value);
+        let actions: Vec<i64> = samples.iter().map(|s| s.action as i64).collect();
+        Self {
+            states: stack_observations(samples.iter().map(|s| s.state.as_slice()), device),
+            actions: Tensor::<B, 1, Int>::from_ints(actions.as_slice(), device).reshape([n, 1]),
+            rewards: column(samples.iter().map(|s| s.reward).collect()),
+            next_states: stack_observations(
+                samples.iter().map(|s| s.next_state.as_slice()),
+                device,
+            ),
+            not_done: column(
+                samples
+                    .iter()
+                    .map(|s| if s.done { 0.0 } else { 1.0 })
+                    .collect(),
+            ),
         }
-        let next_state = Tensor::<B, 2>::from_floats(next_state_vec.as_slice(), &device)
-            .reshape([1, next_state_vec.len()]);
-
-        samples.push(ReplaySample {
-            state,
-            action,
-            reward,
-            next_state,
-        });
     }
+}
 
-    Ok(samples)
+/// 🎯 배치 TD 타겟 r + γ·(1 - done)·V(s'), 그래디언트는 흐르지 않음
+pub fn batch_td_targets<M: QNetwork<B>>(
+    online: &M,
+    target: &M,
+    batch: &Batch,
+    config: &TrainConfig,
+) -> Tensor<B, 2> {
+    let target_next = target.forward(batch.next_states.clone()).detach();
+    let next_value = if config.double_dqn {
+        let best = online.forward(batch.next_states.clone()).detach().argmax(1);
+        target_next.gather(1, best)
+    } else {
+        target_next.max_dim(1)
+    };
+
+    (batch.rewards.clone() + batch.not_done.clone() * next_value * config.gamma).detach()
 }
 
-/// CSV 파일을 읽어와서 모델을 학습시키는 함수
-pub fn train_from_csv(csv_path: &str, model: &mut DqnModel<B>) -> Result<(), Box<dyn Error>> {
-    println!("📚 학습 시작: {}", csv_path);
+/// ⚡ 미니배치 한 번 학습: Q(s, a)를 디바이스에서 gather, 배치 전체 loss로 optimizer step 한 번
+/// 학습된 모델과 loss를 반환
+/// 모델은 QNetwork면 무엇이든 (DqnModel, transformer::TransformerDqn)
+pub fn train_step<M, O>(
+    model: M,
+    optimizer: &mut O,
+    target: &M,
+    batch: &[ReplaySample],
+    config: &TrainConfig,
+) -> (M, f32)
+where
+    M: QNetwork<B> + AutodiffModule<B>,
+    O: Optimizer<M, B>,
+{
+    let weights = vec![1.0; batch.len()];
+    let output = train_step_weighted(model, optimizer, target, batch, &weights, config);
+    (output.model, output.loss)
+}
 
-    let samples = load_samples_from_csv(csv_path)?;
+/// 가중치 미니배치 학습 결과
+pub struct StepOutput<M = DqnModel<B>> {
+    pub model: M,
+    pub loss: f32,
+    pub td_errors: Vec<f32>, // 샘플별 target - Q(s, a), 우선순위 갱신용
+}
+
+/// ⚖️ 샘플별 가중치(중요도 샘플링 보정)를 곱한 loss mean(w·(Q(s, a) - target)²)로 학습
+/// 가중치가 모두 1이면 train_step과 같음
+pub fn train_step_weighted<M, O>(
+    model: M,
+    optimizer: &mut O,
+    target: &M,
+    batch: &[ReplaySample],
+    weights: &[f32],
+    config: &TrainConfig,
+) -> StepOutput<M>
+where
+    M: QNetwork<B> + AutodiffModule<B>,
+    O: Optimizer<M, B>,
+{
     let device = <B as Backend>::Device::default();
-    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
-    let loss_fn = MseLoss::new();
+    let n = batch.len();
+    let batch = Batch::from_samples(batch, &device);
+    let weights = Tensor::<B, 1>::from_floats(weights, &device).reshape([n, 1]);
+
+    let targets = batch_td_targets(&model, target, &batch, config);
+    let q_taken = model
+        .forward(batch.states.clone())
+        .gather(1, batch.actions.clone());
+    let errors = targets - q_taken;
+    let td_errors = errors.clone().detach().to_data().convert::<f32>();
+
+    let loss = (errors.powf_scalar(2.0) * weights).mean();
+    let loss_value = loss.clone().into_scalar();
+
+    let grads = GradientsParams::from_grads(loss.backward(), &model);
+    StepOutput {
+        model: optimizer.step(config.learning_rate, model, grads),
+        loss: loss_value,
+        td_errors: td_errors.to_vec::<f32>().unwrap(),
+    }
+}
 
-    let learning_rate = 0.001;
+/// 🎯 우선순위 버퍼에서 batch_size개를 뽑아 가중치 loss로 학습하고 TD 오차로 우선순위 갱신
+pub fn train_step_prioritized<M, O>(
+    model: M,
+    optimizer: &mut O,
+    target: &M,
+    buffer: &mut PrioritizedReplayBuffer,
+    config: &TrainConfig,
+) -> (M, f32)
+where
+    M: QNetwork<B> + AutodiffModule<B>,
+    O: Optimizer<M, B>,
+{
+    let batch = buffer.sample(config.batch_size);
+    if batch.samples.is_empty() {
+        return (model, 0.0);
+    }
 
-    for sample in samples.iter() {
-        let pred = model.forward(sample.state.clone());
-        let next_q = model.forward(sample.next_state.clone());
+    let output = train_step_weighted(
+        model,
+        optimizer,
+        target,
+        &batch.samples,
+        &batch.weights,
+        config,
+    );
+    buffer.update_priorities(&batch.indices, &output.td_errors);
+    (output.model, output.loss)
+}
 
-        let pred_data = pred.to_data().convert::<f32>();
-        let pred_data = pred_data.as_slice::<f32>().unwrap();
-        let next_data = next_q.to_data().convert::<f32>();
-        let next_data = next_data.as_slice::<f32>().unwrap();
+/// CSV 파일을 읽어와서 모델을 학습시키는 함수
+pub fn train_from_csv<M: QNetwork<B> + AutodiffModule<B>>(
+    csv_path: &str,
+    model: &mut M,
+) -> Result<(), Box<dyn Error>> {
+    train_from_csv_with(csv_path, model, &TrainConfig::default())
+}
 
-        let max_next_q = next_data.iter().copied().fold(f32::NEG_INFINITY, f32::max);
-        let target = sample.reward + 0.9 * max_next_q;
+/// 설정을 지정해서 CSV 학습
+pub fn train_from_csv_with<M: QNetwork<B> + AutodiffModule<B>>(
+    csv_path: &str,
+    model: &mut M,
+    config: &TrainConfig,
+) -> Result<(), Box<dyn Error>> {
+    println!("📚 학습 시작: {}", csv_path);
 
-        println!(
-            "🎯 액션: {}, 예측값: {:.3}, 타겟값: {:.3}",
-            sample.action, pred_data[sample.action], target
-        );
+    let samples = load_samples_from_csv(csv_path)?;
+    let loss = train_samples(&samples, model, config);
 
-        let mut target_vec = pred_data.to_vec();
-        target_vec[sample.action] = target;
+    println!("✅ 학습 완료 (마지막 에포크 평균 Loss: {:.6})", loss);
+    Ok(())
+}
 
-        let target_tensor = Tensor::<B, 2>::from_floats(target_vec.as_slice(), &device)
-            .reshape([1, target_vec.len()]);
-        let pred_tensor = pred.reshape([1, pred.shape().dims::<2>()[0]]);
+/// 🧠 에포크마다 샘플을 섞어 batch_size씩 train_step, TD 타겟은 타겟 네트워크로 계산
+/// 마지막 에포크의 평균 loss를 반환
+pub fn train_samples<M: QNetwork<B> + AutodiffModule<B>>(
+    samples: &[ReplaySample],
+    model: &mut M,
+    config: &TrainConfig,
+) -> f32 {
+    let mut optimizer = AdamConfig::new().init::<B, M>();
+    let mut target_net = TargetNetwork::new(model, config.target_update);
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    let mut epoch_loss = 0.0;
 
-        let loss = loss_fn.forward(pred_tensor, target_tensor, Reduction::Mean);
+    for _ in 0..config.epochs {
+        order.shuffle(&mut rng);
+        let mut total = 0.0;
+        let mut batches = 0;
 
-        let grads = loss.backward();
-        let grads_params = GradientsParams::from_grads(grads, model);
+        for chunk in order.chunks(config.batch_size.max(1)) {
+            let batch: Vec<ReplaySample> = chunk.iter().map(|&i| samples[i].clone()).collect();
+            let (trained, loss) = train_step(
+                model.clone(),
+                &mut optimizer,
+                &target_net.model,
+                &batch,
+                config,
+            );
+            *model = trained;
+            target_net.step(model);
+            total += loss;
+            batches += 1;
+        }
 
-        *model = optimizer.step(learning_rate, model.clone(), grads_params);
+        epoch_loss = total / batches.max(1) as f32;
     }
 
-    println!("✅ 학습 완료");
-    Ok(())
+    epoch_loss
 }
@@ -1,21 +1,34 @@
 use crate::dqn_model::DqnModel;
-use crate::replay_loader::load_replay_csv;
+use crate::replay_loader::{load_replay_csv, read_replay_csv_schema};
 use crate::replay_log::ReplaySample;
-use crate::train::train_step;
+use crate::target_net::TargetNetwork;
+use crate::train::{TrainConfig, train_step};
 use crate::types::B;
 
-use burn::optim::{AdamConfig, Optimizer};
+use burn::optim::AdamConfig;
 use burn::tensor::backend::Backend;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
 
-pub fn run_training(csv_path: &str, epochs: usize, batch_size: usize) {
+/// 📚 CSV 리플레이에서 매 에포크 batch_size개를 무작위로 뽑아 학습
+/// 학습된 모델을 반환 (데이터가 부족하면 None)
+pub fn run_training(csv_path: &str, epochs: usize, batch_size: usize) -> Option<DqnModel<B>> {
     let device = <B as Backend>::Device::default();
-
-    // 모델 & 옵티마이저 초기화
-    let mut model = DqnModel::new(&device);
-    let mut optimizer = AdamConfig::new().init::<B, DqnModel>().into();
+    let config = TrainConfig {
+        epochs,
+        batch_size,
+        ..TrainConfig::default()
+    };
 
     // CSV에서 학습 샘플 로드
-    let dataset: Vec<ReplaySample> = load_replay_csv(csv_path, &device);
+    let dataset: Vec<ReplaySample> = match load_replay_csv(csv_path) {
+        Ok(dataset) => dataset,
+        Err(e) => {
+            println!("❗ 리플레이 로드 실패: {}", e);
+            return None;
+        }
+    };
 
     if dataset.len() < batch_size {
         println!(
@@ -23,29 +36,42 @@ pub fn run_training(csv_path: &str, epochs: usize, batch_size: usize) {
             dataset.len(),
             batch_size
         );
-        return;
+        return None;
     }
 
+    // 모델 & 옵티마이저 & 타겟 네트워크 초기화 (입력은 CSV 헤더의 스키마, 오더북 블록이 있으면 합성곱 인코더)
+    let schema = match read_replay_csv_schema(csv_path) {
+        Ok(schema) => schema,
+        Err(e) => {
+            println!("❗ 리플레이 헤더 읽기 실패: {}", e);
+            return None;
+        }
+    };
+    let mut model = DqnModel::<B>::from_schema(&device, &schema);
+    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
+    let mut target_net = TargetNetwork::new(&model, config.target_update);
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
     println!(
         "🔧 학습 시작: 총 {} 에포크, 배치 크기 {}",
         epochs, batch_size
     );
 
     for epoch in 1..=epochs {
-        // 무작위 셔플 + 배치 추출
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
+        // 무작위 배치 추출
         let batch: Vec<ReplaySample> = dataset
             .choose_multiple(&mut rng, batch_size)
             .cloned()
             .collect();
 
-        let (new_model, new_optimizer, loss) = train_step(model, optimizer, batch);
+        let (new_model, loss) =
+            train_step(model, &mut optimizer, &target_net.model, &batch, &config);
         model = new_model;
-        optimizer = new_optimizer;
+        target_net.step(&model);
 
         println!("📚 Epoch {:>3} | Loss: {:.6}", epoch, loss);
     }
 
     println!("✅ 학습 완료!");
+    Some(model)
 }
@@ -0,0 +1,352 @@
+use crate::dqn_model::QNetwork;
+use crate::dqn_model::QPolicy;
+use crate::env::EnvConfig;
+use crate::normalizer::FeatureNormalizer;
+use crate::replay_log::ReplaySample;
+use crate::schema::FeatureSchema;
+use crate::types;
+use burn::{
+    module::Module,
+    nn::{
+        Embedding, EmbeddingConfig, Linear, LinearConfig,
+        transformer::{TransformerEncoder, TransformerEncoderConfig, TransformerEncoderInput},
+    },
+    tensor::{Int, Tensor, backend::Backend},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// 🤖 트랜스포머 Q 네트워크
+//
+// 최근 window개 틱의 관측(프레임)을 토큰으로 보고 self-attention으로 섞은 뒤
+// 가장 최신 토큰의 출력으로 Q값을 냄
+// - 입력: 프레임을 오래된 것부터 이어 붙인 [batch, window x 피처 수] (DqnModel과 같은 2차원 입력이라
+//         train / model_saver 코드를 그대로 씀, 리플레이 샘플은 stack_window로 만듦)
+// - 위치 정보: 학습되는 위치 임베딩, 또는 최신 틱까지의 경과 시간(ms)을 사인/코사인으로 인코딩
+
+/// 경과 시간 인코딩의 가장 긴 주기 기준 (ms), 주기는 2π ms ~ 2π x 10초
+const TIME_SCALE_MS: f32 = 10_000.0;
+
+/// 📍 토큰 위치 정보를 넣는 방식
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PositionEncoding {
+    /// 위치(0..window)마다 학습되는 임베딩
+    Learned,
+    /// 프레임의 시간 간격 피처(직전 틱과의 ms, 기본 "tick_gap")를 누적한 최신 틱까지의 경과 시간
+    /// 틱 간격이 불규칙해도 실제 시간 차이를 반영 (이 피처는 정규화하지 않은 ms 값이어야 하고,
+    /// 토큰 임베딩에는 들어가지 않음 → 정규화기는 TransformerConfig::normalizer로 만듦)
+    TimeDelta { feature: String },
+}
+
+/// ⚙️ 트랜스포머 구조 설정 (체크포인트 옆에 {model_path}.transformer.json으로 함께 저장)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformerConfig {
+    pub window: usize,  // 몇 개의 최근 틱을 보는지
+    pub d_model: usize, // 토큰 크기 (짝수, heads로 나누어 떨어져야 함)
+    pub heads: usize,   // 어텐션 헤드 수
+    pub layers: usize,  // 인코더 층 수
+    pub d_ff: usize,    // 층마다 피드포워드 은닉 크기
+    pub position: PositionEncoding,
+}
+
+impl Default for TransformerConfig {
+    fn default() -> Self {
+        Self {
+            window: 16,
+            d_model: 32,
+            heads: 4,
+            layers: 2,
+            d_ff: 64,
+            position: PositionEncoding::Learned,
+        }
+    }
+}
+
+impl TransformerConfig {
+    /// ⏱️ "tick_gap" 피처로 경과 시간 인코딩 (스키마에 tick_gap이 있어야 함)
+    pub fn with_time_delta(mut self) -> Self {
+        self.position = PositionEncoding::TimeDelta {
+            feature: "tick_gap".to_string(),
+        };
+        self
+    }
+
+    /// 📏 이 설정에 맞는 빈 정규화기 (경과 시간 피처는 원본 ms 그대로 둠)
+    pub fn normalizer(&self, schema: &FeatureSchema) -> Result<FeatureNormalizer, Box<dyn Error>> {
+        let normalizer = FeatureNormalizer::new(schema.len());
+        Ok(match self.check(schema)? {
+            Some(index) => normalizer.with_raw(index),
+            None => normalizer,
+        })
+    }
+
+    /// 스키마 한 프레임 기준 모델 입력 크기 (window x 피처 수)
+    pub fn input_size(&self, schema: &FeatureSchema) -> usize {
+        self.window * schema.len()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// 설정 검사, 경과 시간 인코딩이면 프레임 안의 시간 간격 피처 위치를 반환
+    fn check(&self, schema: &FeatureSchema) -> Result<Option<usize>, Box<dyn Error>> {
+        if self.window == 0 || self.layers == 0 || self.heads == 0 {
+            return Err("window, layers, heads는 1 이상이어야 합니다".into());
+        }
+        if !self.d_model.is_multiple_of(2) || !self.d_model.is_multiple_of(self.heads) {
+            return Err(format!(
+                "d_model {}은 짝수이고 heads {}로 나누어 떨어져야 합니다",
+                self.d_model, self.heads
+            )
+            .into());
+        }
+        match &self.position {
+            PositionEncoding::Learned => Ok(None),
+            PositionEncoding::TimeDelta { feature } => match schema.index_of(feature) {
+                Some(index) => Ok(Some(index)),
+                None => Err(format!("스키마에 시간 간격 피처 {}가 없습니다", feature).into()),
+            },
+        }
+    }
+}
+
+#[derive(Module, Debug)]
+pub struct TransformerDqn<B: Backend> {
+    embed: Linear<B>,               // 프레임 → 토큰
+    position: Option<Embedding<B>>, // PositionEncoding::Learned일 때만
+    encoder: TransformerEncoder<B>,
+    out: Linear<B>,
+    window: usize,
+    frame: usize, // 프레임 하나의 피처 수
+    d_model: usize,
+    time_feature: Option<usize>, // PositionEncoding::TimeDelta일 때 프레임 안의 시간 간격 피처 위치
+}
+
+impl<B: Backend> TransformerDqn<B> {
+    /// 스키마는 프레임 하나의 피처 (입력은 config.input_size(schema))
+    /// 설정이 잘못됐거나 경과 시간 피처가 스키마에 없으면 오류
+    pub fn new(
+        device: &B::Device,
+        schema: &FeatureSchema,
+        config: &TransformerConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        let time_feature = config.check(schema)?;
+        let position = match config.position {
+            PositionEncoding::Learned => {
+                Some(EmbeddingConfig::new(config.window, config.d_model).init(device))
+            }
+            PositionEncoding::TimeDelta { .. } => None,
+        };
+        // 드롭아웃 없음: 같은 관측에 대해 Q값과 TD 타겟이 흔들리지 않게
+        let encoder =
+            TransformerEncoderConfig::new(config.d_model, config.d_ff, config.heads, config.layers)
+                .with_dropout(0.0)
+                .with_norm_first(true)
+                .init(device);
+
+        Ok(Self {
+            embed: LinearConfig::new(schema.len(), config.d_model).init(device),
+            position,
+            encoder,
+            out: LinearConfig::new(config.d_model, 3).init(device),
+            window: config.window,
+            frame: schema.len(),
+            d_model: config.d_model,
+            time_feature,
+        })
+    }
+
+    /// 한 번에 보는 프레임 수
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// 경과 시간 인코딩에 쓰는 프레임 안의 시간 간격 피처 위치 (Learned면 None)
+    pub fn time_feature(&self) -> Option<usize> {
+        self.time_feature
+    }
+
+    /// 📏 이 모델에 맞는 빈 정규화기 (TransformerConfig::normalizer와 같음, 경과 시간 피처는 원본 ms)
+    pub fn normalizer(&self) -> FeatureNormalizer {
+        let normalizer = FeatureNormalizer::new(self.frame);
+        match self.time_feature {
+            Some(index) => normalizer.with_raw(index),
+            None => normalizer,
+        }
+    }
+
+    /// 🔍 관측을 만드는 Env 설정과 맞는지 검사
+    /// 경과 시간 인코딩은 시간 간격 피처를 ms 그대로 받아야 하므로, 정규화기가 그 피처를 바꾸면 오류
+    pub fn check_env(&self, config: &EnvConfig) -> Result<(), Box<dyn Error>> {
+        if config.schema.len() != self.frame {
+            return Err(format!(
+                "Env 스키마의 피처 수 {}가 모델 프레임 {}와 다릅니다",
+                config.schema.len(),
+                self.frame
+            )
+            .into());
+        }
+        match (self.time_feature, &config.normalizer) {
+            (Some(index), Some(normalizer)) if !normalizer.is_raw(index) => Err(format!(
+                "정규화기가 시간 간격 피처 {}를 바꿉니다 (TransformerConfig::normalizer로 만들어야 함)",
+                config.schema.names[index]
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// [batch, window x 피처 수] (오래된 프레임부터) → [batch, 3]
+    pub fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        let [batch, _] = input.dims();
+        let frames = input.reshape([batch, self.window, self.frame]);
+        let tokens = match (&self.position, self.time_feature) {
+            (Some(position), _) => {
+                let index = Tensor::<B, 1, Int>::arange(0..self.window as i64, &frames.device())
+                    .reshape([1, self.window]);
+                self.embed.forward(frames) + position.forward(index)
+            }
+            (None, Some(feature)) => {
+                // 시간 간격(ms)은 크기가 커서 토큰에는 넣지 않고 경과 시간 인코딩으로만 씀
+                let keep: Vec<f32> = (0..self.frame)
+                    .map(|i| if i == feature { 0.0 } else { 1.0 })
+                    .collect();
+                let keep = Tensor::<B, 1>::from_floats(keep.as_slice(), &frames.device())
+                    .reshape([1, 1, self.frame]);
+                let time = self.time_encoding(frames.clone(), feature);
+                self.embed.forward(frames * keep) + time
+            }
+            (None, None) => self.embed.forward(frames),
+        };
+
+        let x = self.encoder.forward(TransformerEncoderInput::new(tokens));
+        let latest = x
+            .slice([0..batch, self.window - 1..self.window])
+            .reshape([batch, self.d_model]);
+        self.out.forward(latest)
+    }
+
+    /// ⏱️ 프레임별 최신 틱까지의 경과 시간(ms) → 사인/코사인 [batch, window, d_model]
+    /// 프레임 k의 경과 시간 = k+1번째부터 마지막 프레임까지의 시간 간격 합
+    fn time_encoding(&self, frames: Tensor<B, 3>, feature: usize) -> Tensor<B, 3> {
+        let [batch, window, _] = frames.dims();
+        let device = frames.device();
+        let gaps = frames
+            .slice([0..batch, 0..window, feature..feature + 1])
+            .reshape([batch, window]);
+        // later[j][k] = 1 (j > k): gaps x later = 프레임 k 뒤에 쌓인 간격의 합
+        let later: Vec<f32> = (0..window * window)
+            .map(|i| if i / window > i % window { 1.0 } else { 0.0 })
+            .collect();
+        let later =
+            Tensor::<B, 1>::from_floats(later.as_slice(), &device).reshape([window, window]);
+        let age = gaps.matmul(later).reshape([batch, window, 1]);
+
+        let half = self.d_model / 2;
+        let rates: Vec<f32> = (0..half)
+            .map(|i| TIME_SCALE_MS.powf(-(i as f32) / half as f32))
+            .collect();
+        let rates = Tensor::<B, 1>::from_floats(rates.as_slice(), &device).reshape([1, 1, half]);
+        let angle = age * rates;
+        Tensor::cat(vec![angle.clone().sin(), angle.cos()], 2)
+    }
+}
+
+impl<B: Backend> QNetwork<B> for TransformerDqn<B> {
+    fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        TransformerDqn::forward(self, input)
+    }
+}
+
+/// 📚 연속 구간(한 마켓, 시간 순서)을 트랜스포머 입력 샘플 하나로 합침
+/// state / next_state는 프레임을 이어 붙인 것, 행동 / 보상 / done은 마지막 스텝의 것
+/// 구간은 SequenceReplayBuffer(burn_in 0, length = window)에서 뽑으면 에피소드를 넘지 않음
+pub fn stack_window(window: &[ReplaySample]) -> ReplaySample {
+    let last = window.last().expect("빈 구간");
+    ReplaySample {
+        state: window
+            .iter()
+            .flat_map(|s| s.state.iter().copied())
+            .collect(),
+        action: last.action,
+        reward: last.reward,
+        next_state: window
+            .iter()
+            .flat_map(|s| s.next_state.iter().copied())
+            .collect(),
+        done: last.done,
+    }
+}
+
+/// 📡 실시간 추론용: 마켓별 최근 window개 관측을 들고 있다가 틱마다 Q값 계산
+/// window개가 모이기 전에는 가장 오래된 관측을 반복해서 채움, 에피소드가 끝나면 비움
+/// (경과 시간 인코딩이면 채운 프레임의 시간 간격은 0이라 실제 관측의 경과 시간이 늘어나지 않음)
+pub struct TransformerPolicy {
+    pub model: TransformerDqn<types::B>,
+    frames: HashMap<String, VecDeque<Tensor<types::B, 2>>>,
+}
+
+impl TransformerPolicy {
+    pub fn new(model: TransformerDqn<types::B>) -> Self {
+        Self {
+            model,
+            frames: HashMap::new(),
+        }
+    }
+
+    /// 관측을 들고 있는 마켓 수
+    pub fn markets(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+impl QPolicy for TransformerPolicy {
+    fn check_env(&self, config: &EnvConfig) -> Result<(), Box<dyn Error>> {
+        self.model.check_env(config)
+    }
+
+    fn normalizer(&self, _schema: &FeatureSchema) -> FeatureNormalizer {
+        self.model.normalizer()
+    }
+
+    fn q_values(&mut self, code: &str, observation: Tensor<types::B, 2>) -> Vec<f32> {
+        let window = self.model.window();
+        let frames = self.frames.entry(code.to_string()).or_default();
+        frames.push_back(observation);
+        if frames.len() > window {
+            frames.pop_front();
+        }
+
+        let oldest = frames
+            .front()
+            .cloned()
+            .map(|oldest| match self.model.time_feature() {
+                Some(feature) => {
+                    let zero = Tensor::zeros([1, 1], &oldest.device());
+                    oldest.slice_assign([0..1, feature..feature + 1], zero)
+                }
+                None => oldest,
+            });
+        let padding = oldest.into_iter().cycle().take(window - frames.len());
+        let input = Tensor::cat(padding.chain(frames.iter().cloned()).collect(), 1);
+        self.model
+            .forward(input)
+            .into_data()
+            .convert::<f32>()
+            .to_vec()
+            .unwrap()
+    }
+
+    fn reset(&mut self, code: &str) {
+        self.frames.remove(code);
+    }
+}
@@ -1,3 +1,3 @@
-use burn::backend::{ndarray::NdArray, Autodiff};
+use burn::backend::{Autodiff, ndarray::NdArray};
 
 pub type B = Autodiff<NdArray<f32>>;
@@ -0,0 +1,269 @@
+use crate::websocket::{OrderBookData, OrderBookUnit, TickData};
+use serde::Deserialize;
+use std::fmt;
+
+// === 공통 열거형 ===
+
+/// 매수/매도 구분
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum AskBid {
+    #[serde(rename = "ASK")]
+    Ask,
+    #[serde(rename = "BID")]
+    Bid,
+}
+
+impl AskBid {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AskBid::Ask => "ASK",
+            AskBid::Bid => "BID",
+        }
+    }
+}
+
+/// 전일 종가 대비 등락
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Change {
+    #[serde(rename = "RISE")]
+    Rise,
+    #[serde(rename = "EVEN")]
+    Even,
+    #[serde(rename = "FALL")]
+    Fall,
+}
+
+/// 스냅샷 / 실시간 구분
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum StreamType {
+    #[serde(rename = "SNAPSHOT")]
+    Snapshot,
+    #[serde(rename = "REALTIME")]
+    Realtime,
+}
+
+// === 메시지 구조체 ===
+
+/// 📈 체결 (type = "trade")
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpbitTrade {
+    pub code: String,
+    pub trade_price: f64,
+    pub trade_volume: f64,
+    pub ask_bid: AskBid,
+    pub prev_closing_price: f64,
+    pub change: Change,
+    pub change_price: f64,
+    pub trade_date: String, // "yyyy-MM-dd" (UTC)
+    pub trade_time: String, // "HH:mm:ss" (UTC)
+    pub trade_timestamp: u64,
+    pub timestamp: u64,
+    pub sequential_id: u64,
+    #[serde(default)]
+    pub best_ask_price: Option<f64>,
+    #[serde(default)]
+    pub best_ask_size: Option<f64>,
+    #[serde(default)]
+    pub best_bid_price: Option<f64>,
+    #[serde(default)]
+    pub best_bid_size: Option<f64>,
+    pub stream_type: StreamType,
+}
+
+/// 📚 호가 단위 한 칸
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpbitOrderbookUnit {
+    pub ask_price: f64,
+    pub bid_price: f64,
+    pub ask_size: f64,
+    pub bid_size: f64,
+}
+
+/// 📚 호가 (type = "orderbook")
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpbitOrderbook {
+    pub code: String,
+    pub total_ask_size: f64,
+    pub total_bid_size: f64,
+    pub orderbook_units: Vec<UpbitOrderbookUnit>,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub level: Option<f64>, // 호가 모아보기 단위
+    pub stream_type: StreamType,
+}
+
+/// 📊 현재가 (type = "ticker")
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpbitTicker {
+    pub code: String,
+    pub opening_price: f64,
+    pub high_price: f64,
+    pub low_price: f64,
+    pub trade_price: f64,
+    pub prev_closing_price: f64,
+    pub change: Change,
+    pub change_price: f64,
+    pub signed_change_price: f64,
+    pub change_rate: f64,
+    pub signed_change_rate: f64,
+    pub trade_volume: f64,
+    pub acc_trade_volume: f64,
+    pub acc_trade_volume_24h: f64,
+    pub acc_trade_price: f64,
+    pub acc_trade_price_24h: f64,
+    pub trade_date: String, // "yyyyMMdd"
+    pub trade_time: String, // "HHmmss"
+    pub trade_timestamp: u64,
+    pub ask_bid: AskBid,
+    pub acc_ask_volume: f64,
+    pub acc_bid_volume: f64,
+    pub highest_52_week_price: f64,
+    pub highest_52_week_date: String,
+    pub lowest_52_week_price: f64,
+    pub lowest_52_week_date: String,
+    pub market_state: String,
+    #[serde(default)]
+    pub is_trading_suspended: Option<bool>,
+    #[serde(default)]
+    pub delisting_date: Option<String>,
+    pub market_warning: String,
+    pub timestamp: u64,
+    pub stream_type: StreamType,
+}
+
+/// 📨 Upbit 웹소켓 메시지
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpbitMessage {
+    Trade(UpbitTrade),
+    Orderbook(UpbitOrderbook),
+    Ticker(UpbitTicker),
+}
+
+// === 파싱 에러 ===
+
+#[derive(Debug)]
+pub enum UpbitParseError {
+    /// JSON 문법 자체가 잘못됨
+    InvalidJson(serde_json::Error),
+    /// "type" 필드가 없음
+    MissingType,
+    /// 지원하지 않는 type
+    UnknownType(String),
+    /// type은 맞지만 필드가 빠졌거나 형식이 다름
+    Schema {
+        kind: String,
+        source: serde_json::Error,
+    },
+    /// 서버가 보낸 에러 메시지 ({"error": {"name", "message"}})
+    Server { name: String, message: String },
+}
+
+impl fmt::Display for UpbitParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpbitParseError::InvalidJson(e) => write!(f, "잘못된 JSON: {}", e),
+            UpbitParseError::MissingType => write!(f, "type 필드 없음"),
+            UpbitParseError::UnknownType(kind) => write!(f, "알 수 없는 type: {}", kind),
+            UpbitParseError::Schema { kind, source } => {
+                write!(f, "{} 메시지 형식 오류: {}", kind, source)
+            }
+            UpbitParseError::Server { name, message } => {
+                write!(f, "서버 에러 {}: {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UpbitParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UpbitParseError::InvalidJson(e) => Some(e),
+            UpbitParseError::Schema { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// type 구분용으로 먼저 읽어보는 헤더
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    code: Option<String>,
+    error: Option<ServerError>,
+}
+
+#[derive(Deserialize)]
+struct ServerError {
+    name: String,
+    message: String,
+}
+
+/// 🔍 원본 프레임을 타입이 정해진 메시지로 변환
+pub fn parse_message(bin: &[u8]) -> Result<UpbitMessage, UpbitParseError> {
+    let envelope: Envelope = serde_json::from_slice(bin).map_err(UpbitParseError::InvalidJson)?;
+
+    if let Some(error) = envelope.error {
+        return Err(UpbitParseError::Server {
+            name: error.name,
+            message: error.message,
+        });
+    }
+
+    let kind = envelope.kind.ok_or(UpbitParseError::MissingType)?;
+    let schema = |source| UpbitParseError::Schema {
+        kind: kind.clone(),
+        source,
+    };
+
+    match kind.as_str() {
+        "trade" => serde_json::from_slice(bin)
+            .map(UpbitMessage::Trade)
+            .map_err(schema),
+        "orderbook" => serde_json::from_slice(bin)
+            .map(UpbitMessage::Orderbook)
+            .map_err(schema),
+        "ticker" => serde_json::from_slice(bin)
+            .map(UpbitMessage::Ticker)
+            .map_err(schema),
+        _ => Err(UpbitParseError::UnknownType(kind)),
+    }
+}
+
+/// 🏷️ 전체를 파싱하지 않고 마켓 코드만 확인
+pub fn peek_code(bin: &[u8]) -> Option<String> {
+    serde_json::from_slice::<Envelope>(bin).ok()?.code
+}
+
+// === 기존 구조체로 변환 ===
+
+impl From<&UpbitTrade> for TickData {
+    fn from(trade: &UpbitTrade) -> Self {
+        TickData {
+            code: trade.code.clone(),
+            price: trade.trade_price as f32,
+            volume: trade.trade_volume as f32,
+            side: trade.ask_bid.as_str().to_string(),
+            timestamp: trade.timestamp,
+        }
+    }
+}
+
+impl From<&UpbitOrderbook> for OrderBookData {
+    fn from(orderbook: &UpbitOrderbook) -> Self {
+        OrderBookData {
+            code: orderbook.code.clone(),
+            timestamp: orderbook.timestamp,
+            order_units: orderbook
+                .orderbook_units
+                .iter()
+                .map(|unit| OrderBookUnit {
+                    ask_price: unit.ask_price as f32,
+                    ask_size: unit.ask_size as f32,
+                    bid_price: unit.bid_price as f32,
+                    bid_size: unit.bid_size as f32,
+                })
+                .collect(),
+        }
+    }
+}
@@ -1,19 +1,34 @@
+use crate::feed::MarketEvent;
+use crate::upbit::{UpbitMessage, UpbitParseError, parse_message, peek_code};
 use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio::sync::mpsc::Sender;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{Sender, WeakSender};
+use tokio::time::{Duration, sleep};
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async, tungstenite::protocol::Message,
+};
+
+pub const UPBIT_WS_URL: &str = "wss://api.upbit.com/websocket/v1";
 
 // === Tick & OrderBook 구조체 ===
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickData {
+    #[serde(default)]
+    pub code: String, // 마켓 코드 (예: KRW-BTC)
     pub price: f32,
     pub volume: f32,
     pub side: String,   // "ASK" or "BID"
     pub timestamp: u64, // Unix time in milliseconds
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookUnit {
     pub ask_price: f32,
     pub ask_size: f32,
@@ -21,84 +36,244 @@ pub struct OrderBookUnit {
     pub bid_size: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookData {
+    #[serde(default)]
+    pub code: String, // 마켓 코드 (예: KRW-BTC)
     pub timestamp: u64,
     pub order_units: Vec<OrderBookUnit>,
 }
 
+/// 📼 수신한 원본 프레임 (녹화용)
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    pub symbol: String,
+    pub received_at: u64, // 로컬 수신 시각 (Unix ms)
+    pub payload: Vec<u8>,
+}
+
+/// 📼 수신 루프에서 녹화기로 원본 프레임을 넘기는 통로
+/// 녹화기가 밀려 채널이 가득 차면 기다리지 않고 버린 뒤 개수만 셈 (수신은 멈추지 않음)
+/// Sender를 약하게 잡고 있어서, 원래 Sender를 가진 쪽(LiveFeed)이 놓으면 녹화기가 남은 프레임을 쓰고 끝남
+#[derive(Debug, Clone)]
+pub struct RawTap {
+    sender: WeakSender<RawFrame>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RawTap {
+    pub fn new(sender: &Sender<RawFrame>) -> Self {
+        Self {
+            sender: sender.downgrade(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 녹화기로 보내기 (녹화가 끝났으면 무시)
+    pub fn send(&self, frame: RawFrame) {
+        let Some(sender) = self.sender.upgrade() else {
+            return;
+        };
+        if sender.try_send(frame).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 녹화기가 밀려서 버린 프레임 수
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// 🕒 현재 로컬 시각 (Unix ms)
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// === 재연결 설정 & 연결 상태 ===
+
+/// 🔁 웹소켓 접속 주소와 재연결(지수 백오프) 설정
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    pub url: String,
+    pub initial_backoff: Duration, // 첫 재연결 대기 시간
+    pub max_backoff: Duration,     // 대기 시간 상한
+    pub multiplier: f64,           // 재시도마다 곱해지는 배수
+    pub jitter: f64,               // 0.0 ~ 1.0, 대기 시간에 ±비율만큼 무작위 흔들림
+    pub max_retries: Option<u32>,  // 연속 실패 허용 횟수 (None = 무한)
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            url: UPBIT_WS_URL.to_string(),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_retries: None,
+        }
+    }
+}
+
+impl WebSocketConfig {
+    /// ⏱️ attempt번째(1부터) 재연결 전 대기 시간 계산
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exp = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let base = (self.initial_backoff.as_secs_f64() * exp).min(self.max_backoff.as_secs_f64());
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            1.0 + rand::rng().random_range(-jitter..=jitter)
+        } else {
+            1.0
+        };
+
+        Duration::from_secs_f64(base * factor)
+    }
+}
+
+/// 📡 연결 상태 변화 알림
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting { attempt: u32, delay: Duration },
+    GaveUp { retries: u32 },
+}
+
+/// 한 번의 연결 세션이 끝난 이유
+enum SessionEnd {
+    Disconnected,   // 서버 종료 / 에러 → 재연결
+    ReceiverClosed, // 데이터를 받을 쪽이 사라짐 → 핸들러 종료
+}
+
+/// 🌐 여러 마켓을 한 연결로 구독하고, 이벤트는 code 필드로 구분해서 전달
 pub async fn upbit_websocket_handler(
-    coin_code: String,
+    coin_codes: Vec<String>,
     tick_sender: Sender<TickData>,
     order_sender: Sender<OrderBookData>,
+    config: WebSocketConfig,
+    state_sender: Sender<ConnectionState>,
+    raw_tap: Option<RawTap>,
 ) {
-    println!("first");
-    let url = "wss://api.upbit.com/websocket/v1";
-    let (ws_stream, _) = connect_async(url).await.expect("[WebSocket] 연결 실패");
+    let mut attempt: u32 = 0;
+
+    loop {
+        match connect_async(config.url.as_str()).await {
+            Ok((ws_stream, _)) => {
+                println!("[WebSocket] 연결 성공: {}", coin_codes.join(","));
+                attempt = 0;
+                let _ = state_sender.send(ConnectionState::Connected).await;
+
+                let session = run_session(
+                    ws_stream,
+                    &coin_codes,
+                    &tick_sender,
+                    &order_sender,
+                    raw_tap.as_ref(),
+                );
+                match session.await {
+                    SessionEnd::ReceiverClosed => return,
+                    SessionEnd::Disconnected => {
+                        println!("[WebSocket] 연결 끊김: {}", coin_codes.join(","))
+                    }
+                }
+            }
+            Err(e) => eprintln!("[WebSocket] 연결 실패: {}", e),
+        }
+
+        if config.max_retries.is_some_and(|max| attempt >= max) {
+            eprintln!("[WebSocket] 재연결 포기 ({}회 시도)", attempt);
+            let _ = state_sender
+                .send(ConnectionState::GaveUp { retries: attempt })
+                .await;
+            return;
+        }
+
+        attempt += 1;
+        let delay = config.backoff_delay(attempt);
+        let _ = state_sender
+            .send(ConnectionState::Reconnecting { attempt, delay })
+            .await;
+        sleep(delay).await;
+    }
+}
 
-    println!("last");
-    println!("[WebSocket] 연결 성공: {}", coin_code);
+/// 🔌 연결 하나에 대해 구독 메시지를 보내고 끊길 때까지 수신
+async fn run_session(
+    ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    coin_codes: &[String],
+    tick_sender: &Sender<TickData>,
+    order_sender: &Sender<OrderBookData>,
+    raw_tap: Option<&RawTap>,
+) -> SessionEnd {
     let (mut write, mut read) = ws_stream.split();
 
-    // 📩 구독 메시지 전송
+    // 📩 구독 메시지 전송 (재연결 때마다 다시 보냄)
     let subscribe_msg = json!([
         { "ticket": "test" },
-        { "type": "trade", "codes": [coin_code] },
-        { "type": "orderbook", "codes": [coin_code] }
+        { "type": "trade", "codes": coin_codes },
+        { "type": "orderbook", "codes": coin_codes }
     ]);
     let msg = Message::Text(subscribe_msg.to_string().into());
-    write.send(msg).await.unwrap();
+    if write.send(msg).await.is_err() {
+        return SessionEnd::Disconnected;
+    }
 
     // 📥 메시지 수신 루프
-    while let Some(Ok(msg)) = read.next().await {
-        if let Message::Binary(bin) = msg {
-            if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bin) {
-                if let Some(data_type) = value.get("type").and_then(|v| v.as_str()) {
-                    match data_type {
-                        "trade" => {
-                            println!("{:?}", value);
-                            if let (Some(price), Some(volume), Some(side), Some(timestamp)) = (
-                                value.get("trade_price"),
-                                value.get("trade_volume"),
-                                value.get("ask_bid"),
-                                value.get("timestamp"),
-                            ) {
-                                let tick = TickData {
-                                    price: price.as_f64().unwrap() as f32,
-                                    volume: volume.as_f64().unwrap() as f32,
-                                    side: side.as_str().unwrap().to_string(),
-                                    timestamp: timestamp.as_u64().unwrap(),
-                                };
-                                let _ = tick_sender.send(tick).await;
-                            }
-                        }
-                        "orderbook" => {
-                            if let (Some(orderbook_units), Some(timestamp)) =
-                                (value.get("orderbook_units"), value.get("timestamp"))
-                            {
-                                let units = orderbook_units
-                                    .as_array()
-                                    .unwrap()
-                                    .iter()
-                                    .map(|unit| OrderBookUnit {
-                                        ask_price: unit["ask_price"].as_f64().unwrap() as f32,
-                                        ask_size: unit["ask_size"].as_f64().unwrap() as f32,
-                                        bid_price: unit["bid_price"].as_f64().unwrap() as f32,
-                                        bid_size: unit["bid_size"].as_f64().unwrap() as f32,
-                                    })
-                                    .collect();
-
-                                let order_data = OrderBookData {
-                                    timestamp: timestamp.as_u64().unwrap(),
-                                    order_units: units,
-                                };
-                                let _ = order_sender.send(order_data).await;
-                            }
-                        }
-                        _ => {}
-                    }
+    while let Some(result) = read.next().await {
+        match result {
+            Ok(Message::Binary(bin)) => {
+                // 녹화기가 밀리거나 멈춰도 수신은 계속 (try_send, 못 보낸 프레임은 개수만 셈)
+                if let Some(raw_tap) = raw_tap {
+                    raw_tap.send(RawFrame {
+                        symbol: peek_code(&bin).unwrap_or_else(|| "UNKNOWN".to_string()),
+                        received_at: unix_millis(),
+                        payload: bin.to_vec(),
+                    });
                 }
+                if !dispatch_message(&bin, tick_sender, order_sender).await {
+                    return SessionEnd::ReceiverClosed;
+                }
+            }
+            Ok(Message::Close(_)) => return SessionEnd::Disconnected,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("[WebSocket] 수신 에러: {}", e);
+                return SessionEnd::Disconnected;
             }
         }
     }
+
+    SessionEnd::Disconnected
+}
+
+/// 📨 메시지를 파싱해서 채널로 전달, 받는 쪽이 닫혔으면 false
+async fn dispatch_message(
+    bin: &[u8],
+    tick_sender: &Sender<TickData>,
+    order_sender: &Sender<OrderBookData>,
+) -> bool {
+    match parse_frame(bin) {
+        Ok(Some(MarketEvent::Tick(tick))) => tick_sender.send(tick).await.is_ok(),
+        Ok(Some(MarketEvent::OrderBook(order))) => order_sender.send(order).await.is_ok(),
+        Ok(None) => true,
+        Err(e) => {
+            // 형식이 바뀐 메시지 하나 때문에 연결을 끊지 않음
+            eprintln!("[WebSocket] 메시지 파싱 실패: {}", e);
+            true
+        }
+    }
+}
+
+/// 🔍 Upbit 원본 프레임을 체결/호가 이벤트로 변환 (현재가 등 그 외 메시지는 Ok(None))
+pub fn parse_frame(bin: &[u8]) -> Result<Option<MarketEvent>, UpbitParseError> {
+    Ok(match parse_message(bin)? {
+        UpbitMessage::Trade(trade) => Some(MarketEvent::Tick((&trade).into())),
+        UpbitMessage::Orderbook(orderbook) => Some(MarketEvent::OrderBook((&orderbook).into())),
+        UpbitMessage::Ticker(_) => None,
+    })
 }
@@ -0,0 +1,88 @@
+use burn_basics::backtest::{BacktestConfig, BacktestResult, run_backtest};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::feed::{
+    MarketEvent, MarketFeed, RecordedFeed, SyntheticConfig, SyntheticFeed, save_events,
+};
+use burn_basics::types::B;
+use std::fs;
+
+/// 두 마켓의 합성 이벤트를 파일로 저장했다가 다시 읽은 녹화
+async fn recorded_events(name: &str) -> Vec<MarketEvent> {
+    let mut events = Vec::new();
+    for (seed, code) in [(1, "KRW-A"), (2, "KRW-B")] {
+        let mut feed = SyntheticFeed::new(SyntheticConfig {
+            seed,
+            code: code.to_string(),
+            max_events: Some(600),
+            ..SyntheticConfig::default()
+        });
+        while let Some(event) = feed.next_event().await {
+            events.push(event);
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!(
+        "burn_basics_backtest_{}_{}",
+        name,
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("events.jsonl").to_string_lossy().into_owned();
+    save_events(&events, &path).unwrap();
+    let events = RecordedFeed::open(&path).unwrap().into_events();
+    fs::remove_dir_all(&dir).unwrap();
+    events
+}
+
+fn actions(result: &BacktestResult) -> Vec<usize> {
+    result.steps.iter().map(|s| s.action).collect()
+}
+
+#[tokio::test]
+async fn same_events_and_config_replay_identically() {
+    let events = recorded_events("same").await;
+    let model = DqnModel::<B>::new(&Default::default());
+    let config = BacktestConfig {
+        epsilon: 0.3, // 탐험도 시드로 재현되어야 함
+        ..BacktestConfig::default()
+    };
+
+    let first = run_backtest(&events, &model, &config);
+    let second = run_backtest(&events, &model, &config);
+    assert!(first.steps.len() > 100);
+    assert_eq!(first.portfolios.len(), 2);
+
+    assert_eq!(first.steps, second.steps);
+    assert_eq!(first.samples, second.samples);
+    assert_eq!(first.portfolios, second.portfolios);
+    assert_eq!(
+        (first.total_reward, first.trades, first.events),
+        (second.total_reward, second.trades, second.events)
+    );
+}
+
+#[tokio::test]
+async fn different_seed_explores_differently() {
+    let events = recorded_events("seed").await;
+    let model = DqnModel::<B>::new(&Default::default());
+    let config = |seed| BacktestConfig {
+        seed,
+        epsilon: 0.3,
+        ..BacktestConfig::default()
+    };
+
+    let first = run_backtest(&events, &model, &config(1));
+    let other = run_backtest(&events, &model, &config(2));
+    assert_ne!(actions(&first), actions(&other));
+    assert_ne!(first.portfolios, other.portfolios);
+
+    // 탐험하지 않으면 시드와 무관
+    let greedy = |seed| BacktestConfig {
+        epsilon: 0.0,
+        ..config(seed)
+    };
+    assert_eq!(
+        run_backtest(&events, &model, &greedy(1)).steps,
+        run_backtest(&events, &model, &greedy(2)).steps
+    );
+}
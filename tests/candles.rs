@@ -0,0 +1,194 @@
+mod common;
+
+use burn_basics::analyzer::{MarketStorage, MultiMarketStorage};
+use burn_basics::backtest::{BacktestConfig, run_backtest};
+use burn_basics::candles::{BarSpec, Candle, CandleBuilder, CandleSet, candles_from_events};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::feed::{MarketEvent, RecordedFeed, save_events};
+use burn_basics::incremental::MultiMarketAnalyzer;
+use burn_basics::types::B;
+use burn_basics::websocket::TickData;
+use common::trade;
+use std::fs;
+
+fn run(spec: BarSpec, ticks: &[TickData]) -> Vec<Candle> {
+    let mut builder = CandleBuilder::new(spec);
+    ticks.iter().filter_map(|t| builder.push(t)).collect()
+}
+
+#[test]
+fn time_bars_align_to_epoch_and_skip_empty_intervals() {
+    let ticks = [
+        trade("KRW-A", 10_100, 100.0, 1.0, "BID"),
+        trade("KRW-A", 10_900, 103.0, 2.0, "BID"),
+        trade("KRW-A", 10_500, 99.0, 1.0, "BID"), // 늦게 온 틱은 현재 봉에 합침
+        trade("KRW-A", 11_000, 101.0, 1.0, "BID"),
+        trade("KRW-A", 13_500, 104.0, 1.0, "BID"), // 12초 구간은 비어 있음
+        trade("KRW-A", 14_000, 105.0, 1.0, "BID"),
+    ];
+    let candles = run(BarSpec::seconds(1), &ticks);
+    assert_eq!(candles.len(), 3);
+
+    let first = &candles[0];
+    assert_eq!((first.open_time, first.close_time), (10_000, 10_900));
+    assert_eq!(
+        (first.open, first.high, first.low, first.close),
+        (100.0, 103.0, 99.0, 99.0)
+    );
+    assert_eq!((first.volume, first.trades), (4.0, 3));
+    assert_eq!(first.value, 100.0 + 206.0 + 99.0);
+    assert_eq!(first.vwap(), 405.0 / 4.0);
+
+    assert_eq!(
+        candles.iter().map(|c| c.open_time).collect::<Vec<_>>(),
+        [10_000, 11_000, 13_000]
+    );
+}
+
+#[test]
+fn tick_volume_and_value_bars_close_on_the_crossing_tick() {
+    let ticks: Vec<TickData> = (0..10)
+        .map(|i| {
+            trade(
+                "KRW-A",
+                i * 10,
+                100.0 + i as f32,
+                1.0 + (i % 3) as f32,
+                "BID",
+            )
+        })
+        .collect();
+
+    let by_ticks = run(BarSpec::Ticks { count: 4 }, &ticks);
+    assert_eq!(by_ticks.len(), 2);
+    assert!(by_ticks.iter().all(|c| c.trades == 4));
+    assert_eq!((by_ticks[1].open, by_ticks[1].close), (104.0, 107.0));
+
+    // 체결량: 1, 2, 3, 1, 2, 3, ... → 합이 5 이상이 되는 세 번째 틱에서 완성
+    let by_volume = run(BarSpec::Volume { amount: 5.0 }, &ticks);
+    assert_eq!(
+        by_volume.iter().map(|c| c.trades).collect::<Vec<_>>(),
+        [3, 3, 3]
+    );
+    assert!(by_volume.iter().all(|c| c.volume >= 5.0));
+
+    // 완성된 봉 + 만들어지는 중인 봉에 모든 틱이 한 번씩 들어감
+    let mut builder = CandleBuilder::new(BarSpec::Value { amount: 500.0 });
+    let by_value: Vec<Candle> = ticks.iter().filter_map(|t| builder.push(t)).collect();
+    assert!(!by_value.is_empty());
+    assert!(by_value.iter().all(|c| c.value >= 500.0));
+    let pending = builder.flush().map_or(0, |c| c.trades);
+    assert_eq!(
+        by_value.iter().map(|c| c.trades).sum::<usize>() + pending,
+        ticks.len()
+    );
+}
+
+#[test]
+fn storage_keeps_a_bounded_candle_history() {
+    let specs = [BarSpec::seconds(1), BarSpec::Ticks { count: 2 }];
+    let mut storage = MarketStorage::with_candles(100, CandleSet::new(&specs, 3));
+    let mut emitted = 0;
+    for i in 0..20 {
+        emitted += storage
+            .push_tick(trade("KRW-A", i * 500, 100.0, 1.0, "BID"))
+            .len();
+    }
+    assert_eq!(emitted, 9 + 10);
+
+    let seconds = storage.candles.get(BarSpec::seconds(1)).unwrap();
+    assert_eq!(seconds.history.len(), 3);
+    assert_eq!(seconds.last().unwrap().open_time, 8_000);
+    assert_eq!(seconds.builder.current().unwrap().open_time, 9_000);
+
+    let mut markets = MultiMarketStorage::with_candles(100, &specs, 5);
+    for i in 0..20 {
+        markets.push_tick(trade("KRW-A", i * 500, 100.0, 1.0, "BID"));
+        markets.push_tick(trade("KRW-B", i * 250, 50.0, 1.0, "BID"));
+    }
+    let a = markets
+        .get("KRW-A")
+        .unwrap()
+        .candles
+        .get(BarSpec::seconds(1));
+    let b = markets
+        .get("KRW-B")
+        .unwrap()
+        .candles
+        .get(BarSpec::seconds(1));
+    assert_eq!(a.unwrap().last().unwrap().code, "KRW-A");
+    assert_eq!(b.unwrap().last().unwrap().open_time, 3_000);
+}
+
+#[test]
+fn candles_rebuilt_from_recorded_events_match_live_ones() {
+    let specs = [
+        BarSpec::seconds(1),
+        BarSpec::minutes(1),
+        BarSpec::Ticks { count: 7 },
+        BarSpec::Volume { amount: 10.0 },
+        BarSpec::Value { amount: 2_000.0 },
+    ];
+    let events: Vec<MarketEvent> = (0..2_000u64)
+        .map(|i| {
+            let code = if i % 3 == 0 { "KRW-A" } else { "KRW-B" };
+            let price = 100.0 + ((i * 37) % 23) as f32;
+            let volume = 0.1 + ((i * 13) % 7) as f32 * 0.5;
+            MarketEvent::Tick(trade(
+                code,
+                1_700_000_000_000 + i * 137,
+                price,
+                volume,
+                "BID",
+            ))
+        })
+        .collect();
+
+    // 실시간: 마켓별 저장소 / 증분 분석기에 흘려보내면서 완성된 봉을 모음
+    let mut live = Vec::new();
+    let mut incremental = Vec::new();
+    let mut markets = MultiMarketStorage::with_candles(200, &specs, 0);
+    let mut analyzers = MultiMarketAnalyzer::new(200, &[]).with_candles(&specs, 0);
+    for event in &events {
+        if let MarketEvent::Tick(t) = event {
+            live.extend(markets.push_tick(t.clone()));
+            incremental.extend_from_slice(analyzers.push_tick(t).completed_candles());
+        }
+    }
+    assert_eq!(incremental, live);
+
+    let path =
+        std::env::temp_dir().join(format!("burn_basics_candles_{}.jsonl", std::process::id()));
+    let path = path.to_string_lossy().into_owned();
+    save_events(&events, &path).unwrap();
+    let replayed = RecordedFeed::open(&path).unwrap().into_events();
+    fs::remove_file(&path).unwrap();
+
+    let rebuilt = candles_from_events(&replayed, &specs);
+    assert!(!rebuilt.is_empty());
+    assert_eq!(rebuilt, live);
+}
+
+#[test]
+fn backtest_reports_candles_completed_while_replaying() {
+    let specs = [BarSpec::seconds(1), BarSpec::Ticks { count: 3 }];
+    let mut events = Vec::new();
+    for i in 0..40u64 {
+        let code = if i % 2 == 0 { "KRW-A" } else { "KRW-B" };
+        events.push(MarketEvent::Tick(trade(
+            code,
+            i * 150,
+            100.0 + (i % 5) as f32,
+            1.0,
+            "BID",
+        )));
+    }
+    let config = BacktestConfig {
+        candle_specs: specs.to_vec(),
+        ..BacktestConfig::default()
+    };
+    let result = run_backtest(&events, &DqnModel::<B>::new(&Default::default()), &config);
+    assert_eq!(result.steps.len(), 0); // 오더북이 없어 거래는 없어도 봉은 만들어짐
+    assert!(result.candles.iter().any(|e| e.candle.code == "KRW-B"));
+    assert_eq!(result.candles, candles_from_events(&events, &specs));
+}
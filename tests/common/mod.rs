@@ -0,0 +1,61 @@
+// 🧪 통합 테스트 공용 픽스처 (테스트 파일에서 `mod common;`으로 가져다 씀)
+// 파일마다 쓰는 헬퍼가 달라서 안 쓰이는 것이 있어도 경고하지 않음
+#![allow(dead_code)]
+
+use burn::backend::NdArray;
+use burn_basics::costs::CostModel;
+use burn_basics::env::{Env, EnvConfig, PositionSize};
+use burn_basics::websocket::{OrderBookData, OrderBookUnit, TickData};
+
+pub type TB = NdArray<f32>;
+
+/// 픽스처 기본 마켓 코드
+pub const CODE: &str = "KRW-TEST";
+
+/// CODE 마켓의 매수 체결 (체결량 1, 시각 0)
+pub fn tick(price: f32) -> TickData {
+    trade(CODE, 0, price, 1.0, "BID")
+}
+
+/// 모든 값을 지정한 체결 (side는 "BID" / "ASK")
+pub fn trade(code: &str, timestamp: u64, price: f32, volume: f32, side: &str) -> TickData {
+    TickData {
+        code: code.to_string(),
+        price,
+        volume,
+        side: side.to_string(),
+        timestamp,
+    }
+}
+
+/// (매도호가, 매도잔량, 매수호가, 매수잔량) 단계들로 만든 CODE 마켓 오더북
+pub fn book(timestamp: u64, levels: &[(f32, f32, f32, f32)]) -> OrderBookData {
+    OrderBookData {
+        code: CODE.to_string(),
+        timestamp,
+        order_units: levels
+            .iter()
+            .map(
+                |&(ask_price, ask_size, bid_price, bid_size)| OrderBookUnit {
+                    ask_price,
+                    ask_size,
+                    bid_price,
+                    bid_size,
+                },
+            )
+            .collect(),
+    }
+}
+
+/// 수수료 없이 1개씩 사고파는 Env 설정 (파일마다 필요한 값만 덮어씀)
+pub fn unit_config() -> EnvConfig {
+    EnvConfig {
+        cost_model: CostModel::free(),
+        position_size: PositionSize::Quantity(1.0),
+        ..EnvConfig::default()
+    }
+}
+
+pub fn env(config: EnvConfig) -> Env<TB> {
+    Env::with_config(Default::default(), config)
+}
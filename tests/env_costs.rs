@@ -0,0 +1,144 @@
+mod common;
+
+use burn_basics::costs::{CostModel, OrderType, Side};
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::websocket::OrderBookData;
+use common::{TB, tick, unit_config};
+
+/// ask: 101 x1, 102 x2, 103 x5 / bid: 99 x1, 98 x2, 97 x5
+fn book() -> OrderBookData {
+    common::book(
+        0,
+        &[
+            (101.0, 1.0, 99.0, 1.0),
+            (102.0, 2.0, 98.0, 2.0),
+            (103.0, 5.0, 97.0, 5.0),
+        ],
+    )
+}
+
+fn env(cost_model: CostModel) -> Env<TB> {
+    common::env(EnvConfig {
+        cost_model,
+        ..unit_config()
+    })
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+}
+
+#[test]
+fn free_model_keeps_plain_return() {
+    let mut env = env(CostModel::free());
+    env.update_orderbook(book());
+    env.step(0, tick(100.0));
+    let (_, reward) = env.step(1, tick(110.0));
+    assert_close(reward, 0.1);
+}
+
+#[test]
+fn fees_are_charged_on_both_legs() {
+    let mut env = env(CostModel {
+        cross_spread: false,
+        ..CostModel::default()
+    });
+    env.step(0, tick(100.0));
+    let buy = env.last_fill.clone().unwrap();
+    assert_close(buy.fee, 0.05);
+    assert_close(env.portfolio.avg_entry_price as f32, 100.05);
+
+    let (_, reward) = env.step(1, tick(110.0));
+    let sell = env.last_fill.clone().unwrap();
+    assert_close(sell.fee, 0.055);
+    assert_close(reward, (109.945 - 100.05) / 100.05);
+}
+
+#[test]
+fn market_orders_cross_the_spread() {
+    let mut env = env(CostModel {
+        maker_fee: 0.0,
+        taker_fee: 0.0,
+        walk_depth: false,
+        ..CostModel::default()
+    });
+    env.update_orderbook(book());
+
+    env.step(0, tick(100.0));
+    let buy = env.last_fill.clone().unwrap();
+    assert_eq!(buy.side, Side::Buy);
+    assert_close(buy.price, 101.0);
+    assert_close(buy.spread_cost, 1.0);
+    assert_close(buy.slippage, 0.0);
+
+    let (_, reward) = env.step(1, tick(100.0));
+    let sell = env.last_fill.clone().unwrap();
+    assert_close(sell.price, 99.0);
+    assert_close(sell.spread_cost, 1.0);
+    assert_close(reward, (99.0 - 101.0) / 101.0);
+}
+
+#[test]
+fn large_orders_walk_the_book() {
+    let model = CostModel {
+        maker_fee: 0.0,
+        taker_fee: 0.0,
+        ..CostModel::default()
+    };
+
+    // 1 x 101 + 1.5 x 102 = 254 → 평균 101.6
+    let fill = model.fill(Side::Buy, 2.5, 100.0, Some(&book()));
+    assert_close(fill.price, 101.6);
+    assert_close(fill.spread_cost, 2.5);
+    assert_close(fill.slippage, 1.5);
+
+    // 1 x 99 + 1.5 x 98 = 246 → 평균 98.4
+    let fill = model.fill(Side::Sell, 2.5, 100.0, Some(&book()));
+    assert_close(fill.price, 98.4);
+    assert_close(fill.slippage, 1.5);
+}
+
+#[test]
+fn orders_beyond_visible_depth_fill_at_last_level() {
+    let model = CostModel {
+        maker_fee: 0.0,
+        taker_fee: 0.0,
+        ..CostModel::default()
+    };
+    // 101 + 2 x 102 + 5 x 103 + 2 x 103 = 1026
+    let fill = model.fill(Side::Buy, 10.0, 100.0, Some(&book()));
+    assert_close(fill.price, 102.6);
+}
+
+#[test]
+fn limit_orders_rest_on_own_side_with_maker_fee() {
+    let model = CostModel {
+        maker_fee: 0.0002,
+        taker_fee: 0.001,
+        order_type: OrderType::Limit,
+        ..CostModel::default()
+    };
+    let fill = model.fill(Side::Buy, 1.0, 100.0, Some(&book()));
+    assert_close(fill.price, 99.0);
+    assert_close(fill.fee, 99.0 * 0.0002);
+    assert_close(fill.spread_cost, 0.0);
+    assert_close(fill.slippage, 0.0);
+}
+
+#[test]
+fn missing_orderbook_falls_back_to_tick_price() {
+    let model = CostModel::default();
+    let fill = model.fill(Side::Buy, 1.0, 100.0, None);
+    assert_close(fill.price, 100.0);
+    assert_close(fill.spread_cost, 0.0);
+    assert_close(fill.total_cost(), 0.05);
+}
+
+#[test]
+fn round_trip_at_same_price_loses_money_with_default_costs() {
+    let mut env = env(CostModel::default());
+    env.update_orderbook(book());
+    env.step(0, tick(100.0));
+    let (_, reward) = env.step(1, tick(100.0));
+    assert!(reward < -0.019, "reward {reward}");
+}
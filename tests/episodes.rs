@@ -0,0 +1,111 @@
+mod common;
+
+use burn_basics::env::{DoneReason, Env, EnvConfig};
+use burn_basics::replay_loader::load_replay_csv;
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::schema::FeatureSchema;
+use burn_basics::train::{load_samples_from_csv, td_target};
+use common::{TB, tick, unit_config};
+use std::fs;
+
+fn env(max_steps: Option<usize>, stop_out: Option<f64>) -> Env<TB> {
+    common::env(EnvConfig {
+        initial_cash: 100.0,
+        max_steps,
+        stop_out,
+        ..unit_config()
+    })
+}
+
+fn csv_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("burn_basics_episodes_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+#[test]
+fn episode_ends_after_max_steps_and_reset_starts_over() {
+    let mut env = env(Some(2), None);
+    env.step(0, tick(100.0));
+    assert!(!env.is_done());
+    env.step(2, tick(101.0));
+    assert_eq!(env.done, Some(DoneReason::MaxSteps));
+
+    env.reset();
+    assert!(!env.is_done());
+    assert!(!env.is_holding());
+    assert_eq!(env.steps, 0);
+    assert_eq!(env.portfolio.cash, 100.0);
+}
+
+#[test]
+fn equity_stop_out_terminates_episode() {
+    let mut env = env(None, Some(0.5));
+    env.step(0, tick(100.0)); // 현금 0 + 코인 1개
+    env.step(2, tick(60.0));
+    assert!(!env.is_done());
+    env.step(2, tick(50.0));
+    assert_eq!(env.done, Some(DoneReason::StopOut));
+}
+
+#[test]
+fn end_of_data_does_not_override_earlier_reason() {
+    let mut env = env(Some(1), None);
+    env.step(2, tick(100.0));
+    env.end_of_data();
+    assert_eq!(env.done, Some(DoneReason::MaxSteps));
+
+    env.reset();
+    env.end_of_data();
+    assert_eq!(env.done, Some(DoneReason::EndOfData));
+}
+
+#[test]
+fn td_target_does_not_bootstrap_terminal_transitions() {
+    assert_eq!(td_target(1.0, 10.0, false, 0.9), 10.0);
+    assert_eq!(td_target(1.0, 10.0, true, 0.9), 1.0);
+}
+
+#[test]
+fn done_flag_round_trips_through_csv() {
+    let sample = |v: f32, done| ReplaySample {
+        state: vec![v; 12],
+        action: 1,
+        reward: v,
+        next_state: vec![v + 1.0; 12],
+        done,
+    };
+    let path = csv_path("replay.csv");
+    save_replay_csv(
+        &[sample(1.0, false), sample(2.0, true)],
+        &FeatureSchema::default(),
+        &path,
+    );
+
+    let loaded = load_replay_csv(&path).unwrap();
+    assert_eq!(
+        loaded.iter().map(|s| s.done).collect::<Vec<_>>(),
+        [false, true]
+    );
+    let loaded = load_samples_from_csv(&path).unwrap();
+    assert_eq!(
+        loaded.iter().map(|s| s.done).collect::<Vec<_>>(),
+        [false, true]
+    );
+}
+
+#[test]
+fn csv_without_done_column_loads_as_non_terminal() {
+    let mut header = vec!["action".to_string(), "reward".to_string()];
+    header.extend((0..12).map(|i| format!("state_{i}")));
+    header.extend((0..12).map(|i| format!("next_{i}")));
+    let row = vec!["2"; 26].join(",");
+    let path = csv_path("legacy.csv");
+    fs::write(&path, format!("{}\n{}\n", header.join(","), row)).unwrap();
+
+    let loaded = load_replay_csv(&path).unwrap();
+    assert!(!loaded[0].done);
+    let loaded = load_samples_from_csv(&path).unwrap();
+    assert!(!loaded[0].done);
+}
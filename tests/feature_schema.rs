@@ -0,0 +1,146 @@
+use burn::tensor::backend::Backend;
+use burn_basics::analyzer::MarketFeatures;
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::model_saver::{load_model, save_model, schema_path};
+use burn_basics::replay_file::csv_to_replay_file;
+use burn_basics::replay_file::load_replay_file;
+use burn_basics::replay_loader::{load_replay_csv, read_replay_csv_schema};
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::schema::{FeatureSchema, SchemaError};
+use burn_basics::types::B;
+use burn_basics::websocket::TickData;
+use std::fs;
+
+fn temp_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("burn_basics_schema_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+fn features() -> MarketFeatures {
+    MarketFeatures {
+        avg_price: 1.0,
+        price_delta: 2.0,
+        volume_sum: 3.0,
+        volatility: 4.0,
+        imbalance: 5.0,
+        spread: 6.0,
+        ask1_price: 7.0,
+        bid1_price: 8.0,
+        ask_depth_ratio: 9.0,
+        bid_depth_ratio: 10.0,
+        tick_speed: 11.0,
+        last_tick_size: 12.0,
+        ..MarketFeatures::default()
+    }
+}
+
+fn small_schema() -> FeatureSchema {
+    FeatureSchema::new(["spread", "avg_price", "imbalance"]).unwrap()
+}
+
+#[test]
+fn default_schema_keeps_analyzer_order() {
+    let schema = FeatureSchema::default();
+    assert_eq!(schema.len(), 12);
+    assert_eq!(
+        schema.extract(&features()),
+        (1..=12).map(|v| v as f32).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn invalid_schemas_are_rejected() {
+    assert_eq!(
+        FeatureSchema::new(Vec::<String>::new()),
+        Err(SchemaError::Empty)
+    );
+    assert_eq!(
+        FeatureSchema::new(["spread", "rsi"]),
+        Err(SchemaError::Unknown("rsi".to_string()))
+    );
+    assert_eq!(
+        FeatureSchema::new(["spread", "spread"]),
+        Err(SchemaError::Duplicate("spread".to_string()))
+    );
+}
+
+#[test]
+fn schema_drives_observation_width_and_model_input() {
+    let device = <B as Backend>::Device::default();
+    let config = EnvConfig {
+        schema: small_schema(),
+        ..EnvConfig::default()
+    };
+    let mut env = Env::<B>::with_config(device, config);
+    env.update(features());
+    assert_eq!(env.observation(), vec![6.0, 1.0, 5.0]);
+
+    let tick = TickData {
+        code: "KRW-TEST".to_string(),
+        price: 100.0,
+        volume: 2.0,
+        side: "BID".to_string(),
+        timestamp: 0,
+    };
+    let (next, _) = env.step(2, tick);
+    assert_eq!(next, vec![6.0, 100.0, 5.0]); // avg_price만 체결가로 갱신
+
+    let model = DqnModel::<B>::with_input(&device, env.config.schema.len());
+    assert_eq!(model.forward(env.observe()).dims(), [1, 3]);
+}
+
+#[test]
+fn replay_columns_are_named_after_the_schema() {
+    let schema = small_schema();
+    let sample = ReplaySample {
+        state: vec![1.0, 2.0, 3.0],
+        action: 0,
+        reward: 0.5,
+        next_state: vec![4.0, 5.0, 6.0],
+        done: false,
+    };
+    let csv = temp_path("named.csv");
+    save_replay_csv(std::slice::from_ref(&sample), &schema, &csv);
+
+    let header = fs::read_to_string(&csv).unwrap();
+    assert!(header.starts_with(
+        "action,reward,state_spread,state_avg_price,state_imbalance,\
+         next_spread,next_avg_price,next_imbalance,done"
+    ));
+    assert_eq!(read_replay_csv_schema(&csv).unwrap(), schema);
+    assert_eq!(load_replay_csv(&csv).unwrap(), vec![sample]);
+
+    let bin = temp_path("named.bin");
+    csv_to_replay_file(&csv, &bin, 3).unwrap();
+    assert_eq!(load_replay_file(&bin).unwrap().0.schema(), schema);
+}
+
+#[test]
+fn model_schema_mismatch_is_detected_at_load() {
+    let device = <B as Backend>::Device::default();
+    let schema = small_schema();
+    let path = temp_path("model");
+    save_model(
+        &DqnModel::<B>::with_input(&device, schema.len()),
+        &schema,
+        &path,
+    );
+
+    assert!(load_model(&path, &schema, &device).is_ok());
+    let err = load_model(&path, &FeatureSchema::default(), &device).unwrap_err();
+    assert!(err.to_string().contains("불일치"), "{err}");
+
+    // 스키마 파일이 없는 예전 모델은 기본 스키마로 간주
+    let legacy = temp_path("legacy_model");
+    save_model(
+        &DqnModel::<B>::new(&device),
+        &FeatureSchema::default(),
+        &legacy,
+    );
+    fs::remove_file(schema_path(&legacy)).unwrap();
+    assert!(load_model(&legacy, &FeatureSchema::default(), &device).is_ok());
+    assert!(load_model(&legacy, &schema, &device).is_err());
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use burn_basics::analyzer::{
+    MarketFeatures, MarketStorage, TimeWindow, analyze, analyze_horizons, analyze_window,
+};
+use burn_basics::incremental::IncrementalAnalyzer;
+use burn_basics::schema::{MARKET_FEATURES, MICROSTRUCTURE_FEATURES};
+use burn_basics::websocket::TickData;
+use common::{CODE, book, trade};
+use proptest::prelude::*;
+
+/// (시간 간격, 가격, 체결량, 매수 체결 여부) → 타임스탬프가 증가하는 틱
+fn ticks(steps: &[(u64, f32, f32, bool)]) -> Vec<TickData> {
+    let mut timestamp = 1_700_000_000_000;
+    steps
+        .iter()
+        .map(|&(gap, price, volume, buy)| {
+            timestamp += gap;
+            trade(
+                CODE,
+                timestamp,
+                price,
+                volume,
+                if buy { "BID" } else { "ASK" },
+            )
+        })
+        .collect()
+}
+
+/// 원래 analyze는 f32로 훑어서 더하므로 가격 피처는 가격 크기에 비례하는 오차를 허용
+fn assert_equivalent(scan: &MarketFeatures, incremental: &MarketFeatures, scale: f32) {
+    const PRICES: [&str; 7] = [
+        "avg_price",
+        "price_delta",
+        "volatility",
+        "ask1_price",
+        "bid1_price",
+        "microprice",
+        "vwap",
+    ];
+    for name in MARKET_FEATURES.iter().chain(&MICROSTRUCTURE_FEATURES) {
+        let (a, b) = (scan.value(name).unwrap(), incremental.value(name).unwrap());
+        let scale = if PRICES.contains(name) { scale } else { 1.0 };
+        let tolerance = 1e-4 * scale.max(a.abs()).max(1.0);
+        assert!(
+            (a - b).abs() <= tolerance,
+            "{name}: scan {a} vs incremental {b}"
+        );
+    }
+}
+
+fn step() -> impl Strategy<Value = (u64, f32, f32, bool)> {
+    (0u64..2_000, 1.0f32..1_000.0, 0.0f32..10.0, any::<bool>())
+}
+
+fn price_level() -> impl Strategy<Value = f32> {
+    prop_oneof![
+        Just(1.0f32),
+        Just(300.0),
+        Just(50_000.0),
+        Just(100_000_000.0)
+    ]
+}
+
+proptest! {
+    #[test]
+    fn matches_analyze_on_every_push(
+        steps in prop::collection::vec(step(), 1..300),
+        capacity in 2usize..64,
+        level in price_level(),
+    ) {
+        let steps: Vec<_> = steps.iter().map(|&(g, p, v, b)| (g, level + p, v, b)).collect();
+        let levels = [(level + 2.0, 1.0, level - 2.0, 3.0), (level + 3.0, 4.0, level - 3.0, 0.5)];
+        let mut storage = MarketStorage::new(capacity);
+        let mut incremental = IncrementalAnalyzer::new(capacity);
+        storage.push_orderbook(book(0, &levels));
+        incremental.push_orderbook(book(0, &levels));
+
+        for (i, tick) in ticks(&steps).into_iter().enumerate() {
+            if i % 3 == 0 {
+                // 체결가를 따라 움직이는 호가 (OFI가 0이 아니도록)
+                let (p, v) = (tick.price, tick.volume);
+                let moved = book(tick.timestamp, &[(p + 1.0, v + 1.0, p - 1.0, 2.0 * v), (p + 2.0, 1.0, p - 2.0, 1.0)]);
+                storage.push_orderbook(moved.clone());
+                incremental.push_orderbook(moved);
+            }
+            storage.push_tick(tick.clone());
+            incremental.push_tick(&tick);
+            prop_assert_eq!(incremental.len(), storage.ticks.len());
+            match (analyze(&storage), incremental.analyze()) {
+                (Some(scan), Some(fast)) => assert_equivalent(&scan, &fast, level + 1_000.0),
+                (None, None) => {}
+                (scan, fast) => prop_assert!(false, "{:?} vs {:?}", scan, fast),
+            }
+        }
+    }
+
+    #[test]
+    fn matches_time_windows_on_every_push(
+        steps in prop::collection::vec(step(), 1..300),
+        millis in prop::collection::vec(0u64..20_000, 1..4),
+    ) {
+        let windows: Vec<TimeWindow> = millis.into_iter().map(TimeWindow::millis).collect();
+        let mut storage = MarketStorage::new(steps.len());
+        let mut incremental = IncrementalAnalyzer::with_windows(steps.len(), &windows);
+        storage.push_orderbook(book(0, &[(101.0, 1.0, 99.0, 1.0)]));
+        incremental.push_orderbook(book(0, &[(101.0, 1.0, 99.0, 1.0)]));
+
+        for tick in ticks(&steps) {
+            storage.push_tick(tick.clone());
+            incremental.push_tick(&tick);
+            for &window in &windows {
+                match (analyze_window(&storage, window), incremental.analyze_window(window)) {
+                    (Some(scan), Some(fast)) => assert_equivalent(&scan, &fast, 1_000.0),
+                    (None, None) => {}
+                    (scan, fast) => prop_assert!(false, "{:?} vs {:?}", scan, fast),
+                }
+            }
+        }
+        prop_assert_eq!(
+            incremental.analyze_horizons().map(|h| h.windows.len()),
+            analyze_horizons(&storage, &windows).map(|h| h.windows.len())
+        );
+    }
+}
+
+#[test]
+fn needs_two_ticks_and_an_orderbook() {
+    let mut incremental = IncrementalAnalyzer::new(10);
+    let ticks = ticks(&[(0, 100.0, 1.0, true), (10, 101.0, 1.0, false)]);
+    incremental.push_tick(&ticks[0]);
+    incremental.push_orderbook(book(0, &[(101.0, 1.0, 99.0, 1.0)]));
+    assert!(incremental.analyze().is_none());
+    incremental.push_tick(&ticks[1]);
+    assert!(incremental.analyze().is_some());
+    assert!(incremental.analyze_window(TimeWindow::seconds(1)).is_none()); // 유지하지 않는 구간
+}
+
+#[test]
+fn long_runs_do_not_drift() {
+    // 큰 가격에서 수십만 번 더하고 빼도 처음부터 다시 훑은 값과 같아야 함
+    let level = 100_000_000.0f32;
+    let steps: Vec<_> = (0..200_000u64)
+        .map(|i| {
+            (
+                7,
+                level + (i % 97) as f32 * 1_000.0,
+                (i % 13) as f32,
+                i % 3 == 0,
+            )
+        })
+        .collect();
+    let mut storage = MarketStorage::new(50);
+    let mut incremental = IncrementalAnalyzer::new(50);
+    storage.push_orderbook(book(0, &[(level, 1.0, level, 1.0)]));
+    incremental.push_orderbook(book(0, &[(level, 1.0, level, 1.0)]));
+    for tick in ticks(&steps) {
+        storage.push_tick(tick.clone());
+        incremental.push_tick(&tick);
+    }
+    let scan = analyze(&storage).unwrap();
+    let fast = incremental.analyze().unwrap();
+    assert_equivalent(&scan, &fast, level);
+    assert!(
+        (scan.volatility - fast.volatility).abs() < 50.0,
+        "{scan:?} {fast:?}"
+    );
+}
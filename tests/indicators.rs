@@ -0,0 +1,464 @@
+use burn_basics::analyzer::{FeatureSource, TimeWindow};
+use burn_basics::backtest::{BacktestConfig, run_backtest};
+use burn_basics::candles::{BarSpec, CandleBuilder};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::env::EnvConfig;
+use burn_basics::feed::{MarketEvent, MarketFeed, SyntheticConfig, SyntheticFeed};
+use burn_basics::indicators::{
+    Atr, Bar, Bollinger, Ema, IndicatorFeature, IndicatorSet, IndicatorSpec, Macd, Obv, Rsi, Sma,
+    Stochastic,
+};
+use burn_basics::schema::{FeatureSchema, SchemaError};
+use burn_basics::types::B;
+use burn_basics::websocket::TickData;
+
+// StockCharts의 Wilder RSI 예제 종가 (+ 뒤에 이어 붙인 종가)
+const CLOSES: [f64; 30] = [
+    44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+    46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+    44.03, 44.29, 44.86, 44.84,
+];
+
+fn bars() -> Vec<Bar> {
+    CLOSES
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Bar {
+            high: close + 0.3 + 0.05 * (i % 4) as f64,
+            low: close - 0.25 - 0.04 * (i % 3) as f64,
+            close,
+            volume: 100.0 + 10.0 * (i % 5) as f64,
+        })
+        .collect()
+}
+
+fn assert_close(actual: &[Option<f64>], expected: &[f64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        let a = a.unwrap_or_else(|| panic!("{}번째 값이 없음", i));
+        assert!((a - e).abs() < tolerance, "{}번째: {} != {}", i, a, e);
+    }
+}
+
+// === 정의대로 한 번에 계산하는 기준값 (증분 계산과 독립) ===
+
+/// EMA(n): 처음 n개의 단순 평균으로 시작, 이후 ema += 2/(n+1) x (값 - ema)
+fn reference_ema(values: &[Option<f64>], n: usize) -> Vec<Option<f64>> {
+    let alpha = 2.0 / (n as f64 + 1.0);
+    let start = values
+        .iter()
+        .position(Option::is_some)
+        .unwrap_or(values.len());
+    let mut out = vec![None; values.len()];
+    if start + n > values.len() {
+        return out;
+    }
+    let seed: f64 = values[start..start + n].iter().map(|v| v.unwrap()).sum();
+    let mut ema = seed / n as f64;
+    out[start + n - 1] = Some(ema);
+    for i in start + n..values.len() {
+        ema += alpha * (values[i].unwrap() - ema);
+        out[i] = Some(ema);
+    }
+    out
+}
+
+/// (MACD 선, 시그널)
+fn reference_macd(
+    closes: &[f64],
+    fast: usize,
+    slow: usize,
+    signal: usize,
+) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let closes: Vec<Option<f64>> = closes.iter().copied().map(Some).collect();
+    let (fast, slow) = (reference_ema(&closes, fast), reference_ema(&closes, slow));
+    let line: Vec<Option<f64>> = fast
+        .iter()
+        .zip(&slow)
+        .map(|(f, s)| Some(f.as_ref()? - s.as_ref()?))
+        .collect();
+    let signal = reference_ema(&line, signal);
+    (line, signal)
+}
+
+/// Wilder ATR(n): TR 처음 n개 평균으로 시작, 이후 (atr x (n-1) + tr) / n
+fn reference_atr(bars: &[Bar], n: usize) -> Vec<f64> {
+    let tr: Vec<f64> = bars
+        .iter()
+        .enumerate()
+        .map(|(i, b)| match i {
+            0 => b.high - b.low,
+            _ => {
+                let prev = bars[i - 1].close;
+                (b.high - b.low)
+                    .max((b.high - prev).abs())
+                    .max((b.low - prev).abs())
+            }
+        })
+        .collect();
+    let mut out = vec![f64::NAN; bars.len()];
+    let mut atr = tr[..n].iter().sum::<f64>() / n as f64;
+    out[n - 1] = atr;
+    for i in n..bars.len() {
+        atr = (atr * (n - 1) as f64 + tr[i]) / n as f64;
+        out[i] = atr;
+    }
+    out
+}
+
+/// (%K(n), %D(d) = %K의 d개 단순 평균)
+fn reference_stochastic(bars: &[Bar], n: usize, d: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut k = vec![f64::NAN; bars.len()];
+    for i in n - 1..bars.len() {
+        let window = &bars[i + 1 - n..=i];
+        let high = window.iter().map(|b| b.high).fold(f64::MIN, f64::max);
+        let low = window.iter().map(|b| b.low).fold(f64::MAX, f64::min);
+        k[i] = 100.0 * (bars[i].close - low) / (high - low);
+    }
+    let mut dv = vec![f64::NAN; bars.len()];
+    for i in n + d - 2..bars.len() {
+        dv[i] = k[i + 1 - d..=i].iter().sum::<f64>() / d as f64;
+    }
+    (k, dv)
+}
+
+fn reference_obv(bars: &[Bar]) -> Vec<f64> {
+    let mut obv = 0.0;
+    let mut out = vec![0.0];
+    for pair in bars.windows(2) {
+        if pair[1].close > pair[0].close {
+            obv += pair[1].volume;
+        } else if pair[1].close < pair[0].close {
+            obv -= pair[1].volume;
+        }
+        out.push(obv);
+    }
+    out
+}
+
+#[test]
+fn rsi_matches_wilder_reference_values() {
+    let mut rsi = Rsi::new(14);
+    let values: Vec<Option<f64>> = CLOSES.iter().map(|&c| rsi.update(c)).collect();
+    assert!(values[..14].iter().all(Option::is_none)); // 종가 15개부터
+    assert_close(
+        &values[14..20],
+        &[70.46, 66.25, 66.48, 69.35, 66.29, 57.92],
+        0.01,
+    );
+
+    let mut flat = Rsi::new(3);
+    let flat: Vec<Option<f64>> = [1.0; 5].iter().map(|&c| flat.update(c)).collect();
+    assert_eq!(flat[4], Some(50.0));
+    let mut rising = Rsi::new(3);
+    assert_eq!(
+        (1..=5).filter_map(|c| rising.update(c as f64)).last(),
+        Some(100.0)
+    );
+}
+
+#[test]
+fn sma_and_ema_match_reference_values() {
+    let mut sma = Sma::new(10);
+    let mut ema = Ema::new(10);
+    let (smas, emas): (Vec<_>, Vec<_>) = CLOSES
+        .iter()
+        .map(|&c| (sma.update(c), ema.update(c)))
+        .unzip();
+
+    assert!(smas[..9].iter().chain(&emas[..9]).all(Option::is_none));
+    // EMA는 처음 10개의 SMA로 시작
+    assert_close(&smas[9..13], &[44.779, 44.934, 45.128, 45.274], 1e-6);
+    assert_close(&emas[9..13], &[44.779, 44.981, 45.171727, 45.251413], 1e-6);
+    assert_close(&smas[27..], &[45.593, 45.457, 45.377], 1e-6);
+    assert_close(&emas[27..], &[45.309318, 45.227624, 45.157147], 1e-6);
+}
+
+#[test]
+fn macd_line_signal_and_histogram() {
+    // 종가가 1씩 오르면 SMA로 시작한 EMA(n)은 항상 (n-1)/2만큼 뒤처짐
+    // → MACD(2,4) = (t - 0.5) - (t - 1.5) = 1, 시그널도 1, 히스토그램 0
+    let mut macd = Macd::new(2, 4, 3);
+    let values: Vec<_> = (1..=10).map(|c| macd.update(c as f64)).collect();
+    assert!(values[..3].iter().all(Option::is_none)); // 느린 EMA는 종가 4개부터
+    assert_eq!(
+        (values[3].unwrap().signal, values[4].unwrap().signal),
+        (None, None)
+    );
+    for value in &values[5..] {
+        let value = value.unwrap();
+        assert_close(
+            &[Some(value.macd), value.signal, value.histogram],
+            &[1.0, 1.0, 0.0],
+            1e-12,
+        );
+    }
+
+    // 정의대로 한 번에 계산한 값과 비교 (12-26-9 대신 데이터 길이에 맞춘 5-10-4)
+    let mut macd = Macd::new(5, 10, 4);
+    let values: Vec<_> = CLOSES.iter().map(|&c| macd.update(c)).collect();
+    let (line, signal) = reference_macd(&CLOSES, 5, 10, 4);
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(
+            value.map(|v| v.macd).is_some(),
+            line[i].is_some(),
+            "{}번째",
+            i
+        );
+        let Some(value) = value else { continue };
+        assert!((value.macd - line[i].unwrap()).abs() < 1e-9);
+        assert_eq!(value.signal.is_some(), signal[i].is_some());
+        if let (Some(s), Some(h)) = (value.signal, value.histogram) {
+            assert!((s - signal[i].unwrap()).abs() < 1e-9);
+            assert!((h - (value.macd - s)).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn bollinger_bands_use_population_std() {
+    let mut bollinger = Bollinger::new(20, 2.0);
+    let values: Vec<_> = CLOSES.iter().map(|&c| bollinger.update(c)).collect();
+    assert!(values[..19].iter().all(Option::is_none));
+
+    let first = values[19].unwrap();
+    assert_close(
+        &[Some(first.middle), Some(first.upper), Some(first.lower)],
+        &[45.409, 47.115328, 43.702672],
+        1e-5,
+    );
+    let last = values[29].unwrap();
+    assert_close(
+        &[Some(last.middle), Some(last.upper), Some(last.lower)],
+        &[45.708, 47.070198, 44.345802],
+        1e-5,
+    );
+    assert!((last.bandwidth - (last.upper - last.lower) / last.middle).abs() < 1e-12);
+    assert!((last.percent_b - (44.84 - last.lower) / (last.upper - last.lower)).abs() < 1e-12);
+
+    let mut flat = Bollinger::new(3, 2.0);
+    let flat = [5.0; 3]
+        .iter()
+        .filter_map(|&c| flat.update(c))
+        .last()
+        .unwrap();
+    assert_eq!((flat.upper, flat.lower, flat.percent_b), (5.0, 5.0, 0.5));
+}
+
+#[test]
+fn atr_and_stochastic_match_reference_values() {
+    // (고가, 저가, 종가): 세 번째는 전일 종가 위로 갭, 다섯 번째는 큰 갭 상승
+    let hand = [
+        (10.0, 8.0, 9.0),
+        (11.0, 9.0, 10.0),
+        (12.0, 11.0, 11.5),
+        (11.0, 8.0, 9.0),
+        (15.0, 14.0, 14.5),
+    ]
+    .map(|(high, low, close)| Bar {
+        high,
+        low,
+        close,
+        volume: 1.0,
+    });
+
+    // TR = max(고가-저가, |고가-전일종가|, |저가-전일종가|) = 2, 2, 2, 3.5, 6
+    // ATR(3): 처음 3개 평균 2 → (2x2 + 3.5)/3 = 2.5 → (2.5x2 + 6)/3 = 11/3
+    let mut atr = Atr::new(3);
+    let atrs: Vec<_> = hand.iter().map(|b| atr.update(b)).collect();
+    assert_eq!(atrs[..2], [None, None]);
+    assert_close(&atrs[2..], &[2.0, 2.5, 11.0 / 3.0], 1e-12);
+
+    // %K(3) = 100 x (종가 - 최저가) / (최고가 - 최저가): 87.5, 25, 650/7
+    // %D(2) = 최근 %K 2개 평균
+    let mut stochastic = Stochastic::new(3, 2);
+    let values: Vec<_> = hand.iter().map(|b| stochastic.update(b)).collect();
+    assert_eq!(values[..2], [None, None]);
+    let k: Vec<Option<f64>> = values[2..].iter().map(|v| Some(v.unwrap().k)).collect();
+    assert_close(&k, &[87.5, 25.0, 650.0 / 7.0], 1e-12);
+    assert_eq!(values[2].unwrap().d, None);
+    let d: Vec<Option<f64>> = values[3..].iter().map(|v| v.unwrap().d).collect();
+    assert_close(&d, &[56.25, (25.0 + 650.0 / 7.0) / 2.0], 1e-12);
+
+    // 정의대로 한 번에 계산한 값과 비교
+    let bars = bars();
+    let mut atr = Atr::new(14);
+    let atrs: Vec<_> = bars.iter().map(|b| atr.update(b)).collect();
+    assert!(atrs[..13].iter().all(Option::is_none));
+    assert_close(&atrs[13..], &reference_atr(&bars, 14)[13..], 1e-9);
+
+    let mut stochastic = Stochastic::new(14, 3);
+    let values: Vec<_> = bars.iter().map(|b| stochastic.update(b)).collect();
+    assert!(values[..13].iter().all(Option::is_none));
+    let (k, d) = reference_stochastic(&bars, 14, 3);
+    let actual_k: Vec<Option<f64>> = values[13..].iter().map(|v| Some(v.unwrap().k)).collect();
+    assert_close(&actual_k, &k[13..], 1e-9);
+    assert!(values[13..15].iter().all(|v| v.unwrap().d.is_none())); // %D는 %K 3개부터
+    let actual_d: Vec<Option<f64>> = values[15..].iter().map(|v| v.unwrap().d).collect();
+    assert_close(&actual_d, &d[15..], 1e-9);
+}
+
+#[test]
+fn obv_accumulates_signed_volume() {
+    let mut obv = Obv::new();
+    assert_eq!(obv.value(), None);
+    // 첫 봉은 0, 오르면 +거래량, 내리면 -거래량, 같으면 그대로
+    let values: Vec<f64> = [
+        (10.0, 3.0),
+        (11.0, 5.0),
+        (11.0, 7.0),
+        (9.0, 4.0),
+        (12.0, 2.0),
+    ]
+    .iter()
+    .filter_map(|&(close, volume)| {
+        obv.update(&Bar {
+            volume,
+            ..Bar::close(close)
+        })
+    })
+    .collect();
+    assert_eq!(values, [0.0, 5.0, 5.0, 1.0, 3.0]);
+
+    let mut obv = Obv::new();
+    let bars = bars();
+    let values: Vec<f64> = bars.iter().filter_map(|b| obv.update(b)).collect();
+    assert_eq!(values, reference_obv(&bars));
+}
+
+#[test]
+fn feature_names_parse_into_indicators() {
+    let feature = IndicatorFeature::parse("macd_12_26_9:signal@1m").unwrap();
+    assert_eq!(
+        feature.spec,
+        IndicatorSpec::Macd {
+            fast: 12,
+            slow: 26,
+            signal: 9
+        }
+    );
+    assert_eq!(feature.field, "signal");
+    assert_eq!(feature.bar, Some(BarSpec::minutes(1)));
+
+    let bands = IndicatorFeature::parse("bb_20_2.5").unwrap();
+    assert_eq!(bands.spec.name(), "bb_20_2.5");
+    assert_eq!((bands.field, bands.bar), ("middle", None));
+
+    for bad in [
+        "rsi",
+        "rsi_0",
+        "rsi_14:upper",
+        "macd_12_26",
+        "obv@1x",
+        "foo_3",
+    ] {
+        assert!(IndicatorFeature::parse(bad).is_none(), "{}", bad);
+    }
+
+    let schema = FeatureSchema::new(["avg_price", "volatility@10s", "rsi_14@1m", "obv"]).unwrap();
+    assert_eq!(schema.windows(), vec![TimeWindow::seconds(10)]); // 지표의 봉 길이는 분석 구간이 아님
+    assert_eq!(
+        FeatureSchema::new(["rsi_14:upper"]),
+        Err(SchemaError::Unknown("rsi_14:upper".to_string()))
+    );
+}
+
+#[test]
+fn indicator_set_feeds_ticks_and_candles() {
+    let schema =
+        FeatureSchema::new(["avg_price", "rsi_3", "obv", "sma_2@1s", "bb_2_1:upper@1s"]).unwrap();
+    let mut set = IndicatorSet::from_schema(&schema);
+    let mut rsi = Rsi::new(3);
+    let mut obv = Obv::new();
+    let mut sma = Sma::new(2);
+    let mut candles = CandleBuilder::new(BarSpec::seconds(1));
+
+    for i in 0..40u64 {
+        let tick = TickData {
+            code: "KRW-A".to_string(),
+            price: 100.0 + ((i * 7) % 5) as f32,
+            volume: 1.0 + (i % 3) as f32,
+            side: "BID".to_string(),
+            timestamp: i * 300,
+        };
+        set.push_tick(&tick);
+        rsi.update(tick.price as f64);
+        obv.update(&Bar::from(&tick));
+        if let Some(candle) = candles.push(&tick) {
+            sma.update(candle.close as f64);
+        }
+
+        assert_eq!(set.value("rsi_3"), rsi.value().map(|v| v as f32));
+        assert_eq!(set.value("obv"), obv.value().map(|v| v as f32));
+        assert_eq!(set.value("sma_2@1s"), sma.value().map(|v| v as f32));
+    }
+    assert!(set.value("bb_2_1:upper@1s").is_some());
+    assert_eq!(set.value("avg_price"), None); // 지표가 아닌 이름은 분석 결과 쪽에서 찾음
+    assert_eq!(set.value("sma_2@1m"), None); // 스키마에 없는 조합
+}
+
+#[test]
+fn indicator_set_is_warm_once_every_used_output_has_a_value() {
+    assert!(IndicatorSet::default().is_warm());
+
+    // macd 선은 slow개부터, signal은 그 뒤 signal개가 더 필요
+    let schema = FeatureSchema::new(["rsi_3", "macd_2_4_3:signal"]).unwrap();
+    let mut set = IndicatorSet::from_schema(&schema);
+    let mut warm = Vec::new();
+    for (i, &close) in CLOSES.iter().enumerate() {
+        set.push_tick(&TickData {
+            code: "KRW-A".to_string(),
+            price: close as f32,
+            volume: 1.0,
+            side: "BID".to_string(),
+            timestamp: i as u64,
+        });
+        warm.push(set.is_warm());
+    }
+    let first = warm.iter().position(|&w| w).unwrap();
+    assert_eq!(first, 5); // 4번째 종가에 macd, 그 뒤 3개로 signal
+    assert!(warm[first..].iter().all(|&w| w));
+}
+
+#[tokio::test]
+async fn backtest_waits_for_indicator_warm_up_before_deciding() {
+    let mut feed = SyntheticFeed::new(SyntheticConfig {
+        max_events: Some(200),
+        ..SyntheticConfig::default()
+    });
+    let mut events = Vec::new();
+    while let Some(event) = feed.next_event().await {
+        events.push(event);
+    }
+    let ticks_until = |timestamp: u64| {
+        events
+            .iter()
+            .filter(|e| matches!(e, MarketEvent::Tick(_)) && e.timestamp() <= timestamp)
+            .count()
+    };
+    // 첫 의사결정까지 들어온 틱 수와 그때의 rsi 관측값
+    let first_decision = |extra: &[&str]| {
+        let mut names = FeatureSchema::default().names;
+        names.extend(extra.iter().map(|n| n.to_string()));
+        let schema = FeatureSchema::new(names).unwrap();
+        let config = BacktestConfig {
+            env: EnvConfig {
+                schema: schema.clone(),
+                ..EnvConfig::default()
+            },
+            ..BacktestConfig::default()
+        };
+        let model = DqnModel::<B>::with_input(&Default::default(), schema.len());
+        let result = run_backtest(&events, &model, &config);
+        let rsi = schema
+            .index_of("rsi_20")
+            .map(|i| result.samples[0].state[i]);
+        (ticks_until(result.steps[0].timestamp), rsi)
+    };
+
+    let (plain, _) = first_decision(&[]);
+    let (gated, rsi) = first_decision(&["rsi_20"]);
+    assert!(plain < 21);
+    assert_eq!(gated, 21); // rsi_20은 종가 21개부터
+    assert!(rsi.is_some_and(|v| v > 0.0 && v < 100.0));
+}
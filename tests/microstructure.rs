@@ -0,0 +1,98 @@
+mod common;
+
+use burn_basics::analyzer::{
+    MarketStorage, TimeWindow, analyze, analyze_horizons, order_flow_imbalance,
+};
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::schema::FeatureSchema;
+use common::{CODE, TB, book, trade};
+
+fn storage() -> MarketStorage {
+    let mut storage = MarketStorage::new(100);
+    storage.push_orderbook(book(0, &[(101.0, 2.0, 99.0, 4.0)]));
+    storage.push_orderbook(book(0, &[(101.0, 1.0, 100.0, 3.0)]));
+    for t in [
+        trade(CODE, 0, 100.0, 1.0, "BID"),
+        trade(CODE, 500, 102.0, 3.0, "BID"),
+        trade(CODE, 1_000, 98.0, 2.0, "ASK"),
+        trade(CODE, 10_000, 100.0, 4.0, "ASK"),
+    ] {
+        storage.push_tick(t);
+    }
+    storage
+}
+
+#[test]
+fn order_flow_imbalance_follows_level_one_changes() {
+    let prev = book(0, &[(101.0, 2.0, 99.0, 4.0)]);
+    // 매수호가 상승: 새 매수 잔량 전부 +, 매도호가 그대로: 매도 잔량 변화만큼 -
+    assert_eq!(
+        order_flow_imbalance(&prev, &book(0, &[(101.0, 1.0, 100.0, 3.0)])),
+        3.0 + 1.0
+    );
+    // 매도호가 하락: 새 매도 잔량 전부 -
+    assert_eq!(
+        order_flow_imbalance(&prev, &book(0, &[(100.0, 5.0, 99.0, 4.0)])),
+        -5.0
+    );
+    // 가격 그대로: 잔량 변화 (매수 +1, 매도 +1)
+    assert_eq!(
+        order_flow_imbalance(&prev, &book(0, &[(101.0, 3.0, 99.0, 5.0)])),
+        0.0
+    );
+    // 매수호가 하락: 이전 매수 잔량이 빠져나감
+    assert_eq!(
+        order_flow_imbalance(&prev, &book(0, &[(101.0, 2.0, 98.0, 9.0)])),
+        -4.0
+    );
+}
+
+#[test]
+fn microstructure_features_from_ticks_and_book() {
+    let f = analyze(&storage()).unwrap();
+    assert_eq!(f.ofi, 4.0);
+    // (101 x 3 + 100 x 1) / 4: 매수 잔량이 많으면 매도호가 쪽으로 치우침
+    assert_eq!(f.microprice, 100.75);
+    assert_eq!(f.vwap, (100.0 + 306.0 + 196.0 + 400.0) / 10.0);
+    assert_eq!(f.trade_imbalance, (1.0 + 3.0 - 2.0 - 4.0) / 10.0);
+    assert_eq!(f.trade_intensity, 3.0 / 10.0);
+    assert_eq!(f.tick_gap, 9_000.0);
+}
+
+#[test]
+fn single_snapshot_and_zero_volume_fall_back_to_neutral_values() {
+    let mut storage = MarketStorage::new(10);
+    storage.push_orderbook(book(0, &[(101.0, 0.0, 99.0, 0.0)]));
+    storage.push_tick(trade(CODE, 0, 100.0, 0.0, "BID"));
+    storage.push_tick(trade(CODE, 0, 102.0, 0.0, "ASK"));
+    let f = analyze(&storage).unwrap();
+    assert_eq!(f.ofi, 0.0);
+    assert_eq!(f.microprice, 100.0);
+    assert_eq!(f.vwap, f.avg_price);
+    assert_eq!(f.trade_imbalance, 0.0);
+    assert_eq!(f.trade_intensity, 0.0);
+    assert_eq!(f.tick_gap, 0.0);
+}
+
+#[test]
+fn features_are_individually_selectable_per_window() {
+    let schema = FeatureSchema::new([
+        "microprice",
+        "ofi",
+        "vwap@1s",
+        "trade_imbalance@1s",
+        "trade_intensity",
+    ])
+    .unwrap();
+    assert_eq!(schema.windows(), vec![TimeWindow::seconds(1)]);
+
+    let features = analyze_horizons(&storage(), &schema.windows()).unwrap();
+    let config = EnvConfig {
+        schema,
+        ..EnvConfig::default()
+    };
+    let mut env = Env::<TB>::with_config(Default::default(), config);
+    env.update(features);
+    // 1초 구간에는 마지막 틱만 남음
+    assert_eq!(env.observation(), vec![100.75, 4.0, 100.0, -1.0, 0.3]);
+}
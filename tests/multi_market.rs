@@ -0,0 +1,135 @@
+mod common;
+
+use burn::tensor::backend::Backend;
+use burn_basics::agent::Agent;
+use burn_basics::analyzer::{MarketStorage, MultiMarketStorage, analyze};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::env::EnvConfig;
+use burn_basics::feed::{MarketEvent, RecordedFeed};
+use burn_basics::trading_loop::run_trading_loop;
+use burn_basics::types::B;
+use burn_basics::websocket::{OrderBookData, TickData};
+use common::{book, trade};
+use std::collections::HashMap;
+
+const BTC: &str = "KRW-BTC";
+const ETH: &str = "KRW-ETH";
+
+/// code 마켓의 1단계 오더북 (중간값 = mid)
+fn market_book(code: &str, timestamp: u64, mid: f32) -> OrderBookData {
+    OrderBookData {
+        code: code.to_string(),
+        ..book(timestamp, &[(mid + 1.0, 2.0, mid - 1.0, 2.0)])
+    }
+}
+
+/// 두 마켓의 호가/체결이 번갈아 들어오는 이벤트 (BTC는 1000원대, ETH는 100원대)
+fn interleaved() -> Vec<MarketEvent> {
+    let mut events = Vec::new();
+    for i in 0..6u64 {
+        let t = i * 10;
+        events.push(MarketEvent::OrderBook(market_book(BTC, t, 1_000.0)));
+        events.push(MarketEvent::OrderBook(market_book(ETH, t + 1, 100.0)));
+        events.push(MarketEvent::Tick(trade(
+            BTC,
+            t + 2,
+            1_000.0 + i as f32,
+            1.0,
+            "BID",
+        )));
+        events.push(MarketEvent::Tick(trade(
+            ETH,
+            t + 3,
+            100.0 - i as f32,
+            2.0,
+            "ASK",
+        )));
+    }
+    events
+}
+
+fn ticks_of(events: &[MarketEvent], code: &str) -> Vec<TickData> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            MarketEvent::Tick(t) if t.code == code => Some(t.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn storage_routes_each_code_to_its_own_market() {
+    let events = interleaved();
+    let mut markets = MultiMarketStorage::new(100);
+    for event in events.clone() {
+        match event {
+            MarketEvent::Tick(tick) => {
+                markets.push_tick(tick);
+            }
+            MarketEvent::OrderBook(ob) => {
+                markets.push_orderbook(ob);
+            }
+        }
+    }
+
+    let all = markets.analyze_all();
+    assert_eq!(all.len(), 2);
+    for (code, mid) in [(BTC, 1_000.0), (ETH, 100.0)] {
+        let storage = markets.get(code).unwrap();
+        assert!(storage.ticks.iter().all(|t| t.code == code));
+        assert!(storage.orderbooks.iter().all(|ob| ob.code == code));
+
+        // 한 마켓만 따로 넣은 저장소와 같은 결과
+        let mut alone = MarketStorage::new(100);
+        alone.push_orderbook(market_book(code, 0, mid));
+        for tick in ticks_of(&events, code) {
+            alone.push_tick(tick);
+        }
+        let expected = analyze(&alone).unwrap();
+        assert_eq!(all[code].avg_price, expected.avg_price);
+        assert_eq!(all[code].volume_sum, expected.volume_sum);
+        assert_eq!(all[code].price_delta, expected.price_delta);
+        assert_eq!(all[code].spread, 2.0);
+    }
+    assert_eq!(all[BTC].price_delta, 5.0);
+    assert_eq!(all[ETH].price_delta, -5.0);
+}
+
+#[tokio::test]
+async fn trading_loop_keeps_a_separate_env_per_market() {
+    let device = <B as Backend>::Device::default();
+    let events = interleaved();
+    let mut envs = HashMap::new();
+    let replay = run_trading_loop(
+        &mut Agent::with_seed(1.0, 7),
+        &mut DqnModel::<B>::new(&device),
+        &mut envs,
+        &EnvConfig::default(),
+        &mut RecordedFeed::from_events(events.clone()),
+        &device,
+    )
+    .await;
+
+    // 마켓마다 두 번째 체결부터 결정 (분석에 틱 2개 필요)
+    assert_eq!(replay.len(), 2 * 5);
+    assert_eq!(envs.len(), 2);
+    for (code, mid) in [(BTC, 1_000.0), (ETH, 100.0)] {
+        let env = &envs[code];
+        assert_eq!(env.steps, 5);
+        assert_eq!(env.orderbook.as_ref().unwrap().code, code);
+        // 평가금액은 자기 마켓의 체결 시각과 호가로만 기록됨
+        let recorded: Vec<u64> = env
+            .portfolio
+            .equity_history
+            .iter()
+            .map(|p| p.timestamp)
+            .collect();
+        let expected: Vec<u64> = ticks_of(&events, code)[1..]
+            .iter()
+            .map(|t| t.timestamp)
+            .collect();
+        assert_eq!(recorded, expected);
+        assert_eq!(env.mark_price(0.0), mid);
+    }
+}
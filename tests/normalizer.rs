@@ -0,0 +1,213 @@
+use burn::tensor::backend::Backend;
+use burn_basics::agent::Agent;
+use burn_basics::analyzer::MarketFeatures;
+use burn_basics::backtest::{BacktestConfig, run_backtest};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::feed::{MarketEvent, MarketFeed, RecordedFeed, SyntheticConfig, SyntheticFeed};
+use burn_basics::model_saver::{copy_normalizer, load_normalizer, save_model, save_normalizer};
+use burn_basics::normalizer::{FeatureNormalizer, normalize_replay, stats_path};
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::schema::FeatureSchema;
+use burn_basics::trading_loop::run_trading_loop;
+use burn_basics::train_loop::run_training;
+use burn_basics::types::B;
+use std::collections::HashMap;
+use std::fs;
+
+fn temp_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("burn_basics_norm_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+fn data() -> Vec<[f32; 2]> {
+    (0..50)
+        .map(|i| [298.0 + (i % 7) as f32, 6805.0 + 40.0 * (i % 5) as f32])
+        .collect()
+}
+
+fn fed(rows: &[[f32; 2]]) -> FeatureNormalizer {
+    let mut normalizer = FeatureNormalizer::new(2).with_clip(None);
+    for row in rows {
+        normalizer.update(row);
+    }
+    normalizer
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-6 * b.abs().max(1.0), "{a} != {b}");
+}
+
+#[test]
+fn running_statistics_match_batch_mean_and_variance() {
+    let rows = data();
+    let normalizer = fed(&rows);
+    for k in 0..2 {
+        let xs: Vec<f64> = rows.iter().map(|r| r[k] as f64).collect();
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / xs.len() as f64;
+        assert_close(normalizer.mean[k], mean);
+        assert_close(normalizer.variance()[k], var);
+    }
+
+    let z: Vec<f32> = rows.iter().map(|r| normalizer.normalize(r)[1]).collect();
+    let mean = z.iter().sum::<f32>() / z.len() as f32;
+    let var = z.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / z.len() as f32;
+    assert!(
+        mean.abs() < 1e-4 && (var - 1.0).abs() < 1e-3,
+        "{mean} {var}"
+    );
+}
+
+#[test]
+fn merged_statistics_equal_sequential_ones() {
+    let rows = data();
+    let mut merged = fed(&rows[..20]);
+    merged.merge(&fed(&rows[20..]));
+    let sequential = fed(&rows);
+    assert_eq!(merged.count, sequential.count);
+    for k in 0..2 {
+        assert_close(merged.mean[k], sequential.mean[k]);
+        assert_close(merged.m2[k], sequential.m2[k]);
+    }
+}
+
+#[test]
+fn frozen_normalizer_keeps_statistics_and_clips() {
+    let mut normalizer = fed(&data()).with_clip(Some(3.0));
+    normalizer.freeze();
+    let before = normalizer.clone();
+    normalizer.update(&[1e6, -1e6]);
+    assert_eq!(normalizer, before);
+    assert_eq!(normalizer.normalize(&[1e6, -1e6]), vec![3.0, -3.0]);
+
+    normalizer.unfreeze();
+    normalizer.update(&[1e6, -1e6]);
+    assert_eq!(normalizer.count, before.count + 1);
+}
+
+#[test]
+fn constant_features_do_not_blow_up() {
+    let normalizer = fed(&[[1.0, 5.0], [1.0, 5.0], [1.0, 5.0]]);
+    assert_eq!(normalizer.normalize(&[1.0, 5.0]), vec![0.0, 0.0]);
+}
+
+#[test]
+fn env_observation_is_normalized_between_analyze_and_observe() {
+    let schema = FeatureSchema::new(["avg_price", "tick_speed"]).unwrap();
+    let config = EnvConfig {
+        normalizer: Some(FeatureNormalizer::new(schema.len())),
+        schema,
+        ..EnvConfig::default()
+    };
+    let mut env = Env::<B>::with_config(<B as Backend>::Device::default(), config);
+    for [avg_price, tick_speed] in data() {
+        env.update(MarketFeatures {
+            avg_price,
+            tick_speed,
+            ..MarketFeatures::default()
+        });
+    }
+
+    let normalizer = env.normalizer.as_ref().unwrap();
+    assert_eq!(normalizer.count, 50);
+    assert_eq!(env.observation(), normalizer.normalize(&env.features));
+    assert!(env.observation().iter().all(|z| z.abs() < 5.0));
+}
+
+#[test]
+fn statistics_are_saved_next_to_the_model_and_loaded_frozen() {
+    let path = temp_path("model");
+    let normalizer = fed(&data());
+    save_normalizer(&normalizer, &path).unwrap();
+
+    let two = FeatureSchema::new(["avg_price", "tick_speed"]).unwrap();
+    let loaded = load_normalizer(&path, &two).unwrap().unwrap();
+    assert!(loaded.is_frozen());
+    assert_eq!(loaded.mean, normalizer.mean);
+    assert_eq!(
+        loaded.normalize(&[300.0, 6900.0]),
+        normalizer.normalize(&[300.0, 6900.0])
+    );
+
+    assert!(load_normalizer(&path, &FeatureSchema::default()).is_err());
+    assert!(
+        load_normalizer(&temp_path("missing"), &two)
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// 두 마켓의 합성 이벤트 (타임스탬프 순으로 섞임)
+async fn two_markets() -> Vec<MarketEvent> {
+    let mut events = Vec::new();
+    for (seed, code) in [(1, "KRW-A"), (2, "KRW-B")] {
+        let mut feed = SyntheticFeed::new(SyntheticConfig {
+            seed,
+            code: code.to_string(),
+            max_events: Some(300),
+            ..SyntheticConfig::default()
+        });
+        while let Some(event) = feed.next_event().await {
+            events.push(event);
+        }
+    }
+    RecordedFeed::from_events(events).into_events()
+}
+
+#[tokio::test]
+async fn statistics_round_trip_from_collection_through_training_to_backtest() {
+    let device = <B as Backend>::Device::default();
+    let events = two_markets().await;
+    let schema = FeatureSchema::default();
+    let env_config = EnvConfig {
+        schema: schema.clone(),
+        ..EnvConfig::default()
+    };
+
+    // run: 원본 피처로 모은 뒤 모든 마켓을 한 통계로 정규화
+    let mut replay = run_trading_loop(
+        &mut Agent::with_seed(1.0, 3),
+        &mut DqnModel::<B>::new(&device),
+        &mut HashMap::new(),
+        &env_config,
+        &mut RecordedFeed::from_events(events.clone()),
+        &device,
+    )
+    .await;
+    let raw = replay.clone();
+    let stats = normalize_replay(&mut replay, FeatureNormalizer::new(schema.len()));
+    assert_eq!(stats.count, raw.len() as u64);
+    // 첫 샘플도 마지막 통계로 변환됨
+    assert_eq!(replay[0].state, stats.normalize(&raw[0].state));
+    assert_eq!(replay[0].next_state, stats.normalize(&raw[0].next_state));
+
+    let csv = temp_path("round_trip.csv");
+    save_replay_csv(&replay, &schema, &csv);
+    stats.save(stats_path(&csv)).unwrap();
+
+    // train: 모델 옆으로 통계 복사
+    let model_path = temp_path("round_trip_model");
+    let model = run_training(&csv, 2, 16).unwrap();
+    save_model(&model, &schema, &model_path);
+    assert!(copy_normalizer(&csv, &model_path).unwrap());
+    assert!(!copy_normalizer(&temp_path("no_stats.csv"), &model_path).unwrap());
+
+    // backtest: 같은 통계를 freeze해서 쓰므로 같은 틱의 관측이 학습 데이터와 같음
+    let loaded = load_normalizer(&model_path, &schema).unwrap().unwrap();
+    assert_eq!(loaded, stats);
+    let config = BacktestConfig {
+        env: EnvConfig {
+            normalizer: Some(loaded),
+            ..env_config
+        },
+        ..BacktestConfig::default()
+    };
+    let result = run_backtest(&events, &model, &config);
+    assert_eq!(result.portfolios.len(), 2); // 두 마켓 모두 같은 통계
+    assert!(result.samples.len() >= replay.len());
+    for (backtest, collected) in result.samples.iter().zip(&replay) {
+        assert_eq!(backtest.state, collected.state);
+    }
+}
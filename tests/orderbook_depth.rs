@@ -0,0 +1,174 @@
+mod common;
+
+use burn::tensor::{Tensor, backend::Backend};
+use burn_basics::analyzer::{FeatureSource, MarketFeatures, OrderBookDepth, orderbook_depth};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::incremental::IncrementalAnalyzer;
+use burn_basics::model_saver::{load_model, save_model};
+use burn_basics::replay_log::{ReplaySample, stack_observations};
+use burn_basics::schema::{FeatureSchema, UPBIT_DEPTH_LEVELS};
+use burn_basics::train::{TrainConfig, train_samples};
+use burn_basics::types::B;
+use burn_basics::websocket::OrderBookData;
+use std::fs;
+
+/// levels단계 오더북: 중간가 1000, 매도 잔량은 단계마다 1씩 늘고 매수 잔량은 2
+fn book(levels: usize) -> OrderBookData {
+    let levels: Vec<_> = (0..levels)
+        .map(|i| (1001.0 + i as f32, 1.0 + i as f32, 999.0 - i as f32, 2.0))
+        .collect();
+    common::book(0, &levels)
+}
+
+fn q_values(model: &DqnModel<B>, observations: &[Vec<f32>]) -> Vec<f32> {
+    let device = <B as Backend>::Device::default();
+    let input: Tensor<B, 2> =
+        stack_observations(observations.iter().map(|o| o.as_slice()), &device);
+    model.forward(input).into_data().to_vec::<f32>().unwrap()
+}
+
+#[test]
+fn depth_rows_hold_offsets_in_bp_and_size_shares() {
+    // 중간가 1000, 전체 잔량 = (1 + 2 + 3) + 2 x 3 = 12
+    let rows = orderbook_depth(&book(3), 5);
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[0], [10.0, 1.0 / 12.0, -10.0, 2.0 / 12.0]);
+    assert_eq!(rows[2], [30.0, 3.0 / 12.0, -30.0, 2.0 / 12.0]);
+    assert_eq!(rows[3], [0.0; 4]); // 없는 단계
+    let total: f32 = rows.iter().map(|r| r[1] + r[3]).sum();
+    assert!((total - 1.0).abs() < 1e-6);
+
+    let empty = OrderBookData {
+        order_units: Vec::new(),
+        ..book(0)
+    };
+    assert_eq!(orderbook_depth(&empty, 2), vec![[0.0; 4]; 2]);
+}
+
+#[test]
+fn schema_depth_block_is_appended_after_scalars() {
+    let schema = FeatureSchema::default().with_depth(UPBIT_DEPTH_LEVELS);
+    assert_eq!(schema.len(), 12 + 15 * 4);
+    assert_eq!((schema.depth_levels(), schema.scalar_len()), (15, 12));
+    assert_eq!(
+        &schema.names[12..16],
+        [
+            "depth0_ask_offset",
+            "depth0_ask_size",
+            "depth0_bid_offset",
+            "depth0_bid_size"
+        ]
+    );
+    assert_eq!(FeatureSchema::new(schema.names.clone()), Ok(schema.clone()));
+    assert!(FeatureSchema::new(["depth0_mid"]).is_err());
+    assert!(FeatureSchema::new(["depth+1_ask_size"]).is_err());
+
+    // 블록이 맨 뒤에 순서대로 있지 않으면 스칼라 피처로 취급
+    let mut shuffled = schema.clone();
+    shuffled.names.swap(12, 13);
+    assert_eq!(shuffled.depth_levels(), 0);
+    assert_eq!(FeatureSchema::default().depth_levels(), 0);
+
+    // 관측 벡터의 뒷부분을 [levels, 4]로 접으면 orderbook_depth와 같음
+    let ob = book(UPBIT_DEPTH_LEVELS);
+    let depth = OrderBookDepth::new(&ob);
+    let observation = schema.extract(&(MarketFeatures::default(), &depth));
+    let rows: Vec<[f32; 4]> = observation[12..]
+        .chunks(4)
+        .map(|c| [c[0], c[1], c[2], c[3]])
+        .collect();
+    assert_eq!(rows, orderbook_depth(&ob, UPBIT_DEPTH_LEVELS));
+    assert_eq!(depth.value("depth14_ask_offset"), Some(rows[14][0]));
+    assert_eq!(None::<OrderBookDepth>.value("depth0_ask_size"), None);
+}
+
+#[test]
+fn analyzer_keeps_the_depth_block_of_the_latest_orderbook() {
+    let mut market = IncrementalAnalyzer::new(10);
+    assert!(market.depth().is_none());
+    market.push_orderbook(book(UPBIT_DEPTH_LEVELS));
+    market.push_orderbook(book(3));
+    let depth = market.depth().unwrap();
+    assert_eq!(depth.rows, orderbook_depth(&book(3), 3));
+
+    // 오더북보다 깊은 단계는 0, 블록 이름이 아니면 없음
+    let schema = FeatureSchema::default().with_depth(5);
+    let observation = schema.extract(&(MarketFeatures::default(), depth));
+    assert_eq!(observation[12..24], *orderbook_depth(&book(3), 3).concat());
+    assert_eq!(observation[24..], [0.0; 8]);
+    assert_eq!(depth.value("depth4_bid_size"), Some(0.0));
+    assert_eq!(depth.value("spread"), None);
+}
+
+#[test]
+fn conv_model_reads_the_depth_block() {
+    let device = <B as Backend>::Device::default();
+    let schema = FeatureSchema::default().with_depth(UPBIT_DEPTH_LEVELS);
+    let model = DqnModel::<B>::from_schema(&device, &schema);
+
+    let base = schema.extract(&(
+        MarketFeatures::default(),
+        OrderBookDepth::new(&book(UPBIT_DEPTH_LEVELS)),
+    ));
+    let mut thinner = base.clone();
+    for size in thinner[12..].iter_mut().skip(1).step_by(2) {
+        *size *= 0.25;
+    }
+    let q = q_values(&model, &[base.clone(), thinner]);
+    assert_eq!(q.len(), 2 * 3);
+    assert_ne!(&q[..3], &q[3..]); // 스칼라 피처가 같아도 호가창 모양이 다르면 Q가 다름
+
+    // 저장 / 로드 후 같은 출력 (CompactRecorder는 반정밀도라 오차 허용), 블록 크기가 다른 스키마로는 로드 실패
+    let dir = std::env::temp_dir().join(format!("burn_basics_depth_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("model").to_string_lossy().into_owned();
+    save_model(&model, &schema, &path);
+    let loaded = load_model(&path, &schema, &device).unwrap();
+    let reloaded = q_values(&loaded, std::slice::from_ref(&base));
+    let original = q_values(&model, &[base]);
+    assert!(
+        reloaded
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| (a - b).abs() < 1e-2)
+    );
+    assert!(load_model(&path, &FeatureSchema::default().with_depth(5), &device).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn conv_model_trains_on_replay_batches() {
+    let device = <B as Backend>::Device::default();
+    let schema = FeatureSchema::new(["spread", "imbalance"])
+        .unwrap()
+        .with_depth(4);
+    let mut model = DqnModel::<B>::from_schema(&device, &schema);
+
+    let samples: Vec<ReplaySample> = (0..16)
+        .map(|i| {
+            let mut state = schema.extract(&(
+                MarketFeatures::default(),
+                OrderBookDepth::new(&book(1 + i % 4)),
+            ));
+            state[0] = i as f32 * 0.1;
+            ReplaySample {
+                state: state.clone(),
+                action: i % 3,
+                reward: if i % 2 == 0 { 1.0 } else { -1.0 },
+                next_state: state,
+                done: i % 4 == 3,
+            }
+        })
+        .collect();
+    let states: Vec<Vec<f32>> = samples.iter().map(|s| s.state.clone()).collect();
+    let before = q_values(&model, &states);
+
+    let config = TrainConfig {
+        epochs: 3,
+        batch_size: 8,
+        ..TrainConfig::default()
+    };
+    let loss = train_samples(&samples, &mut model, &config);
+    assert!(loss.is_finite());
+    assert_ne!(q_values(&model, &states), before);
+}
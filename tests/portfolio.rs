@@ -0,0 +1,109 @@
+mod common;
+
+use burn_basics::costs::{Fill, Side};
+use burn_basics::env::EnvConfig;
+use burn_basics::portfolio::Portfolio;
+use burn_basics::reward::RewardConfig;
+use common::{tick, unit_config};
+
+fn fill(side: Side, quantity: f32, price: f32, fee: f32) -> Fill {
+    Fill {
+        side,
+        quantity,
+        price,
+        fee,
+        spread_cost: 0.0,
+        slippage: 0.0,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+#[test]
+fn average_entry_includes_buy_fees_and_survives_partial_sells() {
+    let mut portfolio = Portfolio::new(10_000.0);
+    assert_eq!(
+        portfolio.apply_fill(&fill(Side::Buy, 10.0, 100.0, 2.0)),
+        0.0
+    );
+    assert_close(portfolio.avg_entry_price, 100.2); // (1000 + 2) / 10
+    portfolio.apply_fill(&fill(Side::Buy, 10.0, 110.0, 2.0));
+    assert_close(portfolio.avg_entry_price, 105.2); // (1002 + 1102) / 20
+    assert_close(portfolio.cash, 7_896.0);
+    assert_close(portfolio.unrealized_pnl(120.0), (120.0 - 105.2) * 20.0);
+
+    // 일부 매도: 평균 단가는 그대로, 손익은 매도 수수료까지 뺀 금액
+    let pnl = portfolio.apply_fill(&fill(Side::Sell, 5.0, 120.0, 1.0));
+    assert_close(pnl, 599.0 - 105.2 * 5.0);
+    assert_close(portfolio.avg_entry_price, 105.2);
+    assert_close(portfolio.quantity, 15.0);
+    assert_close(portfolio.unrealized_pnl(120.0), (120.0 - 105.2) * 15.0);
+
+    // 보유량보다 많이 팔아도 보유량까지만, 전량 매도하면 평균 단가 초기화
+    let pnl = portfolio.apply_fill(&fill(Side::Sell, 20.0, 100.0, 3.0));
+    assert_close(pnl, 1_497.0 - 105.2 * 15.0);
+    assert!(!portfolio.is_holding());
+    assert_eq!(portfolio.avg_entry_price, 0.0);
+    assert_eq!(portfolio.unrealized_pnl(120.0), 0.0);
+
+    assert_close(portfolio.realized_pnl, 73.0 - 81.0);
+    assert_close(portfolio.fees_paid, 8.0);
+    // 다 팔고 나면 현금 변화 = 확정 손익 (수수료 포함)
+    assert_close(
+        portfolio.cash - portfolio.initial_cash,
+        portfolio.realized_pnl,
+    );
+}
+
+#[test]
+fn max_drawdown_tracks_the_running_peak() {
+    let mut portfolio = Portfolio::new(1_000.0);
+    assert_eq!(portfolio.max_drawdown(), 0.0);
+    portfolio.apply_fill(&fill(Side::Buy, 10.0, 100.0, 0.0));
+    for (t, mark) in [100.0, 120.0, 90.0, 130.0, 117.0].into_iter().enumerate() {
+        portfolio.record(t as u64, mark);
+    }
+    assert_close(portfolio.peak_equity(), 1_300.0);
+    assert_close(portfolio.max_drawdown(), 0.25); // 1200 → 900
+    assert_eq!(portfolio.equity_history.len(), 5);
+    assert_close(portfolio.equity_history[2].equity, 900.0);
+}
+
+#[test]
+fn equity_history_is_capped_without_losing_drawdown() {
+    let mut portfolio = Portfolio::new(1_000.0).with_history_len(2);
+    portfolio.apply_fill(&fill(Side::Buy, 10.0, 100.0, 0.0));
+    for (t, mark) in [120.0, 90.0, 130.0, 117.0].into_iter().enumerate() {
+        portfolio.record(t as u64, mark);
+    }
+    let kept: Vec<u64> = portfolio
+        .equity_history
+        .iter()
+        .map(|p| p.timestamp)
+        .collect();
+    assert_eq!(kept, [2, 3]);
+    assert_close(portfolio.max_drawdown(), 0.25); // 버린 기록의 낙폭도 유지
+    assert_close(portfolio.peak_equity(), 1_300.0);
+
+    let mut silent = Portfolio::new(1_000.0).with_history_len(0);
+    silent.record(0, 100.0);
+    assert!(silent.equity_history.is_empty());
+}
+
+#[test]
+fn equity_change_reward_follows_mark_to_market() {
+    let mut env = common::env(EnvConfig {
+        initial_cash: 1_000.0,
+        reward: RewardConfig::EquityChange,
+        ..unit_config()
+    });
+    assert_eq!(env.step(0, tick(100.0)).1, 0.0); // 수수료 없는 매수는 평가금액 그대로
+    let (_, reward) = env.step(2, tick(110.0));
+    assert!((reward - 0.01).abs() < 1e-6);
+    let (_, reward) = env.step(1, tick(99.0));
+    assert!((reward - (999.0 - 1_010.0) / 1_010.0).abs() < 1e-6);
+    assert_close(env.portfolio.realized_pnl, -1.0);
+    assert_close(env.portfolio.max_drawdown(), 11.0 / 1_010.0);
+}
@@ -0,0 +1,225 @@
+use burn::optim::AdamConfig;
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replaybuffer::{
+    PrioritizedConfig, PrioritizedReplayBuffer, ReplayBuffer, SumTree,
+};
+use burn_basics::train::{TrainConfig, train_step, train_step_prioritized, train_step_weighted};
+use burn_basics::types::B;
+
+fn sample(reward: f32) -> ReplaySample {
+    ReplaySample {
+        state: vec![reward; 12],
+        action: 0,
+        reward,
+        next_state: vec![reward; 12],
+        done: true,
+    }
+}
+
+fn config(capacity: usize) -> PrioritizedConfig {
+    PrioritizedConfig {
+        capacity,
+        alpha_start: 1.0,
+        alpha_end: 1.0,
+        alpha_steps: 0,
+        beta_start: 0.4,
+        beta_end: 1.0,
+        beta_steps: 4,
+        priority_eps: 0.0,
+    }
+}
+
+#[test]
+fn sum_tree_tracks_totals_and_finds_prefix_sums() {
+    let mut tree = SumTree::new(5);
+    for (i, p) in [1.0, 2.0, 3.0, 4.0, 0.0].into_iter().enumerate() {
+        tree.set(i, p);
+    }
+    assert_eq!(tree.total(), 10.0);
+    assert_eq!(tree.find(0.5), 0);
+    assert_eq!(tree.find(1.0), 1);
+    assert_eq!(tree.find(2.9), 1);
+    assert_eq!(tree.find(3.0), 2);
+    assert_eq!(tree.find(9.99), 3);
+
+    tree.set(3, 0.0);
+    assert_eq!(tree.total(), 6.0);
+    assert_eq!(tree.find(5.99), 2);
+}
+
+#[test]
+fn uniform_buffer_samples_without_replacement() {
+    let mut buffer = ReplayBuffer::with_seed(4, 1);
+    for i in 0..6 {
+        buffer.push(sample(i as f32));
+    }
+    assert_eq!(buffer.len(), 4);
+
+    let mut rewards: Vec<f32> = buffer.sample(10).iter().map(|s| s.reward).collect();
+    rewards.sort_by(f32::total_cmp);
+    assert_eq!(rewards, [2.0, 3.0, 4.0, 5.0]); // 오래된 0, 1은 밀려남
+}
+
+#[test]
+fn sampling_is_proportional_to_priority() {
+    let mut buffer = PrioritizedReplayBuffer::with_seed(config(3), 7);
+    for i in 0..3 {
+        buffer.push(sample(i as f32));
+    }
+    buffer.update_priorities(&[0, 1, 2], &[1.0, 3.0, 0.0]);
+
+    let mut counts = [0usize; 3];
+    for _ in 0..500 {
+        for i in buffer.sample(4).indices {
+            counts[i] += 1;
+        }
+    }
+    assert_eq!(counts[2], 0);
+    let ratio = counts[1] as f32 / counts[0] as f32;
+    assert!((ratio - 3.0).abs() < 0.3, "{counts:?}");
+}
+
+#[test]
+fn importance_weights_anneal_towards_full_correction() {
+    let mut buffer = PrioritizedReplayBuffer::with_seed(config(2), 3);
+    buffer.push(sample(0.0));
+    buffer.push(sample(1.0));
+    buffer.update_priorities(&[0, 1], &[1.0, 3.0]); // P = 0.25, 0.75
+
+    let weight_of = |batch: &burn_basics::replaybuffer::PrioritizedBatch, index| {
+        let at = batch.indices.iter().position(|&i| i == index);
+        at.map(|k| batch.weights[k])
+    };
+
+    assert!((buffer.beta() - 0.4).abs() < 1e-6);
+    let mut last = None;
+    for _ in 0..6 {
+        let beta = buffer.beta();
+        let batch = buffer.sample(8);
+        assert!(batch.weights.iter().all(|&w| w > 0.0 && w <= 1.0));
+        if let (Some(rare), Some(common)) = (weight_of(&batch, 0), weight_of(&batch, 1)) {
+            // w ∝ (N·P)^-β, 드문 샘플이 가장 큰 가중치(1)를 가짐
+            assert_eq!(rare, 1.0);
+            let expected = (0.5f32 / 1.5).powf(beta);
+            assert!((common - expected).abs() < 1e-5, "{common} != {expected}");
+        }
+        last = Some(beta);
+    }
+    assert_eq!(last, Some(1.0));
+}
+
+#[test]
+fn annealing_alpha_flattens_or_sharpens_the_distribution() {
+    let buffer = |alpha_start, alpha_end| {
+        let mut buffer = PrioritizedReplayBuffer::with_seed(
+            PrioritizedConfig {
+                alpha_start,
+                alpha_end,
+                alpha_steps: 2,
+                ..config(2)
+            },
+            11,
+        );
+        buffer.push(sample(0.0));
+        buffer.push(sample(1.0));
+        buffer
+    };
+    // 같은 TD 오차 1, 3으로 갱신해도 alpha가 움직이면 P(1)이 달라짐
+    let p_common = |buffer: &mut PrioritizedReplayBuffer| {
+        buffer.update_priorities(&[0, 1], &[1.0, 3.0]);
+        buffer.probability(1)
+    };
+
+    let mut flat = buffer(1.0, 0.0);
+    assert!((p_common(&mut flat) - 0.75).abs() < 1e-9);
+    flat.sample(2);
+    assert_eq!(flat.alpha(), 0.5);
+    let half = 3f64.sqrt() / (1.0 + 3f64.sqrt());
+    assert!((p_common(&mut flat) - half).abs() < 1e-6);
+    flat.sample(2);
+    flat.sample(2);
+    assert_eq!(flat.alpha(), 0.0); // 끝에 도달하면 유지
+    assert!((p_common(&mut flat) - 0.5).abs() < 1e-9);
+
+    let mut sharp = buffer(0.0, 1.0);
+    assert!((p_common(&mut sharp) - 0.5).abs() < 1e-9);
+    sharp.sample(2);
+    sharp.sample(2);
+    assert!((p_common(&mut sharp) - 0.75).abs() < 1e-9);
+
+    // 실제 샘플링 빈도도 평평해짐
+    let mut counts = [0usize; 2];
+    for _ in 0..500 {
+        for i in flat.sample(4).indices {
+            counts[i] += 1;
+        }
+    }
+    let ratio = counts[1] as f32 / counts[0] as f32;
+    assert!((ratio - 1.0).abs() < 0.15, "{counts:?}");
+}
+
+#[test]
+fn new_samples_get_max_priority_and_overwrite_oldest() {
+    let mut buffer = PrioritizedReplayBuffer::with_seed(config(2), 5);
+    buffer.push(sample(0.0));
+    buffer.update_priorities(&[0], &[4.0]);
+    buffer.push(sample(1.0));
+    assert_eq!(buffer.probability(1), 0.5);
+
+    buffer.push(sample(2.0)); // 위치 0을 덮어씀
+    assert_eq!(buffer.len(), 2);
+    let batch = buffer.sample(2);
+    assert!(batch.samples.iter().all(|s| s.reward != 0.0));
+}
+
+#[test]
+fn zero_capacity_keeps_the_latest_sample() {
+    let mut buffer = PrioritizedReplayBuffer::with_seed(config(0), 5);
+    assert_eq!(buffer.config.capacity, 1);
+    buffer.push(sample(0.0));
+    buffer.push(sample(1.0));
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(buffer.sample(1).samples[0].reward, 1.0);
+    assert_eq!(SumTree::new(0).total(), 0.0);
+}
+
+#[test]
+fn unit_weights_match_unweighted_step() {
+    let device = Default::default();
+    let model = DqnModel::<B>::new(&device);
+    let target = model.clone();
+    let batch: Vec<ReplaySample> = (0..4).map(|i| sample(i as f32)).collect();
+    let config = TrainConfig::default();
+
+    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
+    let (_, loss) = train_step(model.clone(), &mut optimizer, &target, &batch, &config);
+    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
+    let output = train_step_weighted(model, &mut optimizer, &target, &batch, &[1.0; 4], &config);
+
+    assert!((loss - output.loss).abs() < 1e-6);
+    assert_eq!(output.td_errors.len(), 4);
+    let mean_sq = output.td_errors.iter().map(|e| e * e).sum::<f32>() / 4.0;
+    assert!((mean_sq - loss).abs() < 1e-5);
+}
+
+#[test]
+fn prioritized_step_updates_priorities_from_td_errors() {
+    let device = Default::default();
+    let model = DqnModel::<B>::new(&device);
+    let target = model.clone();
+    let mut buffer = PrioritizedReplayBuffer::with_seed(config(8), 11);
+    for i in 0..8 {
+        buffer.push(sample(i as f32 * 10.0));
+    }
+    let config = TrainConfig {
+        batch_size: 8,
+        ..TrainConfig::default()
+    };
+
+    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
+    train_step_prioritized(model, &mut optimizer, &target, &mut buffer, &config);
+
+    // 보상이 큰(= TD 오차가 큰) 샘플일수록 더 자주 뽑힘
+    assert!(buffer.probability(7) > buffer.probability(1));
+}
@@ -0,0 +1,182 @@
+use burn_basics::feed::MarketEvent;
+use burn_basics::recorder::{
+    MarketRecorder, RecorderConfig, load_recording, read_recording_file, recording_files, utc_date,
+};
+use burn_basics::websocket::{RawFrame, RawTap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TRADE: &[u8] = include_bytes!("fixtures/upbit/trade.json");
+const SYMBOL: &str = "KRW-BTC";
+
+/// 2024-10-31 00:00:00 UTC
+const DAY_START: u64 = 1_730_332_800_000;
+const DAY_MS: u64 = 86_400_000;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "burn_basics_recorder_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn frame(received_at: u64) -> RawFrame {
+    RawFrame {
+        symbol: SYMBOL.to_string(),
+        received_at,
+        payload: TRADE.to_vec(),
+    }
+}
+
+fn config(dir: &Path, compress: bool, max_file_bytes: Option<u64>) -> RecorderConfig {
+    RecorderConfig {
+        dir: dir.to_path_buf(),
+        compress,
+        max_file_bytes,
+    }
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    recording_files(dir, SYMBOL)
+        .unwrap()
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn utc_date_changes_exactly_at_midnight() {
+    assert_eq!(utc_date(0), "1970-01-01");
+    assert_eq!(utc_date(DAY_MS - 1), "1970-01-01");
+    assert_eq!(utc_date(DAY_MS), "1970-01-02");
+    assert_eq!(utc_date(DAY_START - 1), "2024-10-30");
+    assert_eq!(utc_date(DAY_START), "2024-10-31");
+    // 윤일과 연말
+    assert_eq!(utc_date(1_709_251_199_999), "2024-02-29");
+    assert_eq!(utc_date(1_709_251_200_000), "2024-03-01");
+    assert_eq!(utc_date(1_735_689_599_999), "2024-12-31");
+    assert_eq!(utc_date(1_735_689_600_000), "2025-01-01");
+}
+
+#[test]
+fn frames_rotate_to_new_parts_and_days() {
+    let dir = temp_dir("rotate");
+    let line_bytes = {
+        // 한 줄 크기를 재서 파일당 두 줄이 들어가게
+        let mut recorder = MarketRecorder::new(config(&dir, false, None));
+        recorder.record(&frame(DAY_START)).unwrap();
+        recorder.finish().unwrap();
+        let bytes = fs::read(&recording_files(&dir, SYMBOL).unwrap()[0])
+            .unwrap()
+            .len() as u64;
+        fs::remove_dir_all(&dir).unwrap();
+        bytes
+    };
+
+    let mut recorder = MarketRecorder::new(config(&dir, false, Some(2 * line_bytes)));
+    for i in 0..5 {
+        recorder.record(&frame(DAY_START + i)).unwrap();
+    }
+    recorder.record(&frame(DAY_START + DAY_MS - 1)).unwrap(); // 같은 날 마지막 순간
+    recorder.record(&frame(DAY_START + DAY_MS)).unwrap(); // 다음 날 자정 → 새 날짜, part 0
+    recorder.finish().unwrap();
+
+    assert_eq!(
+        file_names(&dir),
+        [
+            "2024-10-31_000.jsonl",
+            "2024-10-31_001.jsonl",
+            "2024-10-31_002.jsonl",
+            "2024-11-01_000.jsonl",
+        ]
+    );
+    let per_file: Vec<usize> = recording_files(&dir, SYMBOL)
+        .unwrap()
+        .iter()
+        .map(|p| read_recording_file(p).unwrap().len())
+        .collect();
+    assert_eq!(per_file, [2, 2, 2, 1]);
+    assert_eq!(load_recording(&dir, SYMBOL).unwrap().len(), 7);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn gzip_recording_appends_after_restart_mid_day() {
+    let dir = temp_dir("restart");
+    let mut recorder = MarketRecorder::new(config(&dir, true, None));
+    for i in 0..3 {
+        recorder.record(&frame(DAY_START + i)).unwrap();
+    }
+    recorder.finish().unwrap();
+
+    // 재시작: 같은 날 파일에 gzip 멤버가 하나 더 붙음
+    let mut recorder = MarketRecorder::new(config(&dir, true, None));
+    for i in 3..5 {
+        recorder.record(&frame(DAY_START + i)).unwrap();
+    }
+    recorder.finish().unwrap();
+
+    assert_eq!(file_names(&dir), ["2024-10-31_000.jsonl.gz"]);
+    let frames = read_recording_file(&recording_files(&dir, SYMBOL).unwrap()[0]).unwrap();
+    assert_eq!(
+        frames.iter().map(|f| f.received_at).collect::<Vec<_>>(),
+        (0..5).map(|i| DAY_START + i).collect::<Vec<_>>()
+    );
+
+    // 이미 한도를 넘은 파일이면 재시작할 때 다음 part로 (디스크 크기 기준)
+    let size = fs::metadata(&recording_files(&dir, SYMBOL).unwrap()[0])
+        .unwrap()
+        .len();
+    let mut recorder = MarketRecorder::new(config(&dir, true, Some(size)));
+    recorder.record(&frame(DAY_START + 5)).unwrap();
+    recorder.finish().unwrap();
+    assert_eq!(
+        file_names(&dir),
+        ["2024-10-31_000.jsonl.gz", "2024-10-31_001.jsonl.gz"]
+    );
+    assert_eq!(load_recording(&dir, SYMBOL).unwrap().len(), 6);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn load_recording_skips_truncated_last_line() {
+    let dir = temp_dir("truncated");
+    let mut recorder = MarketRecorder::new(config(&dir, false, None));
+    recorder.record(&frame(DAY_START)).unwrap();
+    recorder.record(&frame(DAY_START + 1)).unwrap();
+    recorder.finish().unwrap();
+
+    // 비정상 종료로 마지막 줄이 중간에 잘림
+    let path = &recording_files(&dir, SYMBOL).unwrap()[0];
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(br#"{"received_at":1730332800002,"frame":"{\"type\":\"tra"#)
+        .unwrap();
+
+    assert_eq!(read_recording_file(path).unwrap().len(), 2);
+    let events = load_recording(&dir, SYMBOL).unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| matches!(e, MarketEvent::Tick(_))));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn raw_tap_counts_frames_dropped_by_a_full_recorder() {
+    let (sender, mut receiver) = tokio::sync::mpsc::channel::<RawFrame>(2);
+    let tap = RawTap::new(&sender);
+    for i in 0..5 {
+        tap.send(frame(DAY_START + i)); // 기다리지 않음
+    }
+    assert_eq!(tap.dropped(), 3);
+    assert_eq!(receiver.try_recv().unwrap().received_at, DAY_START);
+
+    // 원래 Sender가 사라지면 녹화 종료: 남은 프레임만 받고 채널이 닫힘
+    drop(sender);
+    tap.send(frame(DAY_START + 5));
+    assert_eq!(tap.dropped(), 3);
+    assert_eq!(receiver.try_recv().unwrap().received_at, DAY_START + 1);
+    assert!(receiver.blocking_recv().is_none());
+}
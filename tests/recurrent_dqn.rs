@@ -0,0 +1,227 @@
+use burn::optim::AdamConfig;
+use burn::tensor::{Tensor, backend::Backend};
+use burn_basics::agent::Agent;
+use burn_basics::dqn_model::QPolicy;
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::feed::{SyntheticConfig, SyntheticFeed};
+use burn_basics::recurrent::{RecurrentDqn, RecurrentPolicy, SequenceBatch, train_sequence_step};
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replaybuffer::{SequenceConfig, SequenceReplayBuffer};
+use burn_basics::schema::FeatureSchema;
+use burn_basics::trading_loop::run_trading_loop;
+use burn_basics::train::TrainConfig;
+use burn_basics::types::B;
+use std::collections::HashMap;
+
+const WIDTH: usize = 4;
+
+fn device() -> <B as Backend>::Device {
+    Default::default()
+}
+
+fn observation(t: usize) -> Vec<f32> {
+    (0..WIDTH)
+        .map(|i| ((t * 7 + i * 3) % 11) as f32 * 0.1)
+        .collect()
+}
+
+fn sample(t: usize, done: bool) -> ReplaySample {
+    ReplaySample {
+        state: observation(t),
+        action: t % 3,
+        reward: t as f32,
+        next_state: observation(t + 1),
+        done,
+    }
+}
+
+fn row(t: usize) -> Tensor<B, 2> {
+    Tensor::<B, 1>::from_floats(observation(t).as_slice(), &device()).reshape([1, WIDTH])
+}
+
+fn values(t: Tensor<B, 2>) -> Vec<f32> {
+    t.into_data().to_vec::<f32>().unwrap()
+}
+
+#[test]
+fn stepping_with_carried_state_matches_the_sequence_forward() {
+    let model = RecurrentDqn::<B>::new(&device(), WIDTH);
+    let steps = 6;
+    let flat: Vec<f32> = (0..steps).flat_map(observation).collect();
+    let sequence =
+        Tensor::<B, 1>::from_floats(flat.as_slice(), &device()).reshape([1, steps, WIDTH]);
+
+    let (q, _) = model.forward_sequence(sequence.clone(), None);
+    assert_eq!(q.dims(), [1, steps, 3]);
+    let q = q.into_data().to_vec::<f32>().unwrap();
+
+    let mut state = None;
+    for t in 0..steps {
+        let (q_step, next) = model.forward_step(row(t), state);
+        state = Some(next);
+        let q_step = values(q_step);
+        for (a, b) in q_step.iter().zip(&q[t * 3..t * 3 + 3]) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+    // 마지막 N개를 0 상태에서 읽은 결과 = 시퀀스의 마지막 스텝
+    assert_eq!(values(model.forward(sequence)).len(), 3);
+}
+
+#[test]
+fn policy_keeps_hidden_state_per_market_until_reset() {
+    let mut policy = RecurrentPolicy::new(RecurrentDqn::<B>::new(&device(), WIDTH));
+    let first = policy.q_values("KRW-A", row(0));
+    let again = policy.q_values("KRW-A", row(0));
+    assert_ne!(first, again); // 같은 관측이라도 앞선 관측을 기억함
+    assert_eq!(policy.q_values("KRW-B", row(0)), first); // 마켓마다 따로
+    assert_eq!(policy.markets(), 2);
+
+    policy.reset("KRW-A");
+    assert_eq!(policy.q_values("KRW-A", row(0)), first);
+}
+
+fn sequence_config() -> SequenceConfig {
+    SequenceConfig {
+        capacity: 100,
+        burn_in: 2,
+        length: 3,
+    }
+}
+
+/// KRW-A: 에피소드 길이 8, 3(구간보다 짧음), 6 / KRW-B: 끝나지 않은 에피소드 10개
+fn filled(seed: u64) -> SequenceReplayBuffer {
+    let mut buffer = SequenceReplayBuffer::with_seed(sequence_config(), seed);
+    for t in 0..17 {
+        buffer.push("KRW-A", sample(t, matches!(t, 7 | 10 | 16)));
+    }
+    buffer.extend("KRW-B", (100..110).map(|t| sample(t, false)));
+    buffer
+}
+
+#[test]
+fn sequence_buffer_samples_contiguous_windows_within_episodes() {
+    let window = sequence_config().window();
+    let mut buffer = filled(7);
+    assert_eq!(buffer.len(), 27);
+    assert_eq!(buffer.sequences(), 4 + 2 + 6);
+    assert!(buffer.is_ready(12) && !buffer.is_ready(13));
+
+    let sequences = buffer.sample(100);
+    assert_eq!(sequences.len(), 12);
+    for sequence in &sequences {
+        assert_eq!(sequence.len(), window);
+        let first = sequence[0].reward as usize;
+        for (k, s) in sequence.iter().enumerate() {
+            assert_eq!(s.reward as usize, first + k); // 연속 구간, 스트림이 섞이지 않음
+        }
+        assert!(sequence[..window - 1].iter().all(|s| !s.done)); // done은 마지막 스텝에만
+    }
+    assert_eq!(filled(3).sample(5), filled(3).sample(5)); // 같은 시드면 같은 구간
+
+    // 스트림마다 capacity개만 유지
+    let mut small = SequenceReplayBuffer::with_seed(
+        SequenceConfig {
+            capacity: 4,
+            ..sequence_config()
+        },
+        1,
+    );
+    small.extend("KRW-A", (0..10).map(|t| sample(t, false)));
+    assert_eq!((small.len(), small.sequences()), (4, 0));
+}
+
+#[test]
+fn sequence_training_ignores_burn_in_steps_in_the_loss() {
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        ..TrainConfig::default()
+    };
+    let mut buffer = filled(11);
+    let sequences = buffer.sample(4);
+    let target = RecurrentDqn::<B>::new(&device(), WIDTH);
+    let model = RecurrentDqn::<B>::new(&device(), WIDTH);
+
+    // burn-in 구간의 보상을 바꿔도 loss는 같음
+    let mut changed = sequences.clone();
+    for sequence in &mut changed {
+        for s in &mut sequence[..2] {
+            s.reward += 100.0;
+        }
+    }
+    let mut optimizer = AdamConfig::new().init();
+    let (_, loss) = train_sequence_step(
+        model.clone(),
+        &mut optimizer,
+        &target,
+        &sequences,
+        2,
+        &config,
+    );
+    let mut optimizer = AdamConfig::new().init();
+    let (_, same) =
+        train_sequence_step(model.clone(), &mut optimizer, &target, &changed, 2, &config);
+    assert_eq!(loss, same);
+
+    // 같은 배치로 반복 학습하면 loss가 줄어듦 (타겟 고정)
+    let mut model = model;
+    let mut optimizer = AdamConfig::new().init();
+    let mut losses = Vec::new();
+    for _ in 0..30 {
+        let (trained, loss) =
+            train_sequence_step(model, &mut optimizer, &target, &sequences, 2, &config);
+        model = trained;
+        losses.push(loss);
+    }
+    assert!(losses.iter().all(|l| l.is_finite()));
+    assert!(losses.windows(2).all(|w| w[1] < w[0]), "{:?}", losses);
+}
+
+#[test]
+#[should_panic(expected = "1번 구간 길이가 첫 구간 길이와 다릅니다")]
+fn uneven_sequences_are_rejected() {
+    let sequences = vec![
+        (0..4).map(|t| sample(t, false)).collect(),
+        (0..3).map(|t| sample(t, false)).collect(),
+    ];
+    SequenceBatch::from_sequences(&sequences, &device());
+}
+
+#[test]
+#[should_panic(expected = "피처 수가 4가 아닌 관측")]
+fn sequences_with_mismatched_widths_are_rejected() {
+    let mut short = sample(1, false);
+    short.next_state.pop();
+    let sequences = vec![vec![sample(0, false), short]];
+    SequenceBatch::from_sequences(&sequences, &device());
+}
+
+#[tokio::test]
+async fn trading_loop_runs_a_recurrent_policy() {
+    let schema = FeatureSchema::default();
+    let env_config = EnvConfig {
+        max_steps: Some(7), // 100개째 샘플이 에피소드 끝이 되지 않게
+        ..EnvConfig::default()
+    };
+    let mut policy = RecurrentPolicy::new(RecurrentDqn::<B>::from_schema(&device(), &schema));
+    let mut envs: HashMap<String, Env<B>> = HashMap::new();
+    let mut feed = SyntheticFeed::new(SyntheticConfig {
+        max_events: Some(400),
+        ..SyntheticConfig::default()
+    });
+
+    let samples = run_trading_loop(
+        &mut Agent::with_seed(0.0, 1),
+        &mut policy,
+        &mut envs,
+        &env_config,
+        &mut feed,
+        &device(),
+    )
+    .await;
+
+    assert!(!samples.is_empty());
+    assert!(samples.iter().all(|s| s.state.len() == schema.len()));
+    assert!(samples.iter().filter(|s| s.done).count() >= 2); // 에피소드마다 은닉 상태 초기화
+    assert_eq!(policy.markets(), 1);
+}
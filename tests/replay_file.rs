@@ -0,0 +1,199 @@
+use burn_basics::replay_file::{
+    REPLAY_VERSION, ReplayHeader, ReplayReader, ReplayWriter, csv_to_replay_file, load_replay_file,
+    replay_file_to_csv, save_replay_file,
+};
+use burn_basics::replay_loader::load_replay_csv;
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::schema::FeatureSchema;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burn_basics_replay_file_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn samples(n: usize) -> Vec<ReplaySample> {
+    (0..n)
+        .map(|i| ReplaySample {
+            state: vec![i as f32, 0.5, -1.0],
+            action: i % 3,
+            reward: i as f32 * 0.1,
+            next_state: vec![i as f32 + 1.0, 0.25, 2.0],
+            done: i % 5 == 4,
+        })
+        .collect()
+}
+
+fn header() -> ReplayHeader {
+    ReplayHeader::new(vec!["a".into(), "b".into(), "c".into()], 3)
+}
+
+#[test]
+fn header_and_samples_round_trip() {
+    let path = temp_path("round_trip.bin");
+    save_replay_file(&path, header(), &samples(7)).unwrap();
+
+    let (loaded_header, loaded) = load_replay_file(&path).unwrap();
+    assert_eq!(loaded_header.version, REPLAY_VERSION);
+    assert_eq!(loaded_header.feature_count, 3);
+    assert_eq!(loaded_header.feature_names, ["a", "b", "c"]);
+    assert_eq!(loaded_header.action_count, 3);
+    assert!(loaded_header.created_at > 0);
+    assert_eq!(loaded, samples(7));
+}
+
+#[test]
+fn reader_returns_chunks() {
+    let path = temp_path("chunks.bin");
+    save_replay_file(&path, header(), &samples(10)).unwrap();
+
+    let mut reader = ReplayReader::open(&path).unwrap();
+    let sizes: Vec<usize> = std::iter::from_fn(|| {
+        let chunk = reader.read_chunk(4).unwrap();
+        (!chunk.is_empty()).then_some(chunk.len())
+    })
+    .collect();
+    assert_eq!(sizes, [4, 4, 2]);
+}
+
+#[test]
+fn append_continues_existing_file() {
+    let path = temp_path("append.bin");
+    let all = samples(6);
+    save_replay_file(&path, header(), &all[..2]).unwrap();
+
+    let mut writer = ReplayWriter::open_or_create(&path, header()).unwrap();
+    writer.write_all(&all[2..]).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.written(), 4);
+
+    assert_eq!(load_replay_file(&path).unwrap().1, all);
+}
+
+#[test]
+fn truncated_tail_is_skipped_and_overwritten_on_append() {
+    let path = temp_path("truncated.bin");
+    let all = samples(4);
+    save_replay_file(&path, header(), &all[..3]).unwrap();
+
+    // 마지막 레코드 중간에서 잘린 것처럼 만듦
+    let len = fs::metadata(&path).unwrap().len();
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len - 3)
+        .unwrap();
+    assert_eq!(load_replay_file(&path).unwrap().1, all[..2]);
+
+    let mut writer = ReplayWriter::append(&path).unwrap();
+    writer.write(&all[3]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    assert_eq!(
+        load_replay_file(&path).unwrap().1,
+        [all[0].clone(), all[1].clone(), all[3].clone()]
+    );
+}
+
+#[test]
+fn corrupt_length_prefixes_are_rejected_without_allocating() {
+    let path = temp_path("corrupt.bin");
+    let all = samples(3);
+    save_replay_file(&path, header(), &all).unwrap();
+    let bytes = fs::read(&path).unwrap();
+    let header_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+
+    // 두 번째 레코드의 길이를 4GiB 가까이로 덮어씀
+    let first = 8 + header_len;
+    let first_len = u32::from_le_bytes(bytes[first..first + 4].try_into().unwrap()) as usize;
+    assert!(first_len <= header().max_record_len());
+    let second = first + 4 + first_len;
+    let mut corrupt = bytes.clone();
+    corrupt[second..second + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&path, &corrupt).unwrap();
+
+    let mut reader = ReplayReader::open(&path).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap(), all[0]);
+    assert_eq!(
+        reader.next().unwrap().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(
+        load_replay_file(&path).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    // 이어 쓰기도 잘린 레코드로 보고 잘라내지 않음
+    assert_eq!(
+        ReplayWriter::append(&path).err().unwrap().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(fs::read(&path).unwrap(), corrupt);
+
+    // 헤더 길이도 마찬가지
+    let mut corrupt = bytes;
+    corrupt[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&path, &corrupt).unwrap();
+    assert_eq!(
+        ReplayReader::open(&path).err().unwrap().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn mismatched_samples_and_headers_are_rejected() {
+    let path = temp_path("mismatch.bin");
+    let mut writer = ReplayWriter::create(&path, header()).unwrap();
+    let mut wrong = samples(1).remove(0);
+    wrong.state.push(0.0);
+    assert_eq!(
+        writer.write(&wrong).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    drop(writer);
+
+    let other = ReplayHeader::unnamed(3, 3);
+    let err = ReplayWriter::open_or_create(&path, other).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let not_replay = temp_path("not_replay.bin");
+    fs::write(&not_replay, b"hello world").unwrap();
+    assert_eq!(
+        ReplayReader::open(&not_replay).err().unwrap().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn converts_between_csv_and_binary() {
+    let csv = temp_path("source.csv");
+    let bin = temp_path("converted.bin");
+    let back = temp_path("back.csv");
+    save_replay_csv(
+        &samples(5),
+        &FeatureSchema::indexed(3),
+        csv.to_str().unwrap(),
+    );
+
+    assert_eq!(
+        csv_to_replay_file(csv.to_str().unwrap(), bin.to_str().unwrap(), 3).unwrap(),
+        5
+    );
+    let (header, loaded) = load_replay_file(&bin).unwrap();
+    assert_eq!(header.feature_count, 3);
+    assert_eq!(loaded, samples(5));
+
+    assert_eq!(
+        replay_file_to_csv(bin.to_str().unwrap(), back.to_str().unwrap()).unwrap(),
+        5
+    );
+    assert_eq!(load_replay_csv(back.to_str().unwrap()).unwrap(), samples(5));
+    assert_eq!(
+        fs::read_to_string(&csv).unwrap(),
+        fs::read_to_string(&back).unwrap()
+    );
+}
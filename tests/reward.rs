@@ -0,0 +1,98 @@
+mod common;
+
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::reward::{RewardConfig, StepOutcome};
+use common::{TB, tick, unit_config};
+
+fn env(reward: RewardConfig) -> Env<TB> {
+    common::env(EnvConfig {
+        initial_cash: 1000.0,
+        reward,
+        ..unit_config()
+    })
+}
+
+fn outcome(equity_before: f64, equity: f64, peak_equity: f64) -> StepOutcome {
+    StepOutcome {
+        action: 2,
+        valid: true,
+        realized_return: None,
+        equity_before,
+        equity,
+        peak_equity,
+        holding: false,
+        holding_steps: 0,
+    }
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+}
+
+#[test]
+fn default_reward_matches_previous_env() {
+    let mut env = env(RewardConfig::default());
+    assert_close(env.step(1, tick(100.0)).1, -0.01); // 빈 포지션 매도
+    assert_close(env.step(0, tick(100.0)).1, 0.0);
+    assert_close(env.step(0, tick(100.0)).1, -0.01); // 중복 매수
+    assert_close(env.step(2, tick(105.0)).1, 0.0);
+    assert_close(env.step(1, tick(110.0)).1, 0.1);
+}
+
+#[test]
+fn log_equity_change_tracks_mark_to_market() {
+    let mut env = env(RewardConfig::LogEquityChange);
+    env.step(0, tick(100.0));
+    let (_, reward) = env.step(2, tick(110.0));
+    assert_close(reward, (1010.0f32 / 1000.0).ln());
+}
+
+#[test]
+fn drawdown_penalty_uses_peak_equity() {
+    let mut f = RewardConfig::DrawdownPenalized { penalty: 0.5 }.build();
+    // 1100 → 990: 수익률 -10%, 낙폭 10%
+    assert_close(f.reward(&outcome(1100.0, 990.0, 1100.0)), -0.1 - 0.05);
+}
+
+#[test]
+fn holding_penalty_starts_after_grace_steps() {
+    let mut env = env(RewardConfig::HoldingTimePenalty {
+        per_step: 0.001,
+        grace_steps: 1,
+    });
+    assert_close(env.step(0, tick(100.0)).1, 0.0);
+    assert_close(env.step(2, tick(100.0)).1, -0.001);
+    assert_close(env.step(1, tick(100.0)).1, 0.0);
+}
+
+#[test]
+fn differential_sharpe_rewards_returns_above_average() {
+    let mut f = RewardConfig::DifferentialSharpe { eta: 0.1 }.build();
+    for i in 0..20 {
+        let r = if i % 2 == 0 { 1.01 } else { 0.995 };
+        f.reward(&outcome(1000.0, 1000.0 * r, 1000.0));
+    }
+    assert!(f.reward(&outcome(1000.0, 1020.0, 1000.0)) > 0.0);
+    assert!(f.reward(&outcome(1000.0, 980.0, 1000.0)) < 0.0);
+
+    f.reset();
+    assert_close(f.reward(&outcome(1000.0, 1020.0, 1000.0)), 0.0);
+}
+
+#[test]
+fn weighted_config_is_parsed_and_summed() {
+    let json = r#"{"kind": "weighted", "terms": [
+        {"weight": 1.0, "reward": {"kind": "equity_change"}},
+        {"weight": 2.0, "reward": {"kind": "realized_return"}}
+    ]}"#;
+    let config: RewardConfig = serde_json::from_str(json).unwrap();
+    let mut f = config.build();
+
+    let mut o = outcome(1000.0, 1050.0, 1050.0);
+    o.realized_return = Some(0.05);
+    assert_close(f.reward(&o), 0.05 + 2.0 * 0.05);
+
+    o.valid = false;
+    o.realized_return = None;
+    assert_close(f.reward(&o), 0.05 - 2.0 * 0.01);
+}
@@ -0,0 +1,148 @@
+use burn::module::{Module, ModuleMapper, ModuleVisitor, ParamId};
+use burn::tensor::{Tensor, backend::Backend};
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::replay_log::{ReplaySample, observation_tensor};
+use burn_basics::target_net::{TargetNetwork, TargetUpdate, polyak_update};
+use burn_basics::train::{TrainConfig, next_state_value, train_samples};
+use burn_basics::types::B;
+
+/// 모델의 모든 파라미터를 한 줄로 펼침
+fn params(model: &DqnModel<B>) -> Vec<f32> {
+    struct Flatten(Vec<f32>);
+    impl ModuleVisitor<B> for Flatten {
+        fn visit_float<const D: usize>(&mut self, _id: ParamId, tensor: &Tensor<B, D>) {
+            let data = tensor.to_data().convert::<f32>();
+            self.0.extend_from_slice(data.as_slice::<f32>().unwrap());
+        }
+    }
+    let mut flatten = Flatten(Vec::new());
+    model.visit(&mut flatten);
+    flatten.0
+}
+
+/// 파라미터 ID는 그대로 두고 값만 delta만큼 이동 (학습된 온라인 모델 흉내)
+fn shifted(model: &DqnModel<B>, delta: f32) -> DqnModel<B> {
+    struct Shift(f32);
+    impl ModuleMapper<B> for Shift {
+        fn map_float<const D: usize>(
+            &mut self,
+            _id: ParamId,
+            tensor: Tensor<B, D>,
+        ) -> Tensor<B, D> {
+            tensor + self.0
+        }
+    }
+    model.clone().map(&mut Shift(delta))
+}
+
+fn state(index: usize) -> Vec<f32> {
+    let mut features = vec![0.0; 12];
+    features[index] = 1.0;
+    features
+}
+
+fn sample(from: usize, action: usize, reward: f32, to: usize, done: bool) -> ReplaySample {
+    ReplaySample {
+        state: state(from),
+        action,
+        reward,
+        next_state: state(to),
+        done,
+    }
+}
+
+/// s0 --a0--> s1 (보상 0), s1 --a0--> 종료 (보상 1), 나머지 행동은 보상 0으로 종료
+/// 최적 Q: Q(s1, a0) = 1, Q(s0, a0) = γ, 나머지는 0
+fn chain_mdp() -> Vec<ReplaySample> {
+    vec![
+        sample(0, 0, 0.0, 1, false),
+        sample(0, 1, 0.0, 2, true),
+        sample(0, 2, 0.0, 2, true),
+        sample(1, 0, 1.0, 2, true),
+        sample(1, 1, 0.0, 2, true),
+        sample(1, 2, 0.0, 2, true),
+    ]
+}
+
+fn q(model: &DqnModel<B>, index: usize) -> Vec<f32> {
+    let data = model
+        .forward(observation_tensor(&state(index), &Default::default()))
+        .to_data()
+        .convert::<f32>();
+    data.as_slice::<f32>().unwrap().to_vec()
+}
+
+fn train_chain(target_update: TargetUpdate, double_dqn: bool) -> DqnModel<B> {
+    B::seed(7);
+    let mut model = DqnModel::<B>::new(&Default::default());
+    let config = TrainConfig {
+        learning_rate: 0.005,
+        epochs: 300,
+        target_update,
+        double_dqn,
+        ..TrainConfig::default()
+    };
+    train_samples(&chain_mdp(), &mut model, &config);
+    model
+}
+
+fn assert_learned_chain(model: &DqnModel<B>) {
+    let (q0, q1) = (q(model, 0), q(model, 1));
+    assert!((q1[0] - 1.0).abs() < 0.1, "Q(s1) = {q1:?}");
+    assert!((q0[0] - 0.9).abs() < 0.1, "Q(s0) = {q0:?}");
+    assert!(q0[1].abs() < 0.1 && q0[2].abs() < 0.1, "Q(s0) = {q0:?}");
+}
+
+#[test]
+fn hard_target_updates_learn_chain_mdp() {
+    assert_learned_chain(&train_chain(TargetUpdate::Hard { every: 6 }, false));
+}
+
+#[test]
+fn polyak_double_dqn_learns_chain_mdp() {
+    assert_learned_chain(&train_chain(TargetUpdate::Polyak { tau: 0.05 }, true));
+}
+
+#[test]
+fn hard_sync_copies_only_every_n_steps() {
+    let device = Default::default();
+    let online = DqnModel::<B>::new(&device);
+    let mut target = TargetNetwork::new(&online, TargetUpdate::Hard { every: 2 });
+    let original = params(&target.model);
+
+    let changed = shifted(&online, 1.0);
+    assert_ne!(params(&changed), original);
+    target.step(&changed);
+    assert_eq!(params(&target.model), original);
+    target.step(&changed);
+    assert_eq!(params(&target.model), params(&changed));
+    assert_eq!(target.steps(), 2);
+}
+
+#[test]
+fn polyak_update_mixes_parameters() {
+    let device = Default::default();
+    let target = DqnModel::<B>::new(&device);
+    let online = shifted(&target, 1.0);
+    let (t, o) = (params(&target), params(&online));
+    assert_ne!(t, o);
+
+    let mixed = params(&polyak_update(target.clone(), &online, 0.25));
+    for ((m, t), o) in mixed.iter().zip(&t).zip(&o) {
+        assert!((m - (0.75 * t + 0.25 * o)).abs() < 1e-6);
+    }
+    assert_eq!(params(&polyak_update(target.clone(), &online, 0.0)), t);
+    assert_eq!(params(&polyak_update(target.clone(), &online, 1.0)), o);
+
+    // 다른 모델(파라미터 ID가 다름)은 섞이지 않음
+    let unrelated = DqnModel::<B>::new(&device);
+    assert_eq!(params(&polyak_update(target.clone(), &unrelated, 0.5)), t);
+}
+
+#[test]
+fn double_dqn_evaluates_online_argmax_with_target() {
+    let online = [0.1, 0.9, 0.5];
+    let target = [2.0, 0.3, 1.0];
+    assert_eq!(next_state_value(&online, &target, false), 2.0);
+    assert_eq!(next_state_value(&online, &target, true), 0.3);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use burn_basics::analyzer::{
+    DEFAULT_WINDOWS, MarketStorage, TimeWindow, analyze, analyze_horizons, analyze_window,
+};
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::schema::{FeatureSchema, SchemaError};
+use common::{CODE, TB, book, trade};
+
+fn storage(ticks: &[(u64, f32, f32)]) -> MarketStorage {
+    fill(MarketStorage::new(1000), ticks)
+}
+
+fn fill(mut storage: MarketStorage, ticks: &[(u64, f32, f32)]) -> MarketStorage {
+    storage.push_orderbook(book(0, &[(101.0, 1.0, 99.0, 3.0)]));
+    for &(t, price, volume) in ticks {
+        storage.push_tick(trade(CODE, t, price, volume, "BID"));
+    }
+    storage
+}
+
+const BURSTY: [(u64, f32, f32); 6] = [
+    (0, 100.0, 1.0),
+    (120_000, 102.0, 1.0),
+    (295_000, 104.0, 1.0),
+    (299_500, 110.0, 5.0),
+    (299_800, 112.0, 5.0),
+    (300_000, 114.0, 5.0),
+];
+
+/// 5분 전부터 조용하다가 마지막 1초에 몰린 시장
+fn bursty() -> MarketStorage {
+    storage(&BURSTY)
+}
+
+#[test]
+fn window_labels_round_trip() {
+    for (label, millis) in [
+        ("500ms", 500),
+        ("1s", 1000),
+        ("10s", 10_000),
+        ("5m", 300_000),
+    ] {
+        let window = TimeWindow::parse(label).unwrap();
+        assert_eq!(window.millis, millis);
+        assert_eq!(window.label(), label);
+    }
+    assert_eq!(TimeWindow::parse("60s").unwrap().label(), "1m");
+    assert_eq!(TimeWindow::parse("10x"), None);
+    assert_eq!(TimeWindow::parse("s"), None);
+}
+
+#[test]
+fn window_selects_ticks_by_timestamp_not_count() {
+    let storage = bursty();
+    let count = |w| storage.window(w).count();
+    assert_eq!(count(TimeWindow::seconds(1)), 3);
+    assert_eq!(count(TimeWindow::seconds(10)), 4);
+    assert_eq!(count(TimeWindow::minutes(5)), 6);
+    assert_eq!(count(TimeWindow::millis(0)), 1); // 최근 틱은 항상 포함
+}
+
+#[test]
+fn windowed_features_only_use_ticks_inside_the_window() {
+    let storage = bursty();
+    let recent = analyze_window(&storage, TimeWindow::seconds(1)).unwrap();
+    assert_eq!(recent.avg_price, 112.0);
+    assert_eq!(recent.price_delta, 4.0);
+    assert_eq!(recent.volume_sum, 15.0);
+    assert_eq!(recent.tick_speed, 250.0);
+    assert_eq!(recent.spread, 2.0); // 호가 피처는 구간과 무관
+
+    let all = analyze_window(&storage, TimeWindow::minutes(5)).unwrap();
+    let base = analyze(&storage).unwrap();
+    assert_eq!(all.avg_price, base.avg_price);
+    assert_eq!(all.volume_sum, 18.0);
+    assert_eq!(all.price_delta, 14.0);
+}
+
+#[test]
+fn windows_cut_off_by_capacity_are_not_analyzed() {
+    // 최근 3틱만 남아서 5분 구간의 앞부분이 빠짐 → 잘린 값 대신 None
+    let short = fill(MarketStorage::new(3), &BURSTY);
+    assert_eq!(short.ticks.len(), 3);
+    assert!(!short.covers(TimeWindow::minutes(5)));
+    assert!(analyze_window(&short, TimeWindow::minutes(5)).is_none());
+    assert!(analyze_horizons(&short, &DEFAULT_WINDOWS).is_none());
+    // 버린 틱이 구간 밖이면 그대로 계산
+    assert_eq!(
+        analyze_window(&short, TimeWindow::seconds(1))
+            .unwrap()
+            .volume_sum,
+        15.0
+    );
+}
+
+#[test]
+fn retention_keeps_the_longest_window_beyond_capacity() {
+    let kept = fill(
+        MarketStorage::new(3).with_retention(TimeWindow::minutes(5)),
+        &BURSTY,
+    );
+    assert_eq!(kept.ticks.len(), 6);
+    let all = analyze_window(&kept, TimeWindow::minutes(5)).unwrap();
+    assert_eq!(all.volume_sum, 18.0);
+    assert_eq!(all.price_delta, 14.0);
+    // 기본 피처는 여전히 최근 capacity개 틱
+    let base = analyze(&kept).unwrap();
+    assert_eq!(base.volume_sum, 15.0);
+    assert_eq!(
+        base.avg_price,
+        analyze(&fill(MarketStorage::new(3), &BURSTY))
+            .unwrap()
+            .avg_price
+    );
+
+    // 구간을 벗어난 틱만 버리므로 retention 구간은 항상 다 덮임
+    let mut kept = kept;
+    kept.push_tick(trade(CODE, 500_000, 115.0, 1.0, "BID"));
+    assert_eq!(kept.ticks.len(), 5); // 0, 120_000 제거
+    assert!(kept.covers(TimeWindow::minutes(5)));
+    assert_eq!(
+        analyze_window(&kept, TimeWindow::minutes(5))
+            .unwrap()
+            .volume_sum,
+        17.0
+    );
+}
+
+#[test]
+fn single_tick_window_has_no_movement() {
+    let storage = storage(&[(0, 100.0, 1.0), (60_000, 105.0, 2.0)]);
+    let quiet = analyze_window(&storage, TimeWindow::seconds(1)).unwrap();
+    assert_eq!(quiet.avg_price, 105.0);
+    assert_eq!(quiet.volume_sum, 2.0);
+    assert_eq!(quiet.price_delta, 0.0);
+    assert_eq!(quiet.volatility, 0.0);
+    assert_eq!(quiet.tick_speed, 0.0);
+}
+
+#[test]
+fn multi_horizon_schema_extracts_windowed_values() {
+    let schema = FeatureSchema::multi_horizon(&DEFAULT_WINDOWS);
+    assert_eq!(schema.len(), 12 + 6 * DEFAULT_WINDOWS.len());
+    assert_eq!(schema.windows(), DEFAULT_WINDOWS.to_vec());
+    assert_eq!(FeatureSchema::new(schema.names.clone()), Ok(schema.clone()));
+
+    let features = analyze_horizons(&bursty(), &schema.windows()).unwrap();
+    let config = EnvConfig {
+        schema: schema.clone(),
+        ..EnvConfig::default()
+    };
+    let mut env = Env::<TB>::with_config(Default::default(), config);
+    env.update(features);
+
+    let value = |name: &str| env.features[schema.index_of(name).unwrap()];
+    assert_eq!(value("volume_sum@1s"), 15.0);
+    assert_eq!(value("volume_sum@10s"), 16.0);
+    assert_eq!(value("volume_sum@5m"), 18.0);
+    assert_eq!(value("volume_sum"), 18.0);
+}
+
+#[test]
+fn malformed_window_names_are_rejected() {
+    assert_eq!(
+        FeatureSchema::new(["volatility@10x"]),
+        Err(SchemaError::Unknown("volatility@10x".to_string()))
+    );
+    assert_eq!(
+        FeatureSchema::new(["rsi@10s"]),
+        Err(SchemaError::Unknown("rsi@10s".to_string()))
+    );
+    let schema =
+        FeatureSchema::new(["volatility@1m", "spread", "volume_sum@60s", "avg_price@1s"]).unwrap();
+    assert_eq!(
+        schema.windows(),
+        vec![TimeWindow::seconds(1), TimeWindow::minutes(1)]
+    );
+}
@@ -0,0 +1,116 @@
+use burn::optim::AdamConfig;
+use burn_basics::dqn_model::DqnModel;
+use burn_basics::replay_log::{ReplaySample, observation_tensor};
+use burn_basics::replay_saver::save_replay_csv;
+use burn_basics::schema::FeatureSchema;
+use burn_basics::train::{
+    Batch, TrainConfig, batch_td_targets, next_state_value, td_target, train_step,
+};
+use burn_basics::train_loop::run_training;
+use burn_basics::types::B;
+
+fn q(model: &DqnModel<B>, state: &[f32]) -> Vec<f32> {
+    let data = model
+        .forward(observation_tensor(state, &Default::default()))
+        .to_data()
+        .convert::<f32>();
+    data.as_slice::<f32>().unwrap().to_vec()
+}
+
+fn samples(n: usize) -> Vec<ReplaySample> {
+    (0..n)
+        .map(|i| {
+            let v = i as f32 / n as f32;
+            ReplaySample {
+                state: vec![v; 12],
+                action: i % 3,
+                reward: v - 0.5,
+                next_state: vec![1.0 - v; 12],
+                done: i % 4 == 0,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn batch_stacks_samples_into_columns() {
+    let batch = Batch::from_samples(&samples(5), &Default::default());
+    assert_eq!(batch.states.dims(), [5, 12]);
+    assert_eq!(batch.next_states.dims(), [5, 12]);
+    assert_eq!(batch.actions.dims(), [5, 1]);
+    assert_eq!(batch.rewards.dims(), [5, 1]);
+
+    let not_done = batch.not_done.to_data().convert::<f32>();
+    assert_eq!(
+        not_done.as_slice::<f32>().unwrap(),
+        [0.0, 1.0, 1.0, 1.0, 0.0]
+    );
+}
+
+#[test]
+fn batch_targets_match_per_sample_targets() {
+    let device = Default::default();
+    let online = DqnModel::<B>::new(&device);
+    let target = DqnModel::<B>::new(&device);
+    let samples = samples(8);
+    let batch = Batch::from_samples(&samples, &device);
+
+    for double_dqn in [false, true] {
+        let config = TrainConfig {
+            double_dqn,
+            ..TrainConfig::default()
+        };
+        let targets = batch_td_targets(&online, &target, &batch, &config);
+        let targets = targets.to_data().convert::<f32>();
+
+        for (sample, &actual) in samples.iter().zip(targets.as_slice::<f32>().unwrap()) {
+            let next = next_state_value(
+                &q(&online, &sample.next_state),
+                &q(&target, &sample.next_state),
+                double_dqn,
+            );
+            let expected = td_target(sample.reward, next, sample.done, config.gamma);
+            assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+        }
+    }
+}
+
+#[test]
+fn train_step_reduces_loss_on_fixed_batch() {
+    let device = Default::default();
+    let mut model = DqnModel::<B>::new(&device);
+    let target = model.clone();
+    let mut optimizer = AdamConfig::new().init::<B, DqnModel<B>>();
+    let config = TrainConfig {
+        learning_rate: 0.01,
+        ..TrainConfig::default()
+    };
+    let batch = samples(16);
+
+    let mut losses = Vec::new();
+    for _ in 0..50 {
+        let (trained, loss) = train_step(model, &mut optimizer, &target, &batch, &config);
+        model = trained;
+        losses.push(loss);
+    }
+    assert!(losses[49] < losses[0] * 0.5, "{losses:?}");
+}
+
+#[test]
+fn run_training_reads_csv_and_returns_model() {
+    let path = std::env::temp_dir().join(format!("burn_basics_train_{}.csv", std::process::id()));
+    let path = path.to_string_lossy().into_owned();
+    save_replay_csv(&samples(10), &FeatureSchema::default(), &path);
+
+    assert!(run_training(&path, 3, 4).is_some());
+    assert!(run_training(&path, 3, 64).is_none());
+}
+
+#[test]
+fn replay_samples_are_plain_data() {
+    let sample = samples(2).remove(1);
+    let json = serde_json::to_string(&sample).unwrap();
+    assert!(json.starts_with(r#"{"state":[0.5,"#), "{json}");
+    let back: ReplaySample = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, sample);
+}
@@ -0,0 +1,410 @@
+use burn::optim::AdamConfig;
+use burn::tensor::{Tensor, backend::Backend};
+use burn_basics::agent::Agent;
+use burn_basics::dqn_model::QPolicy;
+use burn_basics::env::{Env, EnvConfig};
+use burn_basics::feed::{SyntheticConfig, SyntheticFeed};
+use burn_basics::model_saver::{load_transformer, save_transformer, transformer_config_path};
+use burn_basics::normalizer::{DEFAULT_CLIP, FeatureNormalizer, normalize_replay};
+use burn_basics::replay_log::ReplaySample;
+use burn_basics::replaybuffer::{SequenceConfig, SequenceReplayBuffer};
+use burn_basics::schema::FeatureSchema;
+use burn_basics::trading_loop::run_trading_loop;
+use burn_basics::train::{TrainConfig, train_samples, train_step};
+use burn_basics::transformer::{
+    PositionEncoding, TransformerConfig, TransformerDqn, TransformerPolicy, stack_window,
+};
+use burn_basics::types::B;
+use std::collections::HashMap;
+use std::fs;
+
+fn device() -> <B as Backend>::Device {
+    Default::default()
+}
+
+/// 프레임 하나: 피처 4개, 마지막이 tick_gap (ms)
+fn schema() -> FeatureSchema {
+    FeatureSchema::new(["price_delta", "spread", "imbalance", "tick_gap"]).unwrap()
+}
+
+fn config() -> TransformerConfig {
+    TransformerConfig {
+        window: 4,
+        d_model: 16,
+        heads: 2,
+        layers: 2,
+        d_ff: 32,
+        ..TransformerConfig::default()
+    }
+}
+
+fn frame(t: usize) -> Vec<f32> {
+    vec![
+        ((t * 7) % 11) as f32 * 0.1,
+        ((t * 3) % 5) as f32 * 0.2,
+        ((t * 5) % 7) as f32 * 0.1 - 0.3,
+        (100 + (t * 37) % 400) as f32,
+    ]
+}
+
+fn sample(t: usize, done: bool) -> ReplaySample {
+    ReplaySample {
+        state: frame(t),
+        action: t % 3,
+        reward: if t.is_multiple_of(2) { 1.0 } else { -1.0 },
+        next_state: frame(t + 1),
+        done,
+    }
+}
+
+fn q_values(model: &TransformerDqn<B>, windows: &[Vec<f32>]) -> Vec<f32> {
+    let width = windows[0].len();
+    let flat: Vec<f32> = windows.iter().flatten().copied().collect();
+    let input =
+        Tensor::<B, 1>::from_floats(flat.as_slice(), &device()).reshape([windows.len(), width]);
+    model.forward(input).into_data().to_vec::<f32>().unwrap()
+}
+
+/// 에피소드 하나(길이 20)를 window 길이 구간으로 잘라 트랜스포머 샘플로
+fn window_samples() -> Vec<ReplaySample> {
+    let window = config().window;
+    let mut buffer = SequenceReplayBuffer::with_seed(
+        SequenceConfig {
+            capacity: 100,
+            burn_in: 0,
+            length: window,
+        },
+        3,
+    );
+    buffer.extend("KRW-A", (0..20).map(|t| sample(t, t == 19)));
+    let sequences = buffer.sample(buffer.sequences());
+    sequences.iter().map(|s| stack_window(s)).collect()
+}
+
+#[test]
+fn config_is_checked_against_the_frame_schema() {
+    let ok = |config: TransformerConfig, schema: &FeatureSchema| {
+        TransformerDqn::<B>::new(&device(), schema, &config).is_ok()
+    };
+    assert!(ok(config(), &schema()));
+    assert!(ok(config().with_time_delta(), &schema()));
+    assert!(!ok(config().with_time_delta(), &FeatureSchema::default())); // tick_gap 없음
+    assert!(!ok(
+        TransformerConfig {
+            d_model: 15,
+            heads: 3,
+            ..config()
+        },
+        &schema()
+    ));
+    assert!(!ok(
+        TransformerConfig {
+            heads: 3,
+            ..config()
+        },
+        &schema()
+    ));
+    assert!(!ok(
+        TransformerConfig {
+            window: 0,
+            ..config()
+        },
+        &schema()
+    ));
+    assert_eq!(config().input_size(&schema()), 16);
+}
+
+#[test]
+fn attends_over_every_frame_in_the_window() {
+    for config in [config(), config().with_time_delta()] {
+        let model = TransformerDqn::<B>::new(&device(), &schema(), &config).unwrap();
+        let window: Vec<f32> = (0..4).flat_map(frame).collect();
+        let mut older = window.clone();
+        older[0] += 1.0; // 가장 오래된 프레임만 다름
+        let mut slower = window.clone();
+        slower[7] *= 3.0; // 두 번째 프레임의 시간 간격만 다름 (경과 시간 인코딩은 이 값으로만 바뀜)
+
+        let q = q_values(&model, &[window.clone(), older, slower, window]);
+        assert_eq!(q.len(), 4 * 3);
+        assert_ne!(q[..3], q[3..6]);
+        assert_ne!(q[..3], q[6..9]);
+        assert_eq!(q[..3], q[9..]);
+    }
+}
+
+#[test]
+fn stacked_windows_train_with_the_shared_trainer() {
+    let samples = window_samples();
+    assert_eq!(samples.len(), 17); // 20 - 4 + 1
+    let first = samples
+        .iter()
+        .find(|s| s.state[..4] == frame(0)[..])
+        .unwrap();
+    assert_eq!(first.state, (0..4).flat_map(frame).collect::<Vec<_>>());
+    assert_eq!(first.next_state, (1..5).flat_map(frame).collect::<Vec<_>>());
+    assert_eq!((first.action, first.reward), (3 % 3, -1.0));
+    assert_eq!(samples.iter().filter(|s| s.done).count(), 1);
+
+    let model =
+        TransformerDqn::<B>::new(&device(), &schema(), &config().with_time_delta()).unwrap();
+    let target = model.clone();
+    let train_config = TrainConfig {
+        learning_rate: 0.005,
+        ..TrainConfig::default()
+    };
+
+    // 같은 배치로 반복 학습하면 loss가 줄어듦 (타겟 고정)
+    let mut model = model;
+    let mut optimizer = AdamConfig::new().init();
+    let mut losses = Vec::new();
+    for _ in 0..20 {
+        let (trained, loss) = train_step(model, &mut optimizer, &target, &samples, &train_config);
+        model = trained;
+        losses.push(loss);
+    }
+    assert!(losses.iter().all(|l| l.is_finite()));
+    assert!(losses[19] < losses[0], "{:?}", losses);
+
+    let states: Vec<Vec<f32>> = samples.iter().map(|s| s.state.clone()).collect();
+    let before = q_values(&model, &states);
+    let loss = train_samples(
+        &samples,
+        &mut model,
+        &TrainConfig {
+            epochs: 2,
+            batch_size: 8,
+            ..TrainConfig::default()
+        },
+    );
+    assert!(loss.is_finite());
+    assert_ne!(q_values(&model, &states), before);
+}
+
+#[test]
+fn checkpoint_keeps_structure_and_weights() {
+    let config = TransformerConfig {
+        position: PositionEncoding::TimeDelta {
+            feature: "tick_gap".to_string(),
+        },
+        ..config()
+    };
+    let model = TransformerDqn::<B>::new(&device(), &schema(), &config).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("burn_basics_transformer_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("model").to_string_lossy().into_owned();
+    save_transformer(&model, &config, &schema(), &path);
+
+    let (loaded, saved) = load_transformer(&path, &schema(), &device()).unwrap();
+    assert_eq!(saved, config);
+    let window: Vec<f32> = (3..7).flat_map(frame).collect();
+    let original = q_values(&model, std::slice::from_ref(&window));
+    // CompactRecorder는 반정밀도라 오차 허용
+    assert!(
+        q_values(&loaded, &[window])
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| (a - b).abs() < 1e-2)
+    );
+    assert!(load_transformer(&path, &FeatureSchema::default(), &device()).is_err());
+
+    fs::remove_file(transformer_config_path(&path)).unwrap();
+    assert!(load_transformer(&path, &schema(), &device()).is_err()); // 구조 설정 없음
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn policy_keeps_recent_frames_per_market_until_reset() {
+    let model = TransformerDqn::<B>::new(&device(), &schema(), &config()).unwrap();
+    let row =
+        |t: usize| Tensor::<B, 1>::from_floats(frame(t).as_slice(), &device()).reshape([1, 4]);
+    let mut policy = TransformerPolicy::new(model.clone());
+
+    // 처음에는 가장 오래된 관측으로 채움
+    let q = policy.q_values("KRW-A", row(0));
+    assert_eq!(
+        q,
+        q_values(&model, &[[frame(0), frame(0), frame(0), frame(0)].concat()])
+    );
+    for t in 1..6 {
+        policy.q_values("KRW-A", row(t));
+    }
+    let q = policy.q_values("KRW-B", row(9));
+    assert_eq!(q, q_values(&model, &[frame(9).repeat(4)]));
+    assert_eq!(policy.markets(), 2);
+
+    // KRW-A는 최근 4개(3..7)만 봄
+    let q = policy.q_values("KRW-A", row(6));
+    assert_eq!(q, q_values(&model, &[(3..7).flat_map(frame).collect()]));
+
+    policy.reset("KRW-A");
+    assert_eq!(policy.markets(), 1);
+    let q = policy.q_values("KRW-A", row(6));
+    assert_eq!(q, q_values(&model, &[frame(6).repeat(4)]));
+}
+
+#[test]
+fn time_delta_padding_adds_no_elapsed_time() {
+    let model =
+        TransformerDqn::<B>::new(&device(), &schema(), &config().with_time_delta()).unwrap();
+    assert_eq!(model.time_feature(), Some(3));
+    let row =
+        |t: usize| Tensor::<B, 1>::from_floats(frame(t).as_slice(), &device()).reshape([1, 4]);
+    let mut policy = TransformerPolicy::new(model.clone());
+
+    // 채운 프레임은 가장 오래된 관측에서 시간 간격만 0으로 바꾼 것
+    let mut pad = frame(0);
+    pad[3] = 0.0;
+    policy.q_values("KRW-A", row(0));
+    let q = policy.q_values("KRW-A", row(1));
+    let expected = q_values(&model, &[[pad.clone(), pad, frame(0), frame(1)].concat()]);
+    assert_eq!(q, expected);
+    // 원래 간격을 그대로 복사하면 경과 시간이 달라져서 Q값도 다름
+    let copied = q_values(&model, &[[frame(0), frame(0), frame(0), frame(1)].concat()]);
+    assert_ne!(q, copied);
+
+    // 다 차면 채우지 않음
+    policy.q_values("KRW-A", row(2));
+    let q = policy.q_values("KRW-A", row(3));
+    assert_eq!(q, q_values(&model, &[(0..4).flat_map(frame).collect()]));
+}
+
+#[tokio::test]
+async fn trading_loop_runs_a_transformer_policy() {
+    let mut names = FeatureSchema::default().names;
+    names.push("tick_gap".to_string());
+    let schema = FeatureSchema::new(names).unwrap();
+    let env_config = EnvConfig {
+        schema: schema.clone(),
+        max_steps: Some(7), // 100개째 샘플이 에피소드 끝이 되지 않게
+        ..EnvConfig::default()
+    };
+    let model = TransformerDqn::<B>::new(&device(), &schema, &config().with_time_delta()).unwrap();
+    let mut policy = TransformerPolicy::new(model);
+    let mut envs: HashMap<String, Env<B>> = HashMap::new();
+    let mut feed = SyntheticFeed::new(SyntheticConfig {
+        max_events: Some(400),
+        ..SyntheticConfig::default()
+    });
+
+    let samples = run_trading_loop(
+        &mut Agent::with_seed(0.0, 1),
+        &mut policy,
+        &mut envs,
+        &env_config,
+        &mut feed,
+        &device(),
+    )
+    .await;
+
+    assert!(!samples.is_empty());
+    assert!(samples.iter().all(|s| s.state.len() == schema.len()));
+    assert!(samples.iter().any(|s| s.state[12] > 0.0)); // 틱 간격이 관측에 들어감
+    assert_eq!(policy.markets(), 1);
+}
+
+/// 기본 스키마 + tick_gap (마지막 열)
+fn schema_with_gap() -> FeatureSchema {
+    let mut names = FeatureSchema::default().names;
+    names.push("tick_gap".to_string());
+    FeatureSchema::new(names).unwrap()
+}
+
+async fn collect(policy: &mut TransformerPolicy, env_config: &EnvConfig) -> Vec<ReplaySample> {
+    let mut feed = SyntheticFeed::new(SyntheticConfig {
+        max_events: Some(400),
+        ..SyntheticConfig::default()
+    });
+    run_trading_loop(
+        &mut Agent::with_seed(0.0, 1),
+        policy,
+        &mut HashMap::new(),
+        env_config,
+        &mut feed,
+        &device(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn time_delta_gaps_reach_the_model_raw_through_a_normalized_env() {
+    let schema = schema_with_gap();
+    let gap = schema.index_of("tick_gap").unwrap();
+    let time_delta = config().with_time_delta();
+    let model = TransformerDqn::<B>::new(&device(), &schema, &time_delta).unwrap();
+
+    // 정규화기가 tick_gap을 바꾸면 거부, 설정에서 만든 정규화기는 통과
+    let env_config = |normalizer| EnvConfig {
+        schema: schema.clone(),
+        normalizer: Some(normalizer),
+        max_steps: Some(7),
+        ..EnvConfig::default()
+    };
+    let plain = env_config(FeatureNormalizer::new(schema.len()));
+    assert!(model.check_env(&plain).is_err());
+    let learned = TransformerDqn::<B>::new(&device(), &schema, &config()).unwrap();
+    assert!(learned.check_env(&plain).is_ok());
+    let keeps_gap = env_config(time_delta.normalizer(&schema).unwrap());
+    assert!(model.check_env(&keeps_gap).is_ok());
+
+    // Env가 정규화해도 tick_gap 열은 ms 그대로, 나머지는 클리핑 범위 안
+    let samples = collect(&mut TransformerPolicy::new(model), &keeps_gap).await;
+    assert!(!samples.is_empty());
+    assert!(samples.iter().all(|s| s.state[gap] >= 0.0));
+    assert!(samples.iter().any(|s| s.state[gap] > DEFAULT_CLIP));
+    assert!(samples.iter().all(|s| {
+        s.state
+            .iter()
+            .enumerate()
+            .all(|(i, v)| i == gap || v.abs() <= DEFAULT_CLIP)
+    }));
+}
+
+#[tokio::test]
+async fn replay_normalized_for_a_time_delta_policy_keeps_gaps_raw() {
+    let schema = schema_with_gap();
+    let gap = schema.index_of("tick_gap").unwrap();
+    let time_delta = config().with_time_delta();
+    let mut policy =
+        TransformerPolicy::new(TransformerDqn::<B>::new(&device(), &schema, &time_delta).unwrap());
+    assert_eq!(
+        policy.normalizer(&schema),
+        time_delta.normalizer(&schema).unwrap()
+    );
+
+    // 원본으로 모은 뒤 정책의 정규화기로 오프라인 정규화 (main의 run과 같은 순서)
+    let env_config = EnvConfig {
+        schema: schema.clone(),
+        max_steps: Some(7),
+        ..EnvConfig::default()
+    };
+    let raw = collect(&mut policy, &env_config).await;
+    let mut replay = raw.clone();
+    let stats = normalize_replay(&mut replay, policy.normalizer(&schema));
+    assert!(
+        replay.iter().zip(&raw).all(|(n, r)| {
+            n.state[gap] == r.state[gap] && n.next_state[gap] == r.next_state[gap]
+        })
+    );
+    assert!(replay.iter().any(|s| s.state[gap] > DEFAULT_CLIP));
+
+    // 그 통계로 만든 Env 설정은 추론 때도 통과
+    let frozen = EnvConfig {
+        normalizer: Some(stats),
+        ..env_config
+    };
+    assert!(policy.check_env(&frozen).is_ok());
+}
+
+#[tokio::test]
+#[should_panic(expected = "tick_gap")]
+async fn trading_loop_rejects_a_normalizer_that_rescales_time_gaps() {
+    let schema = schema_with_gap();
+    let model = TransformerDqn::<B>::new(&device(), &schema, &config().with_time_delta()).unwrap();
+    let env_config = EnvConfig {
+        normalizer: Some(FeatureNormalizer::new(schema.len())),
+        schema,
+        ..EnvConfig::default()
+    };
+    collect(&mut TransformerPolicy::new(model), &env_config).await;
+}
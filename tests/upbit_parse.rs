@@ -0,0 +1,154 @@
+use burn_basics::feed::MarketEvent;
+use burn_basics::upbit::{
+    AskBid, Change, StreamType, UpbitMessage, UpbitParseError, parse_message,
+};
+use burn_basics::websocket::{OrderBookData, TickData, parse_frame};
+
+const TRADE: &[u8] = include_bytes!("fixtures/upbit/trade.json");
+const TRADE_LEGACY: &[u8] = include_bytes!("fixtures/upbit/trade_legacy.json");
+const TRADE_MISSING_PRICE: &[u8] = include_bytes!("fixtures/upbit/trade_missing_price.json");
+const ORDERBOOK: &[u8] = include_bytes!("fixtures/upbit/orderbook.json");
+const TICKER: &[u8] = include_bytes!("fixtures/upbit/ticker.json");
+const SERVER_ERROR: &[u8] = include_bytes!("fixtures/upbit/server_error.json");
+
+#[test]
+fn parses_trade() {
+    let UpbitMessage::Trade(trade) = parse_message(TRADE).unwrap() else {
+        panic!("expected trade");
+    };
+    assert_eq!(trade.code, "KRW-BTC");
+    assert_eq!(trade.trade_price, 100_473_000.0);
+    assert_eq!(trade.trade_volume, 0.00014208);
+    assert_eq!(trade.ask_bid, AskBid::Bid);
+    assert_eq!(trade.change, Change::Fall);
+    assert_eq!(trade.trade_timestamp, 1_730_336_862_047);
+    assert_eq!(trade.sequential_id, 17_303_368_620_470_000);
+    assert_eq!(trade.best_bid_price, Some(100_465_000.0));
+    assert_eq!(trade.stream_type, StreamType::Realtime);
+}
+
+#[test]
+fn parses_trade_without_best_quotes() {
+    let UpbitMessage::Trade(trade) = parse_message(TRADE_LEGACY).unwrap() else {
+        panic!("expected trade");
+    };
+    assert_eq!(trade.ask_bid, AskBid::Ask);
+    assert_eq!(trade.change, Change::Rise);
+    assert_eq!(trade.best_ask_price, None);
+    assert_eq!(trade.stream_type, StreamType::Snapshot);
+}
+
+#[test]
+fn parses_orderbook() {
+    let UpbitMessage::Orderbook(orderbook) = parse_message(ORDERBOOK).unwrap() else {
+        panic!("expected orderbook");
+    };
+    assert_eq!(orderbook.code, "KRW-BTC");
+    assert_eq!(orderbook.total_ask_size, 4.79158413);
+    assert_eq!(orderbook.total_bid_size, 2.65609625);
+    assert_eq!(orderbook.orderbook_units.len(), 5);
+    assert_eq!(orderbook.orderbook_units[0].ask_price, 100_473_000.0);
+    assert_eq!(orderbook.orderbook_units[4].bid_size, 0.00039826);
+    assert_eq!(orderbook.level, Some(0.0));
+}
+
+#[test]
+fn parses_ticker() {
+    let UpbitMessage::Ticker(ticker) = parse_message(TICKER).unwrap() else {
+        panic!("expected ticker");
+    };
+    assert_eq!(ticker.trade_price, 100_473_000.0);
+    assert_eq!(ticker.signed_change_price, -98_000.0);
+    assert_eq!(ticker.change, Change::Fall);
+    assert_eq!(ticker.acc_trade_volume_24h, 1509.63357245);
+    assert_eq!(ticker.market_state, "ACTIVE");
+    assert_eq!(ticker.is_trading_suspended, Some(false));
+    assert_eq!(ticker.delisting_date, None);
+}
+
+#[test]
+fn projects_trade_to_tick_data() {
+    let Ok(Some(MarketEvent::Tick(tick))) = parse_frame(TRADE) else {
+        panic!("expected tick");
+    };
+    let TickData {
+        code,
+        price,
+        volume,
+        side,
+        timestamp,
+    } = tick;
+    assert_eq!(code, "KRW-BTC");
+    assert_eq!(price, 100_473_000.0);
+    assert_eq!(volume, 0.00014208);
+    assert_eq!(side, "BID");
+    assert_eq!(timestamp, 1_730_336_862_082);
+}
+
+#[test]
+fn projects_orderbook_to_orderbook_data() {
+    let Ok(Some(MarketEvent::OrderBook(OrderBookData {
+        code,
+        timestamp,
+        order_units,
+    }))) = parse_frame(ORDERBOOK)
+    else {
+        panic!("expected orderbook");
+    };
+    assert_eq!(code, "KRW-BTC");
+    assert_eq!(timestamp, 1_730_336_862_468);
+    assert_eq!(order_units.len(), 5);
+    assert_eq!(order_units[1].bid_price, 100_460_000.0);
+    assert_eq!(order_units[1].ask_size, 0.00184644);
+}
+
+#[test]
+fn ticker_is_not_a_market_event() {
+    assert!(matches!(parse_frame(TICKER), Ok(None)));
+}
+
+#[test]
+fn missing_field_is_schema_error() {
+    match parse_message(TRADE_MISSING_PRICE) {
+        Err(UpbitParseError::Schema { kind, source }) => {
+            assert_eq!(kind, "trade");
+            assert!(source.to_string().contains("trade_price"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn server_error_is_reported() {
+    match parse_message(SERVER_ERROR) {
+        Err(UpbitParseError::Server { name, .. }) => assert_eq!(name, "INVALID_PARAM"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_and_untyped_messages_are_errors() {
+    assert!(matches!(
+        parse_message(br#"{"type":"myTrade"}"#),
+        Err(UpbitParseError::UnknownType(kind)) if kind == "myTrade"
+    ));
+    assert!(matches!(
+        parse_message(br#"{"status":"UP"}"#),
+        Err(UpbitParseError::MissingType)
+    ));
+    assert!(matches!(
+        parse_message(b"not json"),
+        Err(UpbitParseError::InvalidJson(_))
+    ));
+}
+
+#[test]
+fn wrong_enum_value_is_schema_error() {
+    let drifted = String::from_utf8(TRADE.to_vec())
+        .unwrap()
+        .replace(r#""ask_bid":"BID""#, r#""ask_bid":"BUY""#);
+    assert!(matches!(
+        parse_message(drifted.as_bytes()),
+        Err(UpbitParseError::Schema { .. })
+    ));
+}
@@ -0,0 +1,194 @@
+use burn_basics::websocket::{
+    ConnectionState, OrderBookData, TickData, WebSocketConfig, upbit_websocket_handler,
+};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, timeout};
+use tokio_tungstenite::{accept_async, tungstenite::protocol::Message};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn test_config(url: String, max_retries: Option<u32>) -> WebSocketConfig {
+    WebSocketConfig {
+        url,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        multiplier: 2.0,
+        jitter: 0.0,
+        max_retries,
+    }
+}
+
+fn trade_frame(price: f64, timestamp: u64) -> Message {
+    let mut body: serde_json::Value =
+        serde_json::from_slice(include_bytes!("fixtures/upbit/trade.json")).unwrap();
+    body["trade_price"] = price.into();
+    body["timestamp"] = timestamp.into();
+    Message::Binary(body.to_string().into_bytes().into())
+}
+
+async fn next<T>(rx: &mut mpsc::Receiver<T>) -> T {
+    timeout(WAIT, rx.recv())
+        .await
+        .expect("timed out")
+        .expect("channel closed")
+}
+
+#[tokio::test]
+async fn reconnects_and_replays_subscription_after_drop() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (sub_tx, mut sub_rx) = mpsc::channel::<String>(4);
+
+    // 서버: 첫 연결은 틱 하나 보내고 강제로 끊고, 두 번째 연결은 유지
+    let server = tokio::spawn(async move {
+        let mut kept = Vec::new();
+        for i in 0..2u64 {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(tcp).await.unwrap();
+            if let Some(Ok(Message::Text(sub))) = ws.next().await {
+                sub_tx.send(sub.to_string()).await.unwrap();
+            }
+            ws.send(trade_frame(100.0 + i as f64, 1_000 + i))
+                .await
+                .unwrap();
+            if i == 0 {
+                drop(ws);
+            } else {
+                kept.push(ws);
+            }
+        }
+        tokio::time::sleep(WAIT).await;
+        drop(kept);
+    });
+
+    let (tick_tx, mut tick_rx) = mpsc::channel::<TickData>(8);
+    let (order_tx, _order_rx) = mpsc::channel::<OrderBookData>(8);
+    let (state_tx, mut state_rx) = mpsc::channel::<ConnectionState>(8);
+    let handler = tokio::spawn(upbit_websocket_handler(
+        vec!["KRW-BTC".to_string(), "KRW-ETH".to_string()],
+        tick_tx,
+        order_tx,
+        test_config(url, None),
+        state_tx,
+        None,
+    ));
+
+    assert_eq!(next(&mut state_rx).await, ConnectionState::Connected);
+    let tick = next(&mut tick_rx).await;
+    assert_eq!((tick.code.as_str(), tick.price), ("KRW-BTC", 100.0));
+    assert_eq!(
+        next(&mut state_rx).await,
+        ConnectionState::Reconnecting {
+            attempt: 1,
+            delay: Duration::from_millis(10)
+        }
+    );
+    assert_eq!(next(&mut state_rx).await, ConnectionState::Connected);
+    assert_eq!(next(&mut tick_rx).await.price, 101.0);
+
+    let first = next(&mut sub_rx).await;
+    let second = next(&mut sub_rx).await;
+    assert_eq!(first, second);
+    assert!(first.contains("KRW-BTC") && first.contains("KRW-ETH"));
+
+    handler.abort();
+    server.abort();
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    // 바인딩 후 바로 닫아서 접속이 거부되는 주소를 만든다
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let (tick_tx, _tick_rx) = mpsc::channel::<TickData>(8);
+    let (order_tx, _order_rx) = mpsc::channel::<OrderBookData>(8);
+    let (state_tx, mut state_rx) = mpsc::channel::<ConnectionState>(8);
+    let handler = tokio::spawn(upbit_websocket_handler(
+        vec!["KRW-BTC".to_string(), "KRW-ETH".to_string()],
+        tick_tx,
+        order_tx,
+        test_config(url, Some(2)),
+        state_tx,
+        None,
+    ));
+
+    assert_eq!(
+        next(&mut state_rx).await,
+        ConnectionState::Reconnecting {
+            attempt: 1,
+            delay: Duration::from_millis(10)
+        }
+    );
+    assert_eq!(
+        next(&mut state_rx).await,
+        ConnectionState::Reconnecting {
+            attempt: 2,
+            delay: Duration::from_millis(20)
+        }
+    );
+    assert_eq!(
+        next(&mut state_rx).await,
+        ConnectionState::GaveUp { retries: 2 }
+    );
+    timeout(WAIT, handler).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn stops_when_receivers_are_dropped() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    let server = tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(tcp).await.unwrap();
+        let _ = ws.next().await;
+        for i in 0..10 {
+            if ws.send(trade_frame(100.0, i)).await.is_err() {
+                break;
+            }
+        }
+        tokio::time::sleep(WAIT).await;
+    });
+
+    let (tick_tx, tick_rx) = mpsc::channel::<TickData>(1);
+    let (order_tx, _order_rx) = mpsc::channel::<OrderBookData>(1);
+    let (state_tx, mut state_rx) = mpsc::channel::<ConnectionState>(8);
+    drop(tick_rx);
+    let handler = tokio::spawn(upbit_websocket_handler(
+        vec!["KRW-BTC".to_string(), "KRW-ETH".to_string()],
+        tick_tx,
+        order_tx,
+        test_config(url, None),
+        state_tx,
+        None,
+    ));
+
+    assert_eq!(next(&mut state_rx).await, ConnectionState::Connected);
+    timeout(WAIT, handler).await.unwrap().unwrap();
+    server.abort();
+}
+
+#[test]
+fn backoff_grows_exponentially_and_is_capped() {
+    let config = test_config(String::new(), None);
+    let delays: Vec<u128> = (1..=5)
+        .map(|a| config.backoff_delay(a).as_millis())
+        .collect();
+    assert_eq!(delays, vec![10, 20, 40, 40, 40]);
+}
+
+#[test]
+fn backoff_jitter_stays_within_bounds() {
+    let config = WebSocketConfig {
+        jitter: 0.5,
+        ..test_config(String::new(), None)
+    };
+    for _ in 0..100 {
+        let delay = config.backoff_delay(2).as_secs_f64();
+        assert!((0.010..=0.030).contains(&delay), "delay {delay}");
+    }
+}